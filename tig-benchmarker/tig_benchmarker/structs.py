@@ -137,12 +137,26 @@ class Proof(FromDict):
 @dataclass
 class FraudState(FromDict):
     block_confirmed: int
+    status: Optional[str] # Optional for backwards compatability
+    block_resolved: Optional[int] # Optional for backwards compatability
+
+@dataclass
+class FraudAppeal(FromDict):
+    verification_error: Optional[str]
+
+@dataclass
+class FraudEvidence(FromDict):
+    leaf: OutputMetaData
+    branch: MerkleBranch
+    output_data: OutputData
 
 @dataclass
 class Fraud(FromDict):
     benchmark_id: str
     state: FraudState
     allegation: Optional[str]
+    appeal: Optional[FraudAppeal]
+    challenger_id: Optional[str] # Optional for backwards compatability
 
 @dataclass
 class BlockDetails(FromDict):
@@ -163,6 +177,7 @@ class BlockDetails(FromDict):
     num_active_algorithms: Optional[int] # Optional for backwards compatability
     num_active_benchmarks: Optional[int] # Optional for backwards compatability
     num_active_players: Optional[int] # Optional for backwards compatability
+    state_root: Optional[MerkleHash] # Optional for backwards compatability
 
 @dataclass
 class BlockData(FromDict):
@@ -230,11 +245,13 @@ class PlayerBlockData(FromDict):
     influence: Optional[PreciseNumber]
     reward: Optional[PreciseNumber]
     round_earnings: PreciseNumber
+    fraud_penalty: Optional[PreciseNumber] # Optional for backwards compatability
 
 @dataclass
 class PlayerState(FromDict):
     total_fees_paid: PreciseNumber
     available_fee_balance: PreciseNumber
+    total_fraud_bounties: Optional[PreciseNumber] # Optional for backwards compatability
 
 @dataclass
 class Player(FromDict):
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+/// Why a solution failed verification. Returned by every challenge's `evaluate_solution` and
+/// `verify_solution` wrapped in an `anyhow::Error`, so callers can recover it with
+/// `downcast_ref::<VerificationError>()`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum VerificationError {
+    /// Solution could not be parsed into the challenge's solution type
+    InvalidFormat {
+        msg: String,
+    },
+    /// Solution has the wrong number of entries, e.g. variables or indexes
+    WrongLength {
+        expected: usize,
+        actual: usize,
+    },
+    OutOfBounds {
+        index: usize,
+        len: usize,
+    },
+    DuplicateIndex {
+        index: usize,
+    },
+    /// Total weight of the selected items exceeds the knapsack's max weight
+    WeightExceeded {
+        weight: u32,
+        max_weight: u32,
+    },
+    /// Total demand on a route exceeds the vehicle's capacity
+    CapacityExceeded {
+        route: usize,
+        load: i32,
+        capacity: i32,
+    },
+    /// Route does not start and end at the depot, or visits no other node
+    InvalidRoute {
+        route: usize,
+    },
+    UnvisitedNode {
+        node: usize,
+    },
+    UnsatisfiedClause {
+        clause: usize,
+    },
+    /// Solution is well formed, but its objective does not reach the threshold
+    BelowThreshold {
+        metric: String,
+        achieved: f64,
+        required: f64,
+    },
+    /// Reason given by a verifier outside this crate
+    Other {
+        msg: String,
+    },
+}
+
+impl VerificationError {
+    /// Whether the solution is malformed, rather than just not good enough. Malformed solutions
+    /// cannot be graded with `evaluate_solution`
+    pub fn is_malformed(&self) -> bool {
+        !matches!(
+            self,
+            VerificationError::BelowThreshold { .. } | VerificationError::Other { .. }
+        )
+    }
+
+    /// Recovers the reason from an error returned by a verifier, falling back to `Other`
+    pub fn from_anyhow(e: &anyhow::Error) -> Self {
+        match e.downcast_ref::<VerificationError>() {
+            Some(e) => e.clone(),
+            None => VerificationError::Other { msg: e.to_string() },
+        }
+    }
+}
+
+impl std::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerificationError::InvalidFormat { msg } => {
+                write!(f, "Failed to parse solution: {}", msg)
+            }
+            VerificationError::WrongLength { expected, actual } => write!(
+                f,
+                "Invalid solution length. Expected: {}, Actual: {}",
+                expected, actual
+            ),
+            VerificationError::OutOfBounds { index, len } => write!(
+                f,
+                "Index ({}) is out of bounds. Expected: less than {}",
+                index, len
+            ),
+            VerificationError::DuplicateIndex { index } => {
+                write!(f, "Index ({}) is selected more than once", index)
+            }
+            VerificationError::WeightExceeded { weight, max_weight } => write!(
+                f,
+                "Total weight ({}) exceeded max weight ({})",
+                weight, max_weight
+            ),
+            VerificationError::CapacityExceeded {
+                route,
+                load,
+                capacity,
+            } => write!(
+                f,
+                "Total demand on route {} ({}) exceeds max capacity ({})",
+                route, load, capacity
+            ),
+            VerificationError::InvalidRoute { route } => write!(
+                f,
+                "Route {} must start and end at node 0 (the depot), and visit at least one non-depot node",
+                route
+            ),
+            VerificationError::UnvisitedNode { node } => {
+                write!(f, "Node {} is not visited. All nodes must be visited", node)
+            }
+            VerificationError::UnsatisfiedClause { clause } => {
+                write!(f, "Clause '{}' not satisfied", clause)
+            }
+            VerificationError::BelowThreshold {
+                metric,
+                achieved,
+                required,
+            } => write!(
+                f,
+                "{} ({}) does not reach threshold ({})",
+                metric, achieved, required
+            ),
+            VerificationError::Other { msg } => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for VerificationError {}
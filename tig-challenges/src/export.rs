@@ -0,0 +1,211 @@
+use crate::{knapsack, satisfiability, vector_search, vehicle_routing};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Standard file formats that generated instances can be written in, so they can be given to
+/// external solvers
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// DIMACS CNF, for satisfiability
+    Dimacs,
+    /// CVRPLIB (TSPLIB with demands), for vehicle_routing
+    Cvrplib,
+    /// The QKP text format of Billionnet and Soutif's instances, for knapsack
+    Qkp,
+    /// fvecs database and query vectors, and ivecs nearest neighbours, for vector_search
+    Fvecs,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Dimacs,
+        ExportFormat::Cvrplib,
+        ExportFormat::Qkp,
+        ExportFormat::Fvecs,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Dimacs => "dimacs",
+            ExportFormat::Cvrplib => "cvrplib",
+            ExportFormat::Qkp => "qkp",
+            ExportFormat::Fvecs => "fvecs",
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ExportFormat::ALL
+            .into_iter()
+            .find(|format| format.as_str() == s)
+            .ok_or_else(|| anyhow!("Unknown export format '{}'", s))
+    }
+}
+
+/// A file making up an exported instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedFile {
+    /// e.g. `cnf`, or `base.fvecs` when a format is split over several files
+    pub extension: String,
+    pub data: Vec<u8>,
+}
+
+impl ExportedFile {
+    fn new(extension: &str, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            extension: extension.to_string(),
+            data: data.into(),
+        }
+    }
+}
+
+pub(crate) fn unsupported(challenge: &str, format: ExportFormat) -> anyhow::Error {
+    anyhow!("{} instances cannot be exported as {}", challenge, format)
+}
+
+/// DIMACS CNF. Literals are already 1-indexed and signed, as DIMACS expects
+pub fn to_dimacs(challenge: &satisfiability::Challenge) -> ExportedFile {
+    let mut out = String::new();
+    writeln!(out, "c TIG satisfiability instance").unwrap();
+    writeln!(out, "c seed {}", hex(&challenge.seed)).unwrap();
+    writeln!(
+        out,
+        "p cnf {} {}",
+        challenge.difficulty.num_variables,
+        challenge.clauses.len()
+    )
+    .unwrap();
+    for clause in challenge.clauses.iter() {
+        for literal in clause.iter() {
+            write!(out, "{} ", literal).unwrap();
+        }
+        writeln!(out, "0").unwrap();
+    }
+    ExportedFile::new("cnf", out)
+}
+
+/// CVRPLIB with an explicit distance matrix, as instances have no coordinates. Node 0 is the
+/// depot, and nodes are renumbered from 1 as CVRPLIB expects. The max total distance a solution
+/// has to reach is given in the comment
+pub fn to_cvrplib(challenge: &vehicle_routing::Challenge) -> ExportedFile {
+    let num_nodes = challenge.demands.len();
+    let mut out = String::new();
+    writeln!(out, "NAME : tig-c002-{}", hex(&challenge.seed)).unwrap();
+    writeln!(
+        out,
+        "COMMENT : TIG vehicle_routing instance, max total distance {}",
+        challenge.max_total_distance
+    )
+    .unwrap();
+    writeln!(out, "TYPE : CVRP").unwrap();
+    writeln!(out, "DIMENSION : {}", num_nodes).unwrap();
+    writeln!(out, "EDGE_WEIGHT_TYPE : EXPLICIT").unwrap();
+    writeln!(out, "EDGE_WEIGHT_FORMAT : FULL_MATRIX").unwrap();
+    writeln!(out, "CAPACITY : {}", challenge.max_capacity).unwrap();
+    writeln!(out, "EDGE_WEIGHT_SECTION").unwrap();
+    for row in challenge.distance_matrix.iter() {
+        writeln!(out, "{}", join(row)).unwrap();
+    }
+    writeln!(out, "DEMAND_SECTION").unwrap();
+    for (node, demand) in challenge.demands.iter().enumerate() {
+        writeln!(out, "{} {}", node + 1, demand).unwrap();
+    }
+    writeln!(out, "DEPOT_SECTION").unwrap();
+    writeln!(out, "1").unwrap();
+    writeln!(out, "-1").unwrap();
+    writeln!(out, "EOF").unwrap();
+    ExportedFile::new("vrp", out)
+}
+
+/// Billionnet and Soutif's QKP format: a name, the number of items, the linear values, the
+/// upper triangle of `interaction_values` one row per line, a blank line, `0` (a `<=`
+/// constraint), the max weight and the weights. Unlike the original instances, interaction
+/// values can be negative
+pub fn to_qkp(challenge: &knapsack::Challenge) -> ExportedFile {
+    let num_items = challenge.weights.len();
+    let mut out = String::new();
+    writeln!(out, "tig-c003-{}", hex(&challenge.seed)).unwrap();
+    writeln!(out, "{}", num_items).unwrap();
+    writeln!(out, "{}", join(&challenge.values)).unwrap();
+    for i in 0..num_items.saturating_sub(1) {
+        writeln!(out, "{}", join(&challenge.interaction_values[i][i + 1..])).unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "0").unwrap();
+    writeln!(out, "{}", challenge.max_weight).unwrap();
+    writeln!(out, "{}", join(&challenge.weights)).unwrap();
+    ExportedFile::new("qkp", out)
+}
+
+/// `base.fvecs` with the vector database, `query.fvecs` with the query vectors, and
+/// `groundtruth.ivecs` with the index of each query's nearest neighbour in the database
+pub fn to_fvecs(challenge: &vector_search::Challenge) -> Vec<ExportedFile> {
+    let nearest: Vec<Vec<i32>> = challenge
+        .query_vectors
+        .iter()
+        .map(|query| {
+            let (idx, _) = challenge
+                .vector_database
+                .iter()
+                .map(|v| vector_search::euclidean_distance(query, v))
+                .enumerate()
+                .fold((0, f32::INFINITY), |best, (idx, dist)| {
+                    if dist < best.1 {
+                        (idx, dist)
+                    } else {
+                        best
+                    }
+                });
+            vec![idx as i32]
+        })
+        .collect();
+    vec![
+        ExportedFile::new(
+            "base.fvecs",
+            write_vecs(&challenge.vector_database, |x| x.to_le_bytes()),
+        ),
+        ExportedFile::new(
+            "query.fvecs",
+            write_vecs(&challenge.query_vectors, |x| x.to_le_bytes()),
+        ),
+        ExportedFile::new(
+            "groundtruth.ivecs",
+            write_vecs(&nearest, |x| x.to_le_bytes()),
+        ),
+    ]
+}
+
+// every vector is its dimension as a little endian i32, followed by its components
+fn write_vecs<T>(vectors: &[Vec<T>], to_le_bytes: impl Fn(&T) -> [u8; 4]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for v in vectors.iter() {
+        out.extend_from_slice(&(v.len() as i32).to_le_bytes());
+        for x in v.iter() {
+            out.extend_from_slice(&to_le_bytes(x));
+        }
+    }
+    out
+}
+
+fn join<T: std::fmt::Display>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
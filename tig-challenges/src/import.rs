@@ -0,0 +1,441 @@
+use crate::{knapsack, satisfiability, vector_search, vehicle_routing};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Largest number of nodes or items accepted from a header, so that a hostile file cannot make
+/// the importer allocate an unbounded matrix
+pub const MAX_SIZE: usize = 100_000;
+
+/// How the threshold of an imported instance (`max_total_distance`, `min_value` or
+/// `max_distance`) is set. The instance's `better_than_baseline` is set to match
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    /// Relative to the challenge's own baseline, the same way `generate_instance` does
+    BetterThanBaseline(u32),
+    /// Relative to a known optimum, or best known objective, from the literature. Solutions may
+    /// be up to `gap_per_mille` worse than `objective`
+    KnownOptimum { objective: f64, gap_per_mille: u32 },
+}
+
+/// Builds a satisfiability instance from DIMACS CNF, e.g. SATLIB's uf250 files. Satisfiability
+/// has no threshold, as a solution has to satisfy every clause. Imported instances have a zero
+/// seed
+pub fn from_dimacs(text: &str) -> Result<satisfiability::Challenge> {
+    let mut header: Option<(usize, usize)> = None;
+    let mut clauses = Vec::new();
+    let mut clause = Vec::new();
+    for line in text.lines().map(str::trim) {
+        // SATLIB files end with a `%` line
+        if line.starts_with('%') {
+            break;
+        }
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+        if let Some(problem) = line.strip_prefix('p') {
+            let fields: Vec<&str> = problem.split_whitespace().collect();
+            match fields[..] {
+                ["cnf", num_variables, num_clauses] => {
+                    header = Some((parse(num_variables)?, parse(num_clauses)?))
+                }
+                _ => return Err(anyhow!("Invalid problem line '{}'", line)),
+            }
+            continue;
+        }
+        let (num_variables, _) = header.ok_or_else(|| anyhow!("Missing problem line"))?;
+        for literal in line.split_whitespace() {
+            let literal: i32 = parse(literal)?;
+            if literal == 0 {
+                clauses.push(std::mem::take(&mut clause));
+            } else if literal.unsigned_abs() as usize > num_variables {
+                return Err(anyhow!(
+                    "Literal {} is out of bounds for {} variables",
+                    literal,
+                    num_variables
+                ));
+            } else {
+                clause.push(literal);
+            }
+        }
+    }
+    if !clause.is_empty() {
+        clauses.push(clause);
+    }
+
+    let (num_variables, num_clauses) = header.ok_or_else(|| anyhow!("Missing problem line"))?;
+    if clauses.len() != num_clauses {
+        return Err(anyhow!(
+            "Expected {} clauses, found {}",
+            num_clauses,
+            clauses.len()
+        ));
+    }
+    if num_variables == 0 {
+        return Err(anyhow!("Instance has no variables"));
+    }
+    Ok(satisfiability::Challenge {
+        seed: [0u8; 32],
+        difficulty: satisfiability::Difficulty {
+            num_variables,
+            clauses_to_variables_percent: (num_clauses * 100).div_ceil(num_variables) as u32,
+        },
+        clauses,
+    })
+}
+
+/// Builds a vehicle_routing instance from CVRPLIB, e.g. the X instances. Supports a single
+/// depot, and `EUC_2D` coordinates (rounded to the nearest integer, as in TSPLIB) or an
+/// `EXPLICIT` `FULL_MATRIX`. Nodes are renumbered so that the depot is node 0
+pub fn from_cvrplib(text: &str, threshold: Threshold) -> Result<vehicle_routing::Challenge> {
+    let mut headers = HashMap::new();
+    let mut coords = Vec::new();
+    let mut matrix = Vec::new();
+    let mut demands = Vec::new();
+    let mut depots = Vec::new();
+
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    while let Some(line) = lines.next() {
+        if let Some((key, value)) = line.split_once(':') {
+            headers.insert(key.trim().to_uppercase(), value.trim().to_string());
+            continue;
+        }
+        let dimension = parse_size(
+            headers
+                .get("DIMENSION")
+                .ok_or_else(|| anyhow!("Missing DIMENSION"))?,
+        )?;
+        match line {
+            "NODE_COORD_SECTION" => {
+                for row in read_rows(&mut lines, dimension, 3)? {
+                    coords.push((parse::<f64>(row[1])?, parse::<f64>(row[2])?));
+                }
+            }
+            "EDGE_WEIGHT_SECTION" => {
+                let count = dimension
+                    .checked_mul(dimension)
+                    .ok_or_else(|| anyhow!("DIMENSION {} is too large", dimension))?;
+                for distance in read_tokens(&mut lines, count)? {
+                    matrix.push(parse::<i32>(distance)?);
+                }
+            }
+            "DEMAND_SECTION" => {
+                for row in read_rows(&mut lines, dimension, 2)? {
+                    demands.push(parse::<i32>(row[1])?);
+                }
+            }
+            "DEPOT_SECTION" => {
+                for depot in lines.by_ref() {
+                    match parse::<i64>(depot)? {
+                        -1 => break,
+                        depot => depots.push(depot as usize),
+                    }
+                }
+            }
+            "EOF" => break,
+            _ => return Err(anyhow!("Unsupported section '{}'", line)),
+        }
+    }
+
+    let num_nodes = parse_size(
+        headers
+            .get("DIMENSION")
+            .ok_or_else(|| anyhow!("Missing DIMENSION"))?,
+    )?;
+    let max_capacity: i32 = parse(
+        headers
+            .get("CAPACITY")
+            .ok_or_else(|| anyhow!("Missing CAPACITY"))?,
+    )?;
+    let edge_weight_type = headers.get("EDGE_WEIGHT_TYPE").map(String::as_str);
+    let distance_matrix: Vec<Vec<i32>> = match edge_weight_type {
+        Some("EUC_2D") if coords.len() == num_nodes => coords
+            .iter()
+            .map(|&from| {
+                coords
+                    .iter()
+                    .map(|&to| {
+                        let dx = from.0 - to.0;
+                        let dy = from.1 - to.1;
+                        dx.hypot(dy).round() as i32
+                    })
+                    .collect()
+            })
+            .collect(),
+        Some("EXPLICIT")
+            if headers.get("EDGE_WEIGHT_FORMAT").map(String::as_str) == Some("FULL_MATRIX")
+                && matrix.len() == num_nodes * num_nodes =>
+        {
+            matrix.chunks(num_nodes).map(|row| row.to_vec()).collect()
+        }
+        _ => {
+            return Err(anyhow!(
+                "Expected EUC_2D coordinates or an EXPLICIT FULL_MATRIX for {} nodes",
+                num_nodes
+            ))
+        }
+    };
+    if demands.len() != num_nodes {
+        return Err(anyhow!("Expected {} demands", num_nodes));
+    }
+    let depot = match depots[..] {
+        [depot] if (1..=num_nodes).contains(&depot) => depot - 1,
+        _ => return Err(anyhow!("Expected a single depot")),
+    };
+    if num_nodes < 2 {
+        return Err(anyhow!("Instance has no nodes besides the depot"));
+    }
+    if let Some(node) =
+        (0..num_nodes).find(|&n| n != depot && !(0..=max_capacity).contains(&demands[n]))
+    {
+        return Err(anyhow!(
+            "Demand of node {} must be between 0 and capacity {}",
+            node + 1,
+            max_capacity
+        ));
+    }
+
+    // move the depot to node 0
+    let order: Vec<usize> = std::iter::once(depot)
+        .chain((0..num_nodes).filter(|&n| n != depot))
+        .collect();
+    let mut demands: Vec<i32> = order.iter().map(|&n| demands[n]).collect();
+    demands[0] = 0;
+    let distance_matrix: Vec<Vec<i32>> = order
+        .iter()
+        .map(|&from| order.iter().map(|&to| distance_matrix[from][to]).collect())
+        .collect();
+
+    let baseline_routes =
+        vehicle_routing::calc_baseline_routes(num_nodes, max_capacity, &demands, &distance_matrix)?;
+    let baseline = vehicle_routing::calc_routes_total_distance(
+        num_nodes,
+        max_capacity,
+        &demands,
+        &distance_matrix,
+        &baseline_routes,
+    )?;
+    let (max_total_distance, better_than_baseline) = match threshold {
+        Threshold::BetterThanBaseline(better_than_baseline) => (
+            baseline * (1000 - better_than_baseline as i32) / 1000,
+            better_than_baseline,
+        ),
+        Threshold::KnownOptimum {
+            objective,
+            gap_per_mille,
+        } => {
+            if baseline == 0 {
+                return Err(anyhow!("Baseline routes have a total distance of 0"));
+            }
+            let max_total_distance =
+                (objective * (1000 + gap_per_mille) as f64 / 1000.0).floor() as i32;
+            let better_than_baseline =
+                (1000 - max_total_distance as i64 * 1000 / baseline as i64).max(0) as u32;
+            (max_total_distance, better_than_baseline)
+        }
+    };
+    Ok(vehicle_routing::Challenge {
+        seed: [0u8; 32],
+        difficulty: vehicle_routing::Difficulty {
+            num_nodes,
+            better_than_baseline,
+        },
+        demands,
+        distance_matrix,
+        max_total_distance,
+        max_capacity,
+    })
+}
+
+/// Builds a knapsack instance from the QKP format of Billionnet and Soutif's instances, as
+/// written by `export::to_qkp`
+pub fn from_qkp(text: &str, threshold: Threshold) -> Result<knapsack::Challenge> {
+    // the first line is the instance's name
+    let mut tokens = text.lines().skip(1).flat_map(str::split_whitespace);
+    let mut next = || {
+        tokens
+            .next()
+            .ok_or_else(|| anyhow!("Unexpected end of file"))
+    };
+
+    let num_items = parse_size(next()?)?;
+    let values = (0..num_items)
+        .map(|_| parse::<u32>(next()?))
+        .collect::<Result<Vec<_>>>()?;
+    // one row of the upper triangle per item. Read before allocating the matrix, so that its
+    // size is bounded by the file's
+    let pairs = || (0..num_items).flat_map(move |i| ((i + 1)..num_items).map(move |j| (i, j)));
+    let upper_triangle = pairs()
+        .map(|_| parse::<i32>(next()?))
+        .collect::<Result<Vec<_>>>()?;
+    let mut interaction_values = vec![vec![0; num_items]; num_items];
+    for ((i, j), value) in pairs().zip(upper_triangle) {
+        interaction_values[i][j] = value;
+        interaction_values[j][i] = value;
+    }
+    if next()? != "0" {
+        return Err(anyhow!("Only <= constraints are supported"));
+    }
+    let max_weight: u32 = parse(next()?)?;
+    let weights = (0..num_items)
+        .map(|_| parse::<u32>(next()?))
+        .collect::<Result<Vec<_>>>()?;
+    if weights.contains(&0) {
+        return Err(anyhow!("Weights must be at least 1"));
+    }
+
+    let baseline_items =
+        knapsack::calc_baseline_items(&weights, &values, &interaction_values, max_weight);
+    let baseline = knapsack::calculate_total_value(&baseline_items, &values, &interaction_values);
+    let (min_value, better_than_baseline) = match threshold {
+        Threshold::BetterThanBaseline(better_than_baseline) => (
+            (baseline as f32 * (1.0 + better_than_baseline as f32 / 1000.0)).round() as u32,
+            better_than_baseline,
+        ),
+        Threshold::KnownOptimum {
+            objective,
+            gap_per_mille,
+        } => {
+            let min_value = (objective * (1000 - gap_per_mille.min(1000)) as f64 / 1000.0).ceil();
+            let better_than_baseline =
+                ((min_value / baseline.max(1) as f64 - 1.0) * 1000.0).max(0.0) as u32;
+            (min_value as u32, better_than_baseline)
+        }
+    };
+    Ok(knapsack::Challenge {
+        seed: [0u8; 32],
+        difficulty: knapsack::Difficulty {
+            num_items,
+            better_than_baseline,
+        },
+        weights,
+        values,
+        interaction_values,
+        max_weight,
+        min_value,
+    })
+}
+
+/// Builds a vector_search instance from fvecs database and query vectors, e.g. ANN_SIFT10K. The
+/// baseline for `Threshold::BetterThanBaseline` is the generator's fixed max distance of 6, which
+/// only suits vectors in the unit hypercube
+pub fn from_fvecs(
+    base: &[u8],
+    query: &[u8],
+    threshold: Threshold,
+) -> Result<vector_search::Challenge> {
+    let vector_database = read_fvecs(base)?;
+    let query_vectors = read_fvecs(query)?;
+    let dimension = vector_database.first().map(|v| v.len());
+    if dimension.is_none()
+        || vector_database
+            .iter()
+            .chain(query_vectors.iter())
+            .any(|v| Some(v.len()) != dimension)
+    {
+        return Err(anyhow!(
+            "Database must be non-empty, and every vector must have the same dimension"
+        ));
+    }
+
+    let (max_distance, better_than_baseline) = match threshold {
+        Threshold::BetterThanBaseline(better_than_baseline) => (
+            6.0 - better_than_baseline as f32 / 1000.0,
+            better_than_baseline,
+        ),
+        Threshold::KnownOptimum {
+            objective,
+            gap_per_mille,
+        } => {
+            let max_distance = (objective * (1000 + gap_per_mille) as f64 / 1000.0) as f32;
+            let better_than_baseline = ((6.0 - max_distance) * 1000.0).max(0.0) as u32;
+            (max_distance, better_than_baseline)
+        }
+    };
+    Ok(vector_search::Challenge {
+        seed: [0u8; 32],
+        difficulty: vector_search::Difficulty {
+            num_queries: query_vectors.len() as u32,
+            better_than_baseline,
+        },
+        vector_database,
+        query_vectors,
+        max_distance,
+    })
+}
+
+fn read_fvecs(data: &[u8]) -> Result<Vec<Vec<f32>>> {
+    let mut vectors = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let (dimension, tail) = rest
+            .split_first_chunk::<4>()
+            .ok_or_else(|| anyhow!("Truncated fvecs file"))?;
+        let dimension = i32::from_le_bytes(*dimension);
+        if dimension <= 0 {
+            return Err(anyhow!("Invalid fvecs dimension {}", dimension));
+        }
+        let size = (dimension as usize)
+            .checked_mul(4)
+            .filter(|&size| size <= tail.len())
+            .ok_or_else(|| anyhow!("Truncated fvecs file"))?;
+        let (components, tail) = tail.split_at(size);
+        vectors.push(
+            components
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+        );
+        rest = tail;
+    }
+    Ok(vectors)
+}
+
+// the next `count` whitespace separated tokens, which may span several lines
+fn read_tokens<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    count: usize,
+) -> Result<Vec<&'a str>> {
+    let mut tokens = Vec::new();
+    while tokens.len() < count {
+        let line = lines
+            .next()
+            .ok_or_else(|| anyhow!("Unexpected end of file"))?;
+        tokens.extend(line.split_whitespace());
+    }
+    if tokens.len() != count {
+        return Err(anyhow!("Expected {} values", count));
+    }
+    Ok(tokens)
+}
+
+// the next `count` lines, each with `width` tokens
+fn read_rows<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    count: usize,
+    width: usize,
+) -> Result<Vec<Vec<&'a str>>> {
+    (0..count)
+        .map(|_| {
+            let line = lines
+                .next()
+                .ok_or_else(|| anyhow!("Unexpected end of file"))?;
+            let row: Vec<&str> = line.split_whitespace().collect();
+            match row.len() == width {
+                true => Ok(row),
+                false => Err(anyhow!("Expected {} values in '{}'", width, line)),
+            }
+        })
+        .collect()
+}
+
+// a number of nodes or items, which is used to size matrices
+fn parse_size(s: &str) -> Result<usize> {
+    match parse(s)? {
+        size if size <= MAX_SIZE => Ok(size),
+        size => Err(anyhow!("Size {} exceeds the maximum of {}", size, MAX_SIZE)),
+    }
+}
+
+fn parse<T: std::str::FromStr>(s: &str) -> Result<T> {
+    s.parse().map_err(|_| anyhow!("Failed to parse '{}'", s))
+}
@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use rand::{
     rngs::{SmallRng, StdRng},
     Rng, SeedableRng,
@@ -100,31 +100,8 @@ impl crate::ChallengeTrait<Solution, Difficulty, 2> for Challenge {
 
         let max_weight: u32 = weights.iter().sum::<u32>() / 2;
 
-        // Precompute the ratio between the total value (value + sum of interactive values) and
-        // weight for each item. Pair the ratio with the item's weight and index
-        let mut value_weight_ratios: Vec<(usize, f32, u32)> = (0..difficulty.num_items)
-            .map(|i| {
-                let total_value = values[i] as i32 + interaction_values[i].iter().sum::<i32>();
-                let weight = weights[i];
-                let ratio = total_value as f32 / weight as f32;
-                (i, ratio, weight)
-            })
-            .collect();
-
-        // Sort the list of tuples by value-to-weight ratio in descending order
-        value_weight_ratios.sort_unstable_by(|&(_, ratio_a, _), &(_, ratio_b, _)| {
-            ratio_b.partial_cmp(&ratio_a).unwrap()
-        });
-
-        let mut total_weight = 0;
-        let mut selected_indices = Vec::new();
-        for &(i, _, weight) in &value_weight_ratios {
-            if total_weight + weight <= max_weight {
-                selected_indices.push(i);
-                total_weight += weight;
-            }
-        }
-        selected_indices.sort_unstable();
+        let selected_indices =
+            calc_baseline_items(&weights, &values, &interaction_values, max_weight);
 
         let mut min_value = calculate_total_value(&selected_indices, &values, &interaction_values);
         min_value = (min_value as f32 * (1.0 + difficulty.better_than_baseline as f32 / 1000.0))
@@ -141,44 +118,87 @@ impl crate::ChallengeTrait<Solution, Difficulty, 2> for Challenge {
         })
     }
 
-    fn verify_solution(&self, solution: &Solution) -> Result<()> {
-        let selected_items: HashSet<usize> = solution.items.iter().cloned().collect();
-        if selected_items.len() != solution.items.len() {
-            return Err(anyhow!("Duplicate items selected."));
-        }
-
-        let total_weight = selected_items
-            .iter()
-            .map(|&item| {
-                if item >= self.weights.len() {
-                    return Err(anyhow!("Item ({}) is out of bounds", item));
+    fn evaluate_solution(&self, solution: &Solution) -> Result<crate::SolutionQuality> {
+        let mut selected_items = HashSet::new();
+        let mut total_weight = 0u32;
+        for &item in solution.items.iter() {
+            if item >= self.weights.len() {
+                return Err(crate::VerificationError::OutOfBounds {
+                    index: item,
+                    len: self.weights.len(),
                 }
-                Ok(self.weights[item])
-            })
-            .collect::<Result<Vec<_>, _>>()?
-            .iter()
-            .sum::<u32>();
+                .into());
+            }
+            if !selected_items.insert(item) {
+                return Err(crate::VerificationError::DuplicateIndex { index: item }.into());
+            }
+            total_weight += self.weights[item];
+        }
 
         if total_weight > self.max_weight {
-            return Err(anyhow!(
-                "Total weight ({}) exceeded max weight ({})",
-                total_weight,
-                self.max_weight
-            ));
+            return Err(crate::VerificationError::WeightExceeded {
+                weight: total_weight,
+                max_weight: self.max_weight,
+            }
+            .into());
         }
         let selected_items_vec: Vec<usize> = selected_items.into_iter().collect();
         let total_value =
             calculate_total_value(&selected_items_vec, &self.values, &self.interaction_values);
-        if total_value < self.min_value {
-            Err(anyhow!(
-                "Total value ({}) does not reach minimum value ({})",
-                total_value,
-                self.min_value
-            ))
-        } else {
-            Ok(())
+        Ok(crate::SolutionQuality::new(
+            "total_value",
+            crate::Objective::Maximize,
+            total_value as f64,
+            self.min_value as f64,
+        ))
+    }
+
+    fn verify_solution(&self, solution: &Solution) -> Result<()> {
+        self.evaluate_solution(solution)?.verify()
+    }
+
+    fn export(&self, format: crate::ExportFormat) -> Result<Vec<crate::ExportedFile>> {
+        match format {
+            crate::ExportFormat::Qkp => Ok(vec![crate::export::to_qkp(self)]),
+            _ => Err(crate::export::unsupported("knapsack", format)),
+        }
+    }
+}
+
+/// Greedy baseline the min value is derived from: items by descending ratio of total value
+/// (value plus interaction values) to weight, skipping items that would exceed max weight
+pub fn calc_baseline_items(
+    weights: &Vec<u32>,
+    values: &Vec<u32>,
+    interaction_values: &Vec<Vec<i32>>,
+    max_weight: u32,
+) -> Vec<usize> {
+    // Precompute the ratio between the total value (value + sum of interactive values) and
+    // weight for each item. Pair the ratio with the item's weight and index
+    let mut value_weight_ratios: Vec<(usize, f32, u32)> = (0..weights.len())
+        .map(|i| {
+            let total_value = values[i] as i32 + interaction_values[i].iter().sum::<i32>();
+            let weight = weights[i];
+            let ratio = total_value as f32 / weight as f32;
+            (i, ratio, weight)
+        })
+        .collect();
+
+    // Sort the list of tuples by value-to-weight ratio in descending order
+    value_weight_ratios.sort_unstable_by(|&(_, ratio_a, _), &(_, ratio_b, _)| {
+        ratio_b.partial_cmp(&ratio_a).unwrap()
+    });
+
+    let mut total_weight = 0;
+    let mut selected_indices = Vec::new();
+    for &(i, _, weight) in &value_weight_ratios {
+        if total_weight + weight <= max_weight {
+            selected_indices.push(i);
+            total_weight += weight;
         }
     }
+    selected_indices.sort_unstable();
+    selected_indices
 }
 
 pub fn calculate_total_value(
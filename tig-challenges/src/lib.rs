@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Result};
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "cuda")]
 use cudarc::driver::*;
@@ -13,6 +13,59 @@ pub trait DifficultyTrait<const N: usize>: Serialize + DeserializeOwned {
 }
 pub trait SolutionTrait: Serialize + DeserializeOwned {}
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    Maximize,
+    Minimize,
+}
+
+/// How good a solution is compared to the threshold it has to reach to be valid
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SolutionQuality {
+    /// Name of the objective, e.g. `total_value`
+    pub metric: String,
+    pub direction: Objective,
+    pub objective: f64,
+    pub threshold: f64,
+    /// How far the objective is past the threshold. Negative if the solution is invalid
+    pub margin: f64,
+}
+
+impl SolutionQuality {
+    pub fn new(metric: &str, direction: Objective, objective: f64, threshold: f64) -> Self {
+        let margin = match direction {
+            Objective::Maximize => objective - threshold,
+            Objective::Minimize => threshold - objective,
+        };
+        Self {
+            metric: metric.to_string(),
+            direction,
+            objective,
+            threshold,
+            margin,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.margin >= 0.0
+    }
+
+    /// Fails with `VerificationError::BelowThreshold` if the solution is not valid
+    pub fn verify(&self) -> Result<()> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(VerificationError::BelowThreshold {
+                metric: self.metric.clone(),
+                achieved: self.objective,
+                required: self.threshold,
+            }
+            .into())
+        }
+    }
+}
+
 pub trait ChallengeTrait<T, U, const N: usize>: Serialize + DeserializeOwned
 where
     T: SolutionTrait,
@@ -70,14 +123,39 @@ where
         Self::cuda_generate_instance(seed, &U::from_arr(difficulty), dev, funcs)
     }
 
+    /// Grades a solution. Fails if the solution is malformed, e.g. has out of bounds indexes
+    fn evaluate_solution(&self, solution: &T) -> Result<SolutionQuality>;
+    fn evaluate_solution_from_json(&self, solution: &str) -> Result<SolutionQuality> {
+        let solution = serde_json::from_str(solution)
+            .map_err(|e| VerificationError::InvalidFormat { msg: e.to_string() })?;
+        self.evaluate_solution(&solution)
+    }
+
     fn verify_solution(&self, solution: &T) -> Result<()>;
     fn verify_solution_from_json(&self, solution: &str) -> Result<()> {
         let solution = serde_json::from_str(solution)
-            .map_err(|e| anyhow!("Failed to parse solution: {}", e))?;
+            .map_err(|e| VerificationError::InvalidFormat { msg: e.to_string() })?;
         self.verify_solution(&solution)
     }
+
+    /// Writes the instance in a standard format for external solvers. See the `export` module
+    fn export(&self, format: ExportFormat) -> Result<Vec<ExportedFile>> {
+        Err(anyhow!(
+            "Instances of this challenge cannot be exported as {}",
+            format
+        ))
+    }
 }
 
+pub mod error;
+pub use error::*;
+pub mod export;
+pub use export::{ExportFormat, ExportedFile};
+pub mod import;
+pub use import::Threshold;
+pub mod registry;
+pub use registry::*;
+
 pub mod knapsack;
 pub use knapsack as c003;
 pub mod satisfiability;
@@ -0,0 +1,213 @@
+use crate::{
+    ChallengeTrait, DifficultyTrait, ExportFormat, ExportedFile, SolutionQuality, SolutionTrait,
+    VerificationError,
+};
+use anyhow::Result;
+use serde_json::Value;
+use std::{marker::PhantomData, sync::OnceLock};
+
+/// A challenge with its types erased, so that challenges can be looked up and dispatched to by id
+/// at runtime
+pub trait ChallengeEntry: Send + Sync {
+    /// e.g. `c001`
+    fn id(&self) -> &'static str;
+
+    /// Name of the challenge's module, e.g. `satisfiability`
+    fn name(&self) -> &'static str;
+
+    /// Names of the difficulty parameters, in the order they appear in a difficulty array
+    fn difficulty_parameter_names(&self) -> &'static [&'static str];
+
+    fn difficulty_arity(&self) -> usize {
+        self.difficulty_parameter_names().len()
+    }
+
+    fn generate_instance(
+        &self,
+        seed: [u8; 32],
+        difficulty: &[i32],
+    ) -> Result<Box<dyn ChallengeInstance>>;
+}
+
+/// A generated instance of a challenge
+pub trait ChallengeInstance {
+    /// Bincode serialization of the instance, as passed to an algorithm's wasm
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Grades a solution given as JSON. Fails if it cannot be parsed into the challenge's
+    /// solution type
+    fn evaluate_solution(&self, solution: &Value) -> Result<SolutionQuality>;
+
+    /// Verifies a solution given as JSON. Fails if it cannot be parsed into the challenge's
+    /// solution type
+    fn verify_solution(&self, solution: &Value) -> Result<()>;
+
+    /// Writes the instance in a standard format for external solvers
+    fn export(&self, format: ExportFormat) -> Result<Vec<ExportedFile>>;
+}
+
+// fn pointer so that entries are Send + Sync regardless of the challenge types
+type Types<C, T, U> = PhantomData<fn() -> (C, T, U)>;
+
+/// `ChallengeEntry` for any type implementing `ChallengeTrait`
+pub struct TypedChallengeEntry<C, T, U, const N: usize> {
+    id: &'static str,
+    name: &'static str,
+    difficulty_parameter_names: &'static [&'static str; N],
+    _types: Types<C, T, U>,
+}
+
+impl<C, T, U, const N: usize> TypedChallengeEntry<C, T, U, N> {
+    pub fn new(
+        id: &'static str,
+        name: &'static str,
+        difficulty_parameter_names: &'static [&'static str; N],
+    ) -> Self {
+        Self {
+            id,
+            name,
+            difficulty_parameter_names,
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<C, T, U, const N: usize> ChallengeEntry for TypedChallengeEntry<C, T, U, N>
+where
+    C: ChallengeTrait<T, U, N> + 'static,
+    T: SolutionTrait + 'static,
+    U: DifficultyTrait<N> + 'static,
+{
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn difficulty_parameter_names(&self) -> &'static [&'static str] {
+        self.difficulty_parameter_names
+    }
+
+    fn generate_instance(
+        &self,
+        seed: [u8; 32],
+        difficulty: &[i32],
+    ) -> Result<Box<dyn ChallengeInstance>> {
+        let challenge = C::generate_instance_from_vec(seed, &difficulty.to_vec())?;
+        Ok(Box::new(TypedChallengeInstance::<C, T, U, N> {
+            challenge,
+            _types: PhantomData,
+        }))
+    }
+}
+
+struct TypedChallengeInstance<C, T, U, const N: usize> {
+    challenge: C,
+    _types: Types<(), T, U>,
+}
+
+impl<C, T, U, const N: usize> ChallengeInstance for TypedChallengeInstance<C, T, U, N>
+where
+    C: ChallengeTrait<T, U, N>,
+    T: SolutionTrait,
+    U: DifficultyTrait<N>,
+{
+    fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self.challenge).unwrap()
+    }
+
+    fn evaluate_solution(&self, solution: &Value) -> Result<SolutionQuality> {
+        self.challenge.evaluate_solution(&parse_solution(solution)?)
+    }
+
+    fn verify_solution(&self, solution: &Value) -> Result<()> {
+        self.challenge.verify_solution(&parse_solution(solution)?)
+    }
+
+    fn export(&self, format: ExportFormat) -> Result<Vec<ExportedFile>> {
+        self.challenge.export(format)
+    }
+}
+
+fn parse_solution<T: SolutionTrait>(solution: &Value) -> Result<T> {
+    serde_json::from_value(solution.clone())
+        .map_err(|e| VerificationError::InvalidFormat { msg: e.to_string() }.into())
+}
+
+/// Challenges that can be looked up by id or name
+#[derive(Default)]
+pub struct ChallengeRegistry {
+    entries: Vec<Box<dyn ChallengeEntry>>,
+}
+
+impl ChallengeRegistry {
+    /// Registry of every challenge in this crate
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register(TypedChallengeEntry::<
+            crate::satisfiability::Challenge,
+            crate::satisfiability::Solution,
+            crate::satisfiability::Difficulty,
+            2,
+        >::new(
+            "c001",
+            "satisfiability",
+            &["num_variables", "clauses_to_variables_percent"],
+        ));
+        registry.register(TypedChallengeEntry::<
+            crate::vehicle_routing::Challenge,
+            crate::vehicle_routing::Solution,
+            crate::vehicle_routing::Difficulty,
+            2,
+        >::new(
+            "c002",
+            "vehicle_routing",
+            &["num_nodes", "better_than_baseline"],
+        ));
+        registry.register(TypedChallengeEntry::<
+            crate::knapsack::Challenge,
+            crate::knapsack::Solution,
+            crate::knapsack::Difficulty,
+            2,
+        >::new(
+            "c003", "knapsack", &["num_items", "better_than_baseline"]
+        ));
+        registry.register(TypedChallengeEntry::<
+            crate::vector_search::Challenge,
+            crate::vector_search::Solution,
+            crate::vector_search::Difficulty,
+            2,
+        >::new(
+            "c004",
+            "vector_search",
+            &["num_queries", "better_than_baseline"],
+        ));
+        registry
+    }
+
+    /// Replaces any entry with the same id
+    pub fn register<E: ChallengeEntry + 'static>(&mut self, entry: E) {
+        self.entries.retain(|e| e.id() != entry.id());
+        self.entries.push(Box::new(entry));
+    }
+
+    /// Looks up a challenge by id (e.g. `c001`) or name (e.g. `satisfiability`)
+    pub fn get(&self, id_or_name: &str) -> Option<&dyn ChallengeEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.id() == id_or_name || e.name() == id_or_name)
+            .map(|e| e.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ChallengeEntry> {
+        self.entries.iter().map(|e| e.as_ref())
+    }
+}
+
+/// The builtin registry, shared by every caller
+pub fn challenge_registry() -> &'static ChallengeRegistry {
+    static REGISTRY: OnceLock<ChallengeRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ChallengeRegistry::builtin)
+}
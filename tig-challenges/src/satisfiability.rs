@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use ndarray::{Array2, Axis};
 use rand::{
     distributions::{Distribution, Uniform},
@@ -119,29 +119,56 @@ impl crate::ChallengeTrait<Solution, Difficulty, 2> for Challenge {
         })
     }
 
-    fn verify_solution(&self, solution: &Solution) -> Result<()> {
+    fn evaluate_solution(&self, solution: &Solution) -> Result<crate::SolutionQuality> {
         if solution.variables.len() != self.difficulty.num_variables {
-            return Err(anyhow!(
-                "Invalid number of variables. Expected: {}, Actual: {}",
-                self.difficulty.num_variables,
-                solution.variables.len()
-            ));
+            return Err(crate::VerificationError::WrongLength {
+                expected: self.difficulty.num_variables,
+                actual: solution.variables.len(),
+            }
+            .into());
+        }
+
+        let num_satisfied = self
+            .clauses
+            .iter()
+            .filter(|clause| is_clause_satisfied(clause, &solution.variables))
+            .count();
+        Ok(crate::SolutionQuality::new(
+            "satisfied_clauses",
+            crate::Objective::Maximize,
+            num_satisfied as f64,
+            self.clauses.len() as f64,
+        ))
+    }
+
+    fn verify_solution(&self, solution: &Solution) -> Result<()> {
+        let quality = self.evaluate_solution(solution)?;
+        if quality.is_valid() {
+            return Ok(());
         }
+        let idx = self
+            .clauses
+            .iter()
+            .position(|clause| !is_clause_satisfied(clause, &solution.variables))
+            .unwrap();
+        Err(crate::VerificationError::UnsatisfiedClause { clause: idx }.into())
+    }
 
-        if let Some((idx, _)) = self.clauses.iter().enumerate().find(|(_, clause)| {
-            !clause.iter().any(|&literal| {
-                let var_idx = literal.abs() as usize - 1;
-                let var_value = solution.variables[var_idx];
-                (literal > 0 && var_value) || (literal < 0 && !var_value)
-            })
-        }) {
-            Err(anyhow!("Clause '{}' not satisfied", idx))
-        } else {
-            Ok(())
+    fn export(&self, format: crate::ExportFormat) -> Result<Vec<crate::ExportedFile>> {
+        match format {
+            crate::ExportFormat::Dimacs => Ok(vec![crate::export::to_dimacs(self)]),
+            _ => Err(crate::export::unsupported("satisfiability", format)),
         }
     }
 }
 
+fn is_clause_satisfied(clause: &[i32], variables: &[bool]) -> bool {
+    clause.iter().any(|&literal| {
+        let var_value = variables[literal.abs() as usize - 1];
+        (literal > 0 && var_value) || (literal < 0 && !var_value)
+    })
+}
+
 mod bool_vec_as_u8 {
     use super::*;
     use std::fmt;
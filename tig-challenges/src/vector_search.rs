@@ -1,5 +1,7 @@
-use crate::{ChallengeTrait, DifficultyTrait, SolutionTrait};
-use anyhow::{anyhow, Result};
+use crate::{
+    ChallengeTrait, DifficultyTrait, Objective, SolutionQuality, SolutionTrait, VerificationError,
+};
+use anyhow::Result;
 use rand::{
     distributions::{Distribution, Uniform},
     rngs::{SmallRng, StdRng},
@@ -102,35 +104,44 @@ impl ChallengeTrait<Solution, Difficulty, 2> for Challenge {
         })
     }
 
-    fn verify_solution(&self, solution: &Solution) -> Result<()> {
+    fn evaluate_solution(&self, solution: &Solution) -> Result<SolutionQuality> {
         if solution.indexes.len() != self.difficulty.num_queries as usize {
-            return Err(anyhow!(
-                "Invalid number of indexes. Expected: {}, Actual: {}",
-                self.difficulty.num_queries,
-                solution.indexes.len()
-            ));
+            return Err(VerificationError::WrongLength {
+                expected: self.difficulty.num_queries as usize,
+                actual: solution.indexes.len(),
+            }
+            .into());
         }
 
         let mut dists = Vec::new();
         for (query, &search_index) in self.query_vectors.iter().zip(solution.indexes.iter()) {
             if search_index >= self.vector_database.len() {
-                return Err(anyhow!(
-                    "Invalid index. Expected: less than {}, Actual: {}",
-                    self.vector_database.len(),
-                    search_index
-                ));
+                return Err(VerificationError::OutOfBounds {
+                    index: search_index,
+                    len: self.vector_database.len(),
+                }
+                .into());
             }
             let search = &self.vector_database[search_index];
             dists.push(euclidean_distance(query, search));
         }
         let avg_dist = dists.iter().sum::<f32>() / dists.len() as f32;
-        if avg_dist > self.max_distance {
-            return Err(anyhow!(
-                "Average query vector distance is '{}'. Max dist: '{}'",
-                avg_dist,
-                self.max_distance
-            ));
+        Ok(SolutionQuality::new(
+            "average_distance",
+            Objective::Minimize,
+            avg_dist as f64,
+            self.max_distance as f64,
+        ))
+    }
+
+    fn verify_solution(&self, solution: &Solution) -> Result<()> {
+        self.evaluate_solution(solution)?.verify()
+    }
+
+    fn export(&self, format: crate::ExportFormat) -> Result<Vec<crate::ExportedFile>> {
+        match format {
+            crate::ExportFormat::Fvecs => Ok(crate::export::to_fvecs(self)),
+            _ => Err(crate::export::unsupported("vector_search", format)),
         }
-        Ok(())
     }
 }
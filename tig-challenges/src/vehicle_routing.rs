@@ -1,4 +1,5 @@
-use anyhow::{anyhow, Result};
+use crate::VerificationError;
+use anyhow::Result;
 use rand::{
     rngs::{SmallRng, StdRng},
     Rng, SeedableRng,
@@ -124,7 +125,7 @@ impl crate::ChallengeTrait<Solution, Difficulty, 2> for Challenge {
         })
     }
 
-    fn verify_solution(&self, solution: &Solution) -> Result<()> {
+    fn evaluate_solution(&self, solution: &Solution) -> Result<crate::SolutionQuality> {
         let total_distance = calc_routes_total_distance(
             self.difficulty.num_nodes,
             self.max_capacity,
@@ -132,14 +133,22 @@ impl crate::ChallengeTrait<Solution, Difficulty, 2> for Challenge {
             &self.distance_matrix,
             &solution.routes,
         )?;
-        if total_distance <= self.max_total_distance {
-            Ok(())
-        } else {
-            Err(anyhow!(
-                "Total distance ({}) exceeds max total distance ({})",
-                total_distance,
-                self.max_total_distance
-            ))
+        Ok(crate::SolutionQuality::new(
+            "total_distance",
+            crate::Objective::Minimize,
+            total_distance as f64,
+            self.max_total_distance as f64,
+        ))
+    }
+
+    fn verify_solution(&self, solution: &Solution) -> Result<()> {
+        self.evaluate_solution(solution)?.verify()
+    }
+
+    fn export(&self, format: crate::ExportFormat) -> Result<Vec<crate::ExportedFile>> {
+        match format {
+            crate::ExportFormat::Cvrplib => Ok(vec![crate::export::to_cvrplib(self)]),
+            _ => Err(crate::export::unsupported("vehicle_routing", format)),
         }
     }
 }
@@ -196,24 +205,37 @@ pub fn calc_routes_total_distance(
     let mut visited = vec![false; num_nodes];
     visited[0] = true;
 
-    for route in routes {
+    for (route_idx, route) in routes.iter().enumerate() {
         if route.len() <= 2 || route[0] != 0 || route[route.len() - 1] != 0 {
-            return Err(anyhow!("Each route must start and end at node 0 (the depot), and visit at least one non-depot node"));
+            return Err(VerificationError::InvalidRoute { route: route_idx }.into());
         }
 
         let mut capacity = max_capacity;
         let mut current_node = 0;
 
         for &node in &route[1..route.len() - 1] {
+            if node >= num_nodes {
+                return Err(VerificationError::OutOfBounds {
+                    index: node,
+                    len: num_nodes,
+                }
+                .into());
+            }
             if visited[node] {
-                return Err(anyhow!(
-                    "The same non-depot node cannot be visited more than once"
-                ));
+                return Err(VerificationError::DuplicateIndex { index: node }.into());
             }
             if demands[node] > capacity {
-                return Err(anyhow!(
-                    "The total demand on each route must not exceed max capacity"
-                ));
+                let load = route[1..route.len() - 1]
+                    .iter()
+                    .filter(|&&n| n < num_nodes)
+                    .map(|&n| demands[n])
+                    .sum();
+                return Err(VerificationError::CapacityExceeded {
+                    route: route_idx,
+                    load,
+                    capacity: max_capacity,
+                }
+                .into());
             }
             visited[node] = true;
             capacity -= demands[node];
@@ -224,8 +246,8 @@ pub fn calc_routes_total_distance(
         total_distance += distance_matrix[current_node][0];
     }
 
-    if visited.iter().any(|&v| !v) {
-        return Err(anyhow!("All nodes must be visited"));
+    if let Some(node) = visited.iter().position(|&v| !v) {
+        return Err(VerificationError::UnvisitedNode { node }.into());
     }
 
     Ok(total_distance)
@@ -0,0 +1,72 @@
+use tig_challenges::{
+    knapsack, satisfiability, vector_search, vehicle_routing, ChallengeTrait, Objective,
+    SolutionQuality, VerificationError,
+};
+
+fn assert_consistent(quality: &SolutionQuality, verified: anyhow::Result<()>) {
+    assert_eq!(quality.is_valid(), verified.is_ok(), "{:?}", quality);
+    let margin = match quality.direction {
+        Objective::Maximize => quality.objective - quality.threshold,
+        Objective::Minimize => quality.threshold - quality.objective,
+    };
+    assert_eq!(quality.margin, margin);
+}
+
+#[test]
+fn test_evaluate_solution() {
+    let seed = [3u8; 32];
+
+    // baseline routes are valid when not required to beat the baseline
+    let c = vehicle_routing::Challenge::generate_instance_from_vec(seed, &vec![40, 0]).unwrap();
+    let routes = vehicle_routing::calc_baseline_routes(
+        c.difficulty.num_nodes,
+        c.max_capacity,
+        &c.demands,
+        &c.distance_matrix,
+    )
+    .unwrap();
+    let solution = vehicle_routing::Solution { routes };
+    let quality = c.evaluate_solution(&solution).unwrap();
+    assert_eq!(quality.metric, "total_distance");
+    assert_eq!(quality.direction, Objective::Minimize);
+    assert_eq!(quality.threshold, c.max_total_distance as f64);
+    assert!(quality.is_valid());
+    assert_consistent(&quality, c.verify_solution(&solution));
+
+    let c = knapsack::Challenge::generate_instance_from_vec(seed, &vec![50, 10]).unwrap();
+    let solution = knapsack::Solution { items: vec![] };
+    let quality = c.evaluate_solution(&solution).unwrap();
+    assert_eq!(quality.objective, 0.0);
+    assert_eq!(quality.threshold, c.min_value as f64);
+    assert_consistent(&quality, c.verify_solution(&solution));
+
+    let c = satisfiability::Challenge::generate_instance_from_vec(seed, &vec![50, 300]).unwrap();
+    let solution = satisfiability::Solution {
+        variables: vec![false; 50],
+    };
+    let quality = c.evaluate_solution(&solution).unwrap();
+    assert_eq!(quality.threshold, c.clauses.len() as f64);
+    assert!(quality.objective <= quality.threshold);
+    assert_consistent(&quality, c.verify_solution(&solution));
+
+    let c = vector_search::Challenge::generate_instance_from_vec(seed, &vec![10, 0]).unwrap();
+    let solution = vector_search::Solution {
+        indexes: vec![0; 10],
+    };
+    let quality = c.evaluate_solution(&solution).unwrap();
+    assert_eq!(quality.metric, "average_distance");
+    assert_consistent(&quality, c.verify_solution(&solution));
+}
+
+#[test]
+fn test_evaluate_malformed_solution() {
+    let c = knapsack::Challenge::generate_instance_from_vec([0u8; 32], &vec![50, 10]).unwrap();
+    let err = c
+        .evaluate_solution(&knapsack::Solution { items: vec![1, 1] })
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<VerificationError>(),
+        Some(&VerificationError::DuplicateIndex { index: 1 })
+    );
+    assert!(c.evaluate_solution_from_json(r#"{"items":[0,1]}"#).is_ok());
+}
@@ -0,0 +1,95 @@
+use tig_challenges::{
+    challenge_registry, knapsack, satisfiability, vector_search, vehicle_routing, ChallengeTrait,
+    ExportFormat,
+};
+
+fn text(data: &[u8]) -> Vec<String> {
+    String::from_utf8(data.to_vec())
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[test]
+fn test_export_text_formats() {
+    let seed = [0u8; 32];
+
+    let c = satisfiability::Challenge::generate_instance_from_vec(seed, &vec![50, 300]).unwrap();
+    let files = c.export(ExportFormat::Dimacs).unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].extension, "cnf");
+    let lines = text(&files[0].data);
+    let header = lines.iter().position(|l| l.starts_with("p ")).unwrap();
+    assert_eq!(lines[header], format!("p cnf 50 {}", c.clauses.len()));
+    let clauses = &lines[header + 1..];
+    assert_eq!(clauses.len(), c.clauses.len());
+    let literals: Vec<i32> = clauses[0].split(' ').map(|l| l.parse().unwrap()).collect();
+    assert_eq!(literals[..literals.len() - 1], c.clauses[0][..]);
+    assert_eq!(literals.last(), Some(&0));
+
+    let c = vehicle_routing::Challenge::generate_instance_from_vec(seed, &vec![40, 250]).unwrap();
+    let lines = text(&c.export(ExportFormat::Cvrplib).unwrap()[0].data);
+    assert!(lines.contains(&"DIMENSION : 40".to_string()));
+    assert!(lines.contains(&format!("CAPACITY : {}", c.max_capacity)));
+    let demands = lines.iter().position(|l| l == "DEMAND_SECTION").unwrap();
+    assert_eq!(lines[demands + 2], format!("2 {}", c.demands[1]));
+    assert_eq!(lines.last().unwrap(), "EOF");
+
+    let c = knapsack::Challenge::generate_instance_from_vec(seed, &vec![50, 10]).unwrap();
+    let lines = text(&c.export(ExportFormat::Qkp).unwrap()[0].data);
+    // name, size, values, 49 interaction rows, blank, constraint type, max weight, weights
+    assert_eq!(lines.len(), 3 + 49 + 4);
+    assert_eq!(lines[1], "50");
+    assert_eq!(lines[3].split(' ').count(), 49);
+    assert_eq!(lines[51].split(' ').count(), 1);
+    assert_eq!(lines[51], c.interaction_values[48][49].to_string());
+    assert_eq!(lines[54], c.max_weight.to_string());
+
+    assert!(c.export(ExportFormat::Dimacs).is_err());
+}
+
+#[test]
+fn test_export_fvecs() {
+    let instance = challenge_registry()
+        .get("vector_search")
+        .unwrap()
+        .generate_instance([0u8; 32], &[10, 0])
+        .unwrap();
+    let files = instance.export(ExportFormat::Fvecs).unwrap();
+    let extensions: Vec<&str> = files.iter().map(|f| f.extension.as_str()).collect();
+    assert_eq!(
+        extensions,
+        ["base.fvecs", "query.fvecs", "groundtruth.ivecs"]
+    );
+    // each vector is a 4 byte dimension followed by 4 byte components
+    assert_eq!(files[0].data.len(), 100000 * (4 + 250 * 4));
+    assert_eq!(files[1].data.len(), 10 * (4 + 250 * 4));
+    assert_eq!(files[2].data.len(), 10 * (4 + 4));
+    assert_eq!(files[1].data[..4], 250i32.to_le_bytes());
+
+    // the nearest neighbours are the best possible solution
+    let indexes: Vec<usize> = files[2]
+        .data
+        .chunks(8)
+        .map(|chunk| i32::from_le_bytes(chunk[4..].try_into().unwrap()) as usize)
+        .collect();
+    let c = vector_search::Challenge::generate_instance_from_vec([0u8; 32], &vec![10, 0]).unwrap();
+    let best = c
+        .evaluate_solution(&vector_search::Solution {
+            indexes: indexes.clone(),
+        })
+        .unwrap();
+    let other = c
+        .evaluate_solution(&vector_search::Solution {
+            indexes: indexes.iter().map(|&i| (i + 1) % 100000).collect(),
+        })
+        .unwrap();
+    assert!(best.objective < other.objective);
+
+    assert_eq!(
+        "fvecs".parse::<ExportFormat>().unwrap(),
+        ExportFormat::Fvecs
+    );
+    assert!("csv".parse::<ExportFormat>().is_err());
+}
@@ -0,0 +1,119 @@
+use tig_challenges::{
+    import, knapsack, satisfiability, vehicle_routing, ChallengeTrait, DifficultyTrait,
+    ExportFormat, Threshold,
+};
+
+#[test]
+fn test_import_round_trips_export() {
+    let seed = [0u8; 32];
+
+    let c = satisfiability::Challenge::generate_instance_from_vec(seed, &vec![50, 300]).unwrap();
+    let file = &c.export(ExportFormat::Dimacs).unwrap()[0];
+    let imported = import::from_dimacs(std::str::from_utf8(&file.data).unwrap()).unwrap();
+    assert_eq!(imported.clauses, c.clauses);
+    assert_eq!(imported.difficulty.to_arr(), c.difficulty.to_arr());
+
+    let c = vehicle_routing::Challenge::generate_instance_from_vec(seed, &vec![40, 250]).unwrap();
+    let file = &c.export(ExportFormat::Cvrplib).unwrap()[0];
+    let imported = import::from_cvrplib(
+        std::str::from_utf8(&file.data).unwrap(),
+        Threshold::BetterThanBaseline(250),
+    )
+    .unwrap();
+    assert_eq!(imported.demands, c.demands);
+    assert_eq!(imported.distance_matrix, c.distance_matrix);
+    assert_eq!(imported.max_capacity, c.max_capacity);
+    assert_eq!(imported.max_total_distance, c.max_total_distance);
+    assert_eq!(imported.difficulty.to_arr(), c.difficulty.to_arr());
+
+    let c = knapsack::Challenge::generate_instance_from_vec(seed, &vec![50, 10]).unwrap();
+    let file = &c.export(ExportFormat::Qkp).unwrap()[0];
+    let imported = import::from_qkp(
+        std::str::from_utf8(&file.data).unwrap(),
+        Threshold::BetterThanBaseline(10),
+    )
+    .unwrap();
+    assert_eq!(imported.weights, c.weights);
+    assert_eq!(imported.values, c.values);
+    assert_eq!(imported.interaction_values, c.interaction_values);
+    assert_eq!(imported.max_weight, c.max_weight);
+    assert_eq!(imported.min_value, c.min_value);
+}
+
+#[test]
+fn test_import_external_formats() {
+    // SATLIB style, with a clause over two lines and a `%` trailer
+    let cnf = "c uf3\np cnf 3 2\n 1 -2 3 0\n-1\n 2 0\n%\n0\n";
+    let c = import::from_dimacs(cnf).unwrap();
+    assert_eq!(c.clauses, vec![vec![1, -2, 3], vec![-1, 2]]);
+    let solution = satisfiability::Solution {
+        variables: vec![true, true, false],
+    };
+    assert!(c.verify_solution(&solution).is_ok());
+    assert!(import::from_dimacs("p cnf 2 1\n1 3 0\n").is_err());
+    assert!(import::from_dimacs("p cnf 2 2\n1 2 0\n").is_err());
+
+    // CVRPLIB with coordinates, and the depot listed last
+    let vrp = "NAME : test\nTYPE : CVRP\nDIMENSION : 3\nEDGE_WEIGHT_TYPE : EUC_2D\nCAPACITY : 10\n\
+               NODE_COORD_SECTION\n1 3 4\n2 0 8\n3 0 0\n\
+               DEMAND_SECTION\n1 5\n2 5\n3 0\n\
+               DEPOT_SECTION\n3\n-1\nEOF\n";
+    let c = import::from_cvrplib(
+        vrp,
+        Threshold::KnownOptimum {
+            objective: 18.0,
+            gap_per_mille: 100,
+        },
+    )
+    .unwrap();
+    assert_eq!(c.demands, vec![0, 5, 5]);
+    assert_eq!(c.distance_matrix[0], vec![0, 5, 8]);
+    assert_eq!(c.max_total_distance, 19);
+    let solution = vehicle_routing::Solution {
+        routes: vec![vec![0, 1, 2, 0]],
+    };
+    assert!(c.verify_solution(&solution).is_ok());
+    let over_capacity = vrp.replace("2 5\n", "2 11\n");
+    assert!(import::from_cvrplib(&over_capacity, Threshold::BetterThanBaseline(0)).is_err());
+    // headers are untrusted, so sizes are bounded before anything is allocated
+    let huge = "DIMENSION : 4294967296\nEDGE_WEIGHT_TYPE : EXPLICIT\nEDGE_WEIGHT_SECTION\n0\n";
+    assert!(import::from_cvrplib(huge, Threshold::BetterThanBaseline(0)).is_err());
+    let huge = "DIMENSION : 100000\nEDGE_WEIGHT_TYPE : EXPLICIT\nEDGE_WEIGHT_SECTION\n0\n";
+    assert!(import::from_cvrplib(huge, Threshold::BetterThanBaseline(0)).is_err());
+    let zero_distances = vrp.replace("1 3 4\n2 0 8\n", "1 0 0\n2 0 0\n");
+    let known_optimum = Threshold::KnownOptimum {
+        objective: 0.0,
+        gap_per_mille: 0,
+    };
+    assert!(import::from_cvrplib(&zero_distances, known_optimum).is_err());
+    assert!(import::from_qkp("huge\n100000\n1\n", Threshold::BetterThanBaseline(0)).is_err());
+    assert!(import::from_qkp(
+        "huge\n18446744073709551615\n",
+        Threshold::BetterThanBaseline(0)
+    )
+    .is_err());
+
+    let mut fvecs = Vec::new();
+    for v in [[0.0f32, 0.0], [3.0, 4.0]] {
+        fvecs.extend_from_slice(&2i32.to_le_bytes());
+        for x in v {
+            fvecs.extend_from_slice(&x.to_le_bytes());
+        }
+    }
+    let c = import::from_fvecs(
+        &fvecs,
+        &fvecs[..12],
+        Threshold::KnownOptimum {
+            objective: 0.0,
+            gap_per_mille: 0,
+        },
+    )
+    .unwrap();
+    assert_eq!(c.vector_database, vec![vec![0.0, 0.0], vec![3.0, 4.0]]);
+    assert_eq!(c.difficulty.num_queries, 1);
+    assert_eq!(c.max_distance, 0.0);
+    assert!(import::from_fvecs(&fvecs[..10], &fvecs, Threshold::BetterThanBaseline(0)).is_err());
+    let mut negative = fvecs.clone();
+    negative[..4].copy_from_slice(&(-1i32).to_le_bytes());
+    assert!(import::from_fvecs(&negative, &fvecs, Threshold::BetterThanBaseline(0)).is_err());
+}
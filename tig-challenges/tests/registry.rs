@@ -0,0 +1,60 @@
+use serde_json::json;
+use tig_challenges::{challenge_registry, ChallengeRegistry, ChallengeTrait, TypedChallengeEntry};
+
+#[test]
+fn test_builtin_registry() {
+    let registry = challenge_registry();
+    let ids: Vec<&str> = registry.iter().map(|c| c.id()).collect();
+    assert_eq!(ids, vec!["c001", "c002", "c003", "c004"]);
+
+    let challenge = registry.get("vehicle_routing").unwrap();
+    assert_eq!(challenge.id(), "c002");
+    assert_eq!(
+        challenge.difficulty_parameter_names(),
+        &["num_nodes", "better_than_baseline"]
+    );
+    assert_eq!(challenge.difficulty_arity(), 2);
+    assert!(registry.get("c999").is_none());
+
+    // same bytes as serializing the typed instance
+    let seed = [7u8; 32];
+    let instance =
+        tig_challenges::c002::Challenge::generate_instance_from_vec(seed, &vec![40, 250]).unwrap();
+    assert_eq!(
+        registry
+            .get("c002")
+            .unwrap()
+            .generate_instance(seed, &[40, 250])
+            .unwrap()
+            .to_bytes(),
+        bincode::serialize(&instance).unwrap()
+    );
+}
+
+#[test]
+fn test_registry_errors() {
+    let challenge = challenge_registry().get("c003").unwrap();
+    assert!(challenge.generate_instance([0u8; 32], &[50]).is_err());
+
+    let instance = challenge.generate_instance([0u8; 32], &[50, 10]).unwrap();
+    let err = instance
+        .verify_solution(&json!({"routes": []}))
+        .unwrap_err();
+    assert!(err.to_string().starts_with("Failed to parse solution"));
+    assert!(instance.verify_solution(&json!({"items": []})).is_err());
+
+    // registering an existing id replaces the entry
+    let mut registry = ChallengeRegistry::builtin();
+    registry.register(TypedChallengeEntry::<
+        tig_challenges::knapsack::Challenge,
+        tig_challenges::knapsack::Solution,
+        tig_challenges::knapsack::Difficulty,
+        2,
+    >::new(
+        "c001",
+        "knapsack_v2",
+        &["num_items", "better_than_baseline"],
+    ));
+    assert_eq!(registry.iter().count(), 4);
+    assert_eq!(registry.get("c001").unwrap().name(), "knapsack_v2");
+}
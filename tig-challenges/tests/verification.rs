@@ -0,0 +1,111 @@
+use serde_json::json;
+use tig_challenges::{
+    challenge_registry, knapsack, satisfiability, vehicle_routing, ChallengeTrait,
+    VerificationError,
+};
+
+fn reason(result: anyhow::Result<()>) -> VerificationError {
+    VerificationError::from_anyhow(&result.unwrap_err())
+}
+
+#[test]
+fn test_verification_reasons() {
+    let seed = [0u8; 32];
+
+    let c = knapsack::Challenge::generate_instance_from_vec(seed, &vec![50, 10]).unwrap();
+    assert_eq!(
+        reason(c.verify_solution(&knapsack::Solution { items: vec![50] })),
+        VerificationError::OutOfBounds { index: 50, len: 50 }
+    );
+    let all_items = knapsack::Solution {
+        items: (0..50).collect(),
+    };
+    assert!(matches!(
+        reason(c.verify_solution(&all_items)),
+        VerificationError::WeightExceeded { .. }
+    ));
+    let e = reason(c.verify_solution(&knapsack::Solution { items: vec![] }));
+    assert_eq!(
+        e,
+        VerificationError::BelowThreshold {
+            metric: "total_value".to_string(),
+            achieved: 0.0,
+            required: c.min_value as f64,
+        }
+    );
+    assert!(!e.is_malformed());
+
+    let c = vehicle_routing::Challenge::generate_instance_from_vec(seed, &vec![40, 250]).unwrap();
+    let e = reason(c.verify_solution(&vehicle_routing::Solution {
+        routes: vec![vec![0, 1, 0], vec![0, 2]],
+    }));
+    assert_eq!(e, VerificationError::InvalidRoute { route: 1 });
+    assert!(e.is_malformed());
+    assert_eq!(
+        reason(c.verify_solution(&vehicle_routing::Solution {
+            routes: vec![vec![0, 1, 0]],
+        })),
+        VerificationError::UnvisitedNode { node: 2 }
+    );
+    let all_nodes = vehicle_routing::Solution {
+        routes: vec![(0..40).chain([0]).collect()],
+    };
+    assert_eq!(
+        reason(c.verify_solution(&all_nodes)),
+        VerificationError::CapacityExceeded {
+            route: 0,
+            load: c.demands.iter().sum(),
+            capacity: c.max_capacity,
+        }
+    );
+
+    let c = satisfiability::Challenge::generate_instance_from_vec(seed, &vec![50, 300]).unwrap();
+    assert_eq!(
+        reason(c.verify_solution(&satisfiability::Solution {
+            variables: vec![true; 10],
+        })),
+        VerificationError::WrongLength {
+            expected: 50,
+            actual: 10,
+        }
+    );
+}
+
+#[test]
+fn test_verification_error_json() {
+    let instance = challenge_registry()
+        .get("c003")
+        .unwrap()
+        .generate_instance([0u8; 32], &[50, 10])
+        .unwrap();
+    let e = VerificationError::from_anyhow(
+        &instance
+            .verify_solution(&json!({"items": [3, 3]}))
+            .unwrap_err(),
+    );
+    assert_eq!(
+        serde_json::to_value(&e).unwrap(),
+        json!({"reason": "duplicate_index", "index": 3})
+    );
+    assert_eq!(e.to_string(), "Index (3) is selected more than once");
+
+    let e = VerificationError::from_anyhow(
+        &instance
+            .verify_solution(&json!({"routes": []}))
+            .unwrap_err(),
+    );
+    assert!(matches!(e, VerificationError::InvalidFormat { .. }));
+
+    // errors from outside this crate are kept as their message
+    let e = VerificationError::from_anyhow(&anyhow::anyhow!("Invalid solution"));
+    assert_eq!(
+        e,
+        VerificationError::Other {
+            msg: "Invalid solution".to_string()
+        }
+    );
+    assert_eq!(
+        serde_json::from_value::<VerificationError>(serde_json::to_value(&e).unwrap()).unwrap(),
+        e
+    );
+}
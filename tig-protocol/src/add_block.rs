@@ -1,12 +1,55 @@
 use crate::context::*;
 use logging_timer::time;
 use rand::{prelude::SliceRandom, rngs::StdRng, Rng, SeedableRng};
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    task::Poll,
+};
 use tig_structs::{config::*, core::*};
 use tig_utils::*;
 
 #[time]
 pub(crate) async fn execute<T: Context>(ctx: &T) -> String {
+    ctx.begin_block_transaction()
+        .await
+        .unwrap_or_else(|e| panic!("begin_block_transaction error: {:?}", e));
+    // a panic midway through must not leave the transaction open, or every later block fails
+    let block_id = match CatchUnwind(Box::pin(add_block(ctx))).await {
+        Ok(block_id) => block_id,
+        Err(panic) => {
+            ctx.rollback_block_transaction()
+                .await
+                .unwrap_or_else(|e| panic!("rollback_block_transaction error: {:?}", e));
+            std::panic::resume_unwind(panic);
+        }
+    };
+    ctx.commit_block_transaction()
+        .await
+        .unwrap_or_else(|e| panic!("commit_block_transaction error: {:?}", e));
+    block_id
+}
+
+// resolves to the panic instead of unwinding, if polling the inner future panics
+struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = std::thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        // writes made before the panic are rolled back, so no broken state is observed
+        let future = self.0.as_mut();
+        match std::panic::catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
+
+async fn add_block<T: Context>(ctx: &T) -> String {
     let (mut block, mut cache) = create_block(ctx).await;
     confirm_mempool_challenges(&block, &mut cache).await;
     confirm_mempool_algorithms(&block, &mut cache).await;
@@ -23,11 +66,13 @@ pub(crate) async fn execute<T: Context>(ctx: &T) -> String {
     update_solution_signature_thresholds(&block, &mut cache).await;
     update_fees(&block, &mut cache).await;
     update_influence(&block, &mut cache).await;
+    update_fraud_penalties(&block, &mut cache).await;
     update_adoption(&mut cache).await;
     update_innovator_rewards(&block, &mut cache).await;
     update_benchmarker_rewards(&block, &mut cache).await;
     update_merge_points(&block, &mut cache).await;
     update_merges(&block, &mut cache).await;
+    update_state_root(&mut block, &cache).await;
     commit_changes(ctx, &block, &mut cache).await;
     block.id
 }
@@ -39,6 +84,7 @@ struct AddBlockCache {
     pub mempool_precommits: Vec<Precommit>,
     pub mempool_proofs: Vec<Proof>,
     pub mempool_frauds: Vec<Fraud>,
+    pub unresolved_frauds: Vec<Fraud>,
     pub mempool_topups: Vec<TopUp>,
     pub mempool_wasms: Vec<Wasm>,
     pub confirmed_precommits: HashMap<String, Precommit>,
@@ -47,6 +93,8 @@ struct AddBlockCache {
     pub active_solutions: HashMap<String, (BenchmarkSettings, u32)>,
     pub active_players: HashMap<String, Player>,
     pub active_fee_players: HashMap<String, Player>,
+    // challenger of each fraud a slashed player is being slashed for. None if raised by the protocol
+    pub slashed_players: HashMap<String, Vec<Option<String>>>,
     pub prev_challenges: HashMap<String, Challenge>,
     pub prev_algorithms: HashMap<String, Algorithm>,
     pub prev_players: HashMap<String, Player>,
@@ -149,9 +197,25 @@ async fn setup_cache<T: Context>(
         }
         fraud.state = Some(FraudState {
             block_confirmed: None,
+            status: None,
+            block_resolved: None,
         });
         mempool_frauds.push(fraud);
     }
+    let mut unresolved_frauds = Vec::new();
+    for fraud in ctx
+        .get_frauds(FraudsFilter::Confirmed { from_block_started }, false)
+        .await
+        .unwrap_or_else(|e| panic!("get_frauds error: {:?}", e))
+    {
+        if fraud
+            .state()
+            .status
+            .is_some_and(|s| s == FraudStatus::Alleged || s == FraudStatus::Disputed)
+        {
+            unresolved_frauds.push(fraud);
+        }
+    }
     let mut mempool_topups = Vec::new();
     for mut topup in ctx
         .get_topups(TopUpsFilter::Mempool)
@@ -250,7 +314,10 @@ async fn setup_cache<T: Context>(
             .await
             .unwrap_or_else(|e| panic!("get_frauds error: {:?}", e))
             .pop();
-        if fraud.is_some_and(|f| f.state.is_some()) {
+        // solutions are removed as soon as a fraud is confirmed, and restored if it is overturned
+        if fraud.is_some_and(|f| {
+            *calc_fraud_state(&f, details.height, config).status() != FraudStatus::Overturned
+        }) {
             continue;
         }
         let state = proof.state();
@@ -296,9 +363,55 @@ async fn setup_cache<T: Context>(
             deposit: None,
             rolling_deposit: None,
             qualifying_percent_rolling_deposit: None,
+            fraud_penalty: None,
         });
         active_players.insert(player.id.clone(), player);
     }
+    let mut slashed_players = HashMap::<String, Vec<Option<String>>>::new();
+    if let Some(fraud_config) = &config.fraud {
+        // slash players with a fraud upheld within the last `slash_period` blocks
+        let resolved_after = details.height.saturating_sub(fraud_config.slash_period);
+        let mut upheld_frauds = Vec::new();
+        for fraud in ctx
+            .get_frauds(
+                FraudsFilter::Confirmed {
+                    from_block_started: resolved_after
+                        .saturating_sub(fraud_config.appeal_period)
+                        .saturating_sub(config.benchmark_submissions.lifespan_period),
+                },
+                false,
+            )
+            .await
+            .unwrap_or_else(|e| panic!("get_frauds error: {:?}", e))
+            .into_iter()
+            .chain(mempool_frauds.iter().cloned())
+        {
+            let state = calc_fraud_state(&fraud, details.height, config);
+            if *state.status() == FraudStatus::Upheld && *state.block_resolved() > resolved_after {
+                upheld_frauds.push((fraud.benchmark_id, fraud.challenger_id));
+            }
+        }
+        for (benchmark_id, challenger_id) in upheld_frauds {
+            let player_id = match confirmed_precommits.get(&benchmark_id) {
+                Some(precommit) => precommit.settings.player_id.clone(),
+                None => {
+                    ctx.get_precommits(PrecommitsFilter::BenchmarkId(benchmark_id.clone()))
+                        .await
+                        .unwrap_or_else(|e| panic!("get_precommits error: {:?}", e))
+                        .pop()
+                        .unwrap_or_else(|| panic!("Expecting precommit {} to exist", benchmark_id))
+                        .settings
+                        .player_id
+                }
+            };
+            if active_players.contains_key(&player_id) {
+                slashed_players
+                    .entry(player_id)
+                    .or_default()
+                    .push(challenger_id);
+            }
+        }
+    }
     let mut active_fee_players = HashMap::new();
     for topup in mempool_topups.iter() {
         let mut player = ctx
@@ -311,6 +424,7 @@ async fn setup_cache<T: Context>(
             player.state = Some(PlayerState {
                 total_fees_paid: Some(PreciseNumber::from(0)),
                 available_fee_balance: Some(PreciseNumber::from(0)),
+                total_fraud_bounties: None,
             });
         }
         active_fee_players.insert(player.id.clone(), player);
@@ -329,6 +443,26 @@ async fn setup_cache<T: Context>(
             player.state = Some(PlayerState {
                 total_fees_paid: Some(PreciseNumber::from(0)),
                 available_fee_balance: Some(PreciseNumber::from(0)),
+                total_fraud_bounties: None,
+            });
+        }
+        active_fee_players.insert(player.id.clone(), player);
+    }
+    for challenger_id in slashed_players.values().flatten().flatten() {
+        if active_fee_players.contains_key(challenger_id) {
+            continue;
+        }
+        let mut player = ctx
+            .get_players(PlayersFilter::Id(challenger_id.clone()), None)
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        if player.state.is_none() {
+            player.state = Some(PlayerState {
+                total_fees_paid: Some(PreciseNumber::from(0)),
+                available_fee_balance: Some(PreciseNumber::from(0)),
+                total_fraud_bounties: None,
             });
         }
         active_fee_players.insert(player.id.clone(), player);
@@ -380,6 +514,7 @@ async fn setup_cache<T: Context>(
         mempool_precommits,
         mempool_proofs,
         mempool_frauds,
+        unresolved_frauds,
         mempool_topups,
         mempool_wasms,
         confirmed_precommits,
@@ -388,6 +523,7 @@ async fn setup_cache<T: Context>(
         active_solutions,
         active_players,
         active_fee_players,
+        slashed_players,
         prev_challenges,
         prev_algorithms,
         prev_players,
@@ -424,6 +560,7 @@ async fn create_block<T: Context>(ctx: &T) -> (Block, AddBlockCache) {
         num_active_algorithms: None,
         num_active_benchmarks: None,
         num_active_players: None,
+        state_root: None,
     };
     let cache = setup_cache(ctx, &details, &config).await;
     details.fees_paid = Some(
@@ -507,9 +644,14 @@ async fn create_block<T: Context>(ctx: &T) -> (Block, AddBlockCache) {
 
 #[time]
 async fn confirm_mempool_challenges(block: &Block, cache: &mut AddBlockCache) {
+    let config = block.config();
     for challenge in cache.mempool_challenges.iter_mut() {
         let state = challenge.state.as_mut().unwrap();
         state.block_confirmed = Some(block.details.height);
+        state.round_active = config
+            .challenges
+            .as_ref()
+            .map(|c| block.details.round + c.activation_delay);
     }
 }
 
@@ -568,13 +710,14 @@ async fn confirm_mempool_benchmarks(block: &Block, cache: &mut AddBlockCache) {
         let precommit = &cache.confirmed_precommits[&benchmark.id];
         let solution_nonces = benchmark.solution_nonces.as_ref().unwrap();
         let num_nonces = *precommit.details.num_nonces.as_ref().unwrap() as usize;
+        let max_non_solution_samples = config
+            .benchmark_submissions
+            .max_non_solution_samples
+            .unwrap_or(config.benchmark_submissions.max_samples);
         if num_nonces > solution_nonces.len() {
             if num_nonces > solution_nonces.len() * 2 {
                 // use rejection sampling
-                let stop_length = config
-                    .benchmark_submissions
-                    .max_samples
-                    .min(num_nonces - solution_nonces.len())
+                let stop_length = max_non_solution_samples.min(num_nonces - solution_nonces.len())
                     + sampled_nonces.len();
                 while sampled_nonces.len() < stop_length {
                     let nonce = rng.gen_range(0..num_nonces as u64);
@@ -588,10 +731,7 @@ async fn confirm_mempool_benchmarks(block: &Block, cache: &mut AddBlockCache) {
                     .filter(|n| !solution_nonces.contains(n))
                     .collect();
                 non_solution_nonces.shuffle(&mut rng);
-                for nonce in non_solution_nonces
-                    .iter()
-                    .take(config.benchmark_submissions.max_samples)
-                {
+                for nonce in non_solution_nonces.iter().take(max_non_solution_samples) {
                     sampled_nonces.insert(*nonce);
                 }
             }
@@ -615,11 +755,55 @@ async fn confirm_mempool_proofs(block: &Block, cache: &mut AddBlockCache) {
 
 #[time]
 async fn confirm_mempool_frauds(block: &Block, cache: &mut AddBlockCache) {
-    // Future Todo: slash player's rewards from past day
-    for fraud in cache.mempool_frauds.iter_mut() {
-        let state = fraud.state.as_mut().unwrap();
-        state.block_confirmed = Some(block.details.height);
+    let config = block.config();
+    for fraud in cache
+        .mempool_frauds
+        .iter_mut()
+        .chain(cache.unresolved_frauds.iter_mut())
+    {
+        fraud.state = Some(calc_fraud_state(fraud, block.details.height, config));
+    }
+}
+
+/// State of `fraud` after the block at `height` is added. Frauds are confirmed as alleged,
+/// become disputed once appealed, and are resolved when the appeal period ends
+fn calc_fraud_state(fraud: &Fraud, height: u32, config: &ProtocolConfig) -> FraudState {
+    let appeal_period = config.fraud.as_ref().map_or(0, |c| c.appeal_period);
+    let mut state = match &fraud.state {
+        Some(state) if state.block_confirmed.is_some() => state.clone(),
+        // confirmed in this block
+        _ => FraudState {
+            block_confirmed: Some(height),
+            status: Some(FraudStatus::Alleged),
+            block_resolved: None,
+        },
+    };
+    let block_confirmed = *state.block_confirmed();
+    match state.status {
+        // confirmed before appeals were introduced
+        None => {
+            state.status = Some(FraudStatus::Upheld);
+            state.block_resolved = Some(block_confirmed);
+        }
+        Some(FraudStatus::Alleged) | Some(FraudStatus::Disputed) => {
+            if height >= block_confirmed + appeal_period {
+                let overturned = fraud
+                    .appeal
+                    .as_ref()
+                    .is_some_and(|a| a.verification_error.is_none());
+                state.status = Some(if overturned {
+                    FraudStatus::Overturned
+                } else {
+                    FraudStatus::Upheld
+                });
+                state.block_resolved = Some(height);
+            } else if fraud.appeal.is_some() {
+                state.status = Some(FraudStatus::Disputed);
+            }
+        }
+        Some(FraudStatus::Upheld) | Some(FraudStatus::Overturned) => {}
     }
+    state
 }
 
 #[time]
@@ -869,54 +1053,6 @@ async fn update_fees(block: &Block, cache: &mut AddBlockCache) {
     }
 }
 
-fn find_smallest_range_dimension(points: &Frontier) -> usize {
-    (0..2)
-        .min_by_key(|&d| {
-            let (min, max) = points
-                .iter()
-                .map(|p| p[d])
-                .fold((i32::MAX, i32::MIN), |(min, max), val| {
-                    (min.min(val), max.max(val))
-                });
-            max - min
-        })
-        .unwrap()
-}
-
-fn pareto_algorithm(points: Frontier, only_one: bool) -> Vec<Frontier> {
-    if points.is_empty() {
-        return Vec::new();
-    }
-    let dimension = find_smallest_range_dimension(&points);
-    let sort_dimension = 1 - dimension;
-
-    let mut buckets: HashMap<i32, Vec<Point>> = HashMap::new();
-    for point in points {
-        buckets.entry(point[dimension]).or_default().push(point);
-    }
-    for (_, group) in buckets.iter_mut() {
-        // sort descending
-        group.sort_unstable_by(|a, b| b[sort_dimension].cmp(&a[sort_dimension]));
-    }
-    let mut result = Vec::new();
-    while !buckets.is_empty() {
-        let points: HashSet<Point> = buckets.values().map(|group| group[0].clone()).collect();
-        let frontier = points.pareto_frontier();
-        for point in frontier.iter() {
-            let bucket = buckets.get_mut(&point[dimension]).unwrap();
-            bucket.remove(0);
-            if bucket.is_empty() {
-                buckets.remove(&point[dimension]);
-            }
-        }
-        result.push(frontier);
-        if only_one {
-            break;
-        }
-    }
-    result
-}
-
 #[time]
 async fn update_qualifiers(block: &Block, cache: &mut AddBlockCache) {
     let config = block.config();
@@ -1187,6 +1323,60 @@ async fn update_influence(block: &Block, cache: &mut AddBlockCache) {
     }
 }
 
+#[time]
+async fn update_fraud_penalties(block: &Block, cache: &mut AddBlockCache) {
+    let config = block.config();
+    let fraud_config = match &config.fraud {
+        Some(fraud_config) => fraud_config,
+        None => return, // Fraud slashing not implemented for these blocks
+    };
+    let slash_percent = PreciseNumber::from_f64(fraud_config.slash_percent.clamp(0.0, 1.0));
+    let bounty_percent =
+        PreciseNumber::from_f64(fraud_config.challenge_bounty_percent.clamp(0.0, 1.0));
+
+    // slashed influence is not redistributed, so the forfeited rewards are never emitted
+    let reward_pool = PreciseNumber::from_f64(get_block_reward(block))
+        * PreciseNumber::from_f64(config.rewards.distribution.benchmarkers);
+    let zero = PreciseNumber::from(0);
+    let mut bounties = HashMap::<String, PreciseNumber>::new();
+    for player in cache.active_players.values_mut() {
+        let data = player.block_data.as_mut().unwrap();
+        let challenger_ids = match cache.slashed_players.get(&player.id) {
+            Some(challenger_ids) => challenger_ids,
+            None => {
+                data.fraud_penalty = Some(zero);
+                continue;
+            }
+        };
+        let influence = *data.influence();
+        let slashed_influence = influence * slash_percent;
+        let fraud_penalty = slashed_influence * reward_pool;
+        data.influence = Some(influence - slashed_influence);
+        data.fraud_penalty = Some(fraud_penalty);
+
+        // each upheld fraud accounts for an equal share of the penalty. Bounties are paid out
+        // of the penalty, and the rest of it is never emitted
+        let bounty = fraud_penalty * bounty_percent / PreciseNumber::from(challenger_ids.len());
+        let mut unpaid_penalty = fraud_penalty;
+        for challenger_id in challenger_ids.iter().flatten() {
+            let bounty = bounty.min(unpaid_penalty);
+            unpaid_penalty -= bounty;
+            *bounties.entry(challenger_id.clone()).or_insert(zero) += bounty;
+        }
+    }
+    for (challenger_id, bounty) in bounties {
+        let state = cache
+            .active_fee_players
+            .get_mut(&challenger_id)
+            .unwrap()
+            .state
+            .as_mut()
+            .unwrap();
+        *state.available_fee_balance.get_or_insert(zero) += bounty;
+        *state.total_fraud_bounties.get_or_insert(zero) += bounty;
+    }
+}
+
 #[time]
 async fn update_adoption(cache: &mut AddBlockCache) {
     let mut algorithms_by_challenge = HashMap::<String, Vec<&mut Algorithm>>::new();
@@ -1357,8 +1547,35 @@ async fn update_merges(block: &Block, cache: &mut AddBlockCache) {
     }
 }
 
+#[time]
+async fn update_state_root(block: &mut Block, cache: &AddBlockCache) {
+    let mut leafs = Vec::new();
+    for challenge in cache.active_challenges.values() {
+        leafs.push(StateLeaf::Challenge {
+            id: challenge.id.clone(),
+            block_data: challenge.block_data().clone(),
+        });
+    }
+    for algorithm in cache.active_algorithms.values() {
+        leafs.push(StateLeaf::Algorithm {
+            id: algorithm.id.clone(),
+            block_data: algorithm.block_data().clone(),
+        });
+    }
+    for player in cache.active_players.values() {
+        leafs.push(StateLeaf::Player {
+            id: player.id.clone(),
+            block_data: player.block_data().clone(),
+        });
+    }
+    block.details.state_root = Some(StateTree::new(leafs).calc_state_root());
+}
+
 #[time]
 async fn commit_changes<T: Context>(ctx: &T, block: &Block, cache: &mut AddBlockCache) {
+    ctx.update_block_details(&block.id, block.details.clone())
+        .await
+        .unwrap_or_else(|e| panic!("update_block_details error: {:?}", e));
     for precommit in cache.mempool_precommits.drain(..) {
         ctx.update_precommit_state(&precommit.benchmark_id, precommit.state.unwrap())
             .await
@@ -1379,7 +1596,11 @@ async fn commit_changes<T: Context>(ctx: &T, block: &Block, cache: &mut AddBlock
             .await
             .unwrap_or_else(|e| panic!("update_benchmark_state error: {:?}", e));
     }
-    for fraud in cache.mempool_frauds.drain(..) {
+    for fraud in cache
+        .mempool_frauds
+        .drain(..)
+        .chain(cache.unresolved_frauds.drain(..))
+    {
         ctx.update_fraud_state(&fraud.benchmark_id, fraud.state.unwrap())
             .await
             .unwrap_or_else(|e| panic!("update_fraud_state error: {:?}", e));
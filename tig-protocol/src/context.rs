@@ -133,6 +133,16 @@ pub trait Context {
         nonce: u64,
         wasm_vm_config: &WasmVMConfig,
     ) -> ContextResult<anyhow::Result<OutputData>>;
+    // Second verifier used for fraud appeals. Should not share infrastructure with
+    // `compute_solution`, so that faults in one are not repeated by the other
+    async fn recompute_solution(
+        &self,
+        _settings: &BenchmarkSettings,
+        _nonce: u64,
+        _wasm_vm_config: &WasmVMConfig,
+    ) -> ContextResult<anyhow::Result<OutputData>> {
+        Err(anyhow::anyhow!("recompute_solution is not supported"))
+    }
     async fn get_transaction(&self, tx_hash: &String) -> ContextResult<Transaction>;
     async fn get_latest_eth_block_num(&self) -> ContextResult<String>;
     async fn get_player_deposit(
@@ -175,6 +185,22 @@ pub trait Context {
         benchmark_id: &String,
         allegation: String,
     ) -> ContextResult<()>;
+    async fn add_fraud_challenge_to_mempool(
+        &self,
+        _benchmark_id: &String,
+        _challenger_id: &String,
+        _allegation: String,
+        _evidence: FraudEvidence,
+    ) -> ContextResult<()> {
+        Err(anyhow::anyhow!("Fraud challenges are not supported"))
+    }
+    async fn add_fraud_appeal_to_mempool(
+        &self,
+        _benchmark_id: &String,
+        _appeal: FraudAppeal,
+    ) -> ContextResult<()> {
+        Err(anyhow::anyhow!("Fraud appeals are not supported"))
+    }
     async fn add_topup_to_mempool(
         &self,
         topup_id: &String,
@@ -187,6 +213,15 @@ pub trait Context {
     ) -> ContextResult<()>;
 
     // Updates
+    // Called once per block with the `state_root`, which is only known after the block was added.
+    // Defaults to dropping it, for implementations that do not store state roots
+    async fn update_block_details(
+        &self,
+        _block_id: &String,
+        _details: BlockDetails,
+    ) -> ContextResult<()> {
+        Ok(())
+    }
     async fn update_challenge_state(
         &self,
         challenge_id: &String,
@@ -243,4 +278,19 @@ pub trait Context {
     ) -> ContextResult<()>;
     async fn update_wasm_state(&self, algorithm_id: &String, state: WasmState)
         -> ContextResult<()>;
+
+    // Transactions
+    // All writes made by `add_block` happen between begin & commit, and are rolled back if it
+    // fails. Implementations must persist either all of them or none of them. Writes from
+    // other callers (e.g. mempool submissions) are not part of the transaction and must survive
+    // a rollback. Defaults do nothing, for implementations that cannot roll back
+    async fn begin_block_transaction(&self) -> ContextResult<()> {
+        Ok(())
+    }
+    async fn commit_block_transaction(&self) -> ContextResult<()> {
+        Ok(())
+    }
+    async fn rollback_block_transaction(&self) -> ContextResult<()> {
+        Ok(())
+    }
 }
@@ -1,4 +1,8 @@
-use tig_structs::{config::DifficultyParameter, core::BenchmarkSettings};
+pub use tig_challenges::VerificationError;
+use tig_structs::{
+    config::DifficultyParameter,
+    core::{BenchmarkSettings, StateLeafType},
+};
 use tig_utils::PreciseNumber;
 
 #[derive(Debug, PartialEq)]
@@ -15,6 +19,9 @@ pub enum ProtocolError {
     DuplicateBenchmarkSettings {
         settings: BenchmarkSettings,
     },
+    DuplicateFraudAppeal {
+        benchmark_id: String,
+    },
     DuplicateNonce {
         nonce: u64,
     },
@@ -27,6 +34,9 @@ pub enum ProtocolError {
     FlaggedAsFraud {
         benchmark_id: String,
     },
+    FraudNotAppealable {
+        benchmark_id: String,
+    },
     InsufficientLifespan,
     InsufficientSolutions {
         min_num_solutions: usize,
@@ -51,6 +61,15 @@ pub enum ProtocolError {
     InvalidChallenge {
         challenge_id: String,
     },
+    InvalidFraud {
+        benchmark_id: String,
+    },
+    InvalidFraudChallenger {
+        benchmark_id: String,
+    },
+    InvalidFraudEvidence {
+        nonce: u64,
+    },
     InvalidDifficulty {
         difficulty: Vec<i32>,
         difficulty_parameters: Vec<DifficultyParameter>,
@@ -75,6 +94,12 @@ pub enum ProtocolError {
     },
     InvalidSolution {
         nonce: u64,
+        reason: VerificationError,
+    },
+    InvalidStateLeaf {
+        block_id: String,
+        leaf_type: StateLeafType,
+        id: String,
     },
     InvalidSolutionData {
         algorithm_id: String,
@@ -107,6 +132,12 @@ pub enum ProtocolError {
     InvalidTransaction {
         tx_hash: String,
     },
+    VerifierUnavailable {
+        msg: String,
+    },
+    WithheldSolution {
+        nonce: u64,
+    },
 }
 
 impl std::fmt::Display for ProtocolError {
@@ -132,6 +163,9 @@ impl std::fmt::Display for ProtocolError {
             ProtocolError::DuplicateBenchmarkSettings { settings }=> {
                 write!(f, "A benchmark with settings '{:?}' has been submitted before.", settings)
             }
+            ProtocolError::DuplicateFraudAppeal { benchmark_id } => {
+                write!(f, "Fraud for benchmark '{}' has already been appealed", benchmark_id)
+            }
             ProtocolError::DuplicateNonce { nonce } => write!(
                 f,
                 "Nonce '{}' is submitted more than once",
@@ -148,6 +182,9 @@ impl std::fmt::Display for ProtocolError {
             ProtocolError::FlaggedAsFraud { benchmark_id } => {
                 write!(f, "Benchmark '{}' is flagged as fraud", benchmark_id)
             }
+            ProtocolError::FraudNotAppealable { benchmark_id } => {
+                write!(f, "Fraud for benchmark '{}' is no longer open to appeal", benchmark_id)
+            }
             ProtocolError::InsufficientFeeBalance {
                 fee_paid,
                 available_fee_balance,
@@ -182,6 +219,15 @@ impl std::fmt::Display for ProtocolError {
             ProtocolError::InvalidChallenge { challenge_id } => {
                 write!(f, "Challenge '{}' either does not exist or in not yet active", challenge_id)
             }
+            ProtocolError::InvalidFraud { benchmark_id } => {
+                write!(f, "Fraud for benchmark '{}' either does not exist or is not yet confirmed", benchmark_id)
+            }
+            ProtocolError::InvalidFraudChallenger { benchmark_id } => {
+                write!(f, "Benchmark '{}' cannot be challenged by its own player", benchmark_id)
+            }
+            ProtocolError::InvalidFraudEvidence { nonce } => {
+                write!(f, "Fraud evidence for nonce '{}' is invalid. Recomputed output must match and contradict the merkle leaf", nonce)
+            }
             ProtocolError::InvalidDifficulty {
                 difficulty,
                 difficulty_parameters,
@@ -220,9 +266,18 @@ impl std::fmt::Display for ProtocolError {
                 "Solution data for nonce '{}' produces invalid solution signature. Expected: '{}', Actual: '{}'",
                 nonce, expected_signature, actual_signature
             ),
-            ProtocolError::InvalidSolution { nonce } => {
-                write!(f, "Solution for nonce '{}' is invalid", nonce)
+            ProtocolError::InvalidSolution { nonce, reason } => {
+                write!(f, "Solution for nonce '{}' is invalid: {}", nonce, reason)
             }
+            ProtocolError::InvalidStateLeaf {
+                block_id,
+                leaf_type,
+                id,
+            } => write!(
+                f,
+                "No {:?} '{}' in the state of block '{}'",
+                leaf_type, id, block_id
+            ),
             ProtocolError::InvalidSolutionData {
                 algorithm_id,
                 nonce,
@@ -278,6 +333,14 @@ impl std::fmt::Display for ProtocolError {
                 "Transaction '{}' is invalid",
                 tx_hash
             ),
+            ProtocolError::VerifierUnavailable { msg } => {
+                write!(f, "Second verifier is unavailable, try again later: {}", msg)
+            }
+            ProtocolError::WithheldSolution { nonce } => write!(
+                f,
+                "Benchmark nonce '{}' has a valid solution but was not submitted as a solution",
+                nonce
+            ),
         }
     }
 }
@@ -0,0 +1,84 @@
+use crate::{context::*, error::*};
+use logging_timer::time;
+use tig_structs::core::*;
+
+#[time]
+pub(crate) async fn execute<T: Context>(
+    ctx: &T,
+    block_id: &str,
+    leaf_type: StateLeafType,
+    id: &String,
+) -> ProtocolResult<StateProof> {
+    let block = ctx
+        .get_block(BlockFilter::Id(block_id.to_string()), true)
+        .await
+        .unwrap_or_else(|e| panic!("get_block error: {:?}", e))
+        .ok_or_else(|| ProtocolError::InvalidBlock {
+            block_id: block_id.to_string(),
+        })?;
+    let state_tree = StateTree::new(get_state_leafs(ctx, &block).await);
+    state_tree
+        .calc_state_proof(leaf_type, id)
+        .ok_or_else(|| ProtocolError::InvalidStateLeaf {
+            block_id: block_id.to_string(),
+            leaf_type,
+            id: id.clone(),
+        })
+}
+
+#[time]
+async fn get_state_leafs<T: Context>(ctx: &T, block: &Block) -> Vec<StateLeaf> {
+    let block_filter = Some(BlockFilter::Id(block.id.clone()));
+    let data = block.data();
+    let mut leafs = Vec::new();
+    for challenge_id in data.active_challenge_ids.iter() {
+        for challenge in ctx
+            .get_challenges(
+                ChallengesFilter::Id(challenge_id.clone()),
+                block_filter.clone(),
+            )
+            .await
+            .unwrap_or_else(|e| panic!("get_challenges error: {:?}", e))
+        {
+            leafs.push(StateLeaf::Challenge {
+                id: challenge.id,
+                block_data: challenge
+                    .block_data
+                    .expect("Expecting active challenge to have block_data"),
+            });
+        }
+    }
+    for algorithm_id in data.active_algorithm_ids.iter() {
+        for algorithm in ctx
+            .get_algorithms(
+                AlgorithmsFilter::Id(algorithm_id.clone()),
+                block_filter.clone(),
+                false,
+            )
+            .await
+            .unwrap_or_else(|e| panic!("get_algorithms error: {:?}", e))
+        {
+            leafs.push(StateLeaf::Algorithm {
+                id: algorithm.id,
+                block_data: algorithm
+                    .block_data
+                    .expect("Expecting active algorithm to have block_data"),
+            });
+        }
+    }
+    for player_id in data.active_player_ids.iter() {
+        for player in ctx
+            .get_players(PlayersFilter::Id(player_id.clone()), block_filter.clone())
+            .await
+            .unwrap_or_else(|e| panic!("get_players error: {:?}", e))
+        {
+            leafs.push(StateLeaf::Player {
+                id: player.id,
+                block_data: player
+                    .block_data
+                    .expect("Expecting active player to have block_data"),
+            });
+        }
+    }
+    leafs
+}
@@ -0,0 +1,1145 @@
+use crate::{context::*, Mempool};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::RwLock,
+};
+use tig_structs::{config::*, core::*};
+use tig_utils::*;
+
+pub type GetTransactionFn = Box<dyn Fn(&String) -> ContextResult<Transaction> + Send + Sync>;
+pub type GetPlayerDepositFn =
+    Box<dyn Fn(&String, &String) -> ContextResult<Option<PreciseNumber>> + Send + Sync>;
+pub type ComputeSolutionFn = Box<
+    dyn Fn(
+            &BenchmarkSettings,
+            &String,
+            u64,
+            &WasmVMConfig,
+        ) -> ContextResult<anyhow::Result<OutputData>>
+        + Send
+        + Sync,
+>;
+pub type VerifySolutionFn = Box<
+    dyn Fn(&BenchmarkSettings, &String, u64, &Solution) -> ContextResult<anyhow::Result<()>>
+        + Send
+        + Sync,
+>;
+
+/// A `Context` that keeps all protocol data in memory.
+///
+/// Starts from a genesis block at height 0. Players have to be registered with `add_player`.
+/// Writes made by `add_block` during a block transaction are discarded on rollback. Writes from
+/// other callers made at the same time (e.g. mempool submissions) are kept.
+/// External lookups (transactions, deposits, solution computation & verification) are stubs
+/// that can be replaced with the `with_*` methods.
+pub struct InMemoryContext {
+    state: RwLock<InMemoryState>,
+    get_transaction_fn: GetTransactionFn,
+    get_player_deposit_fn: GetPlayerDepositFn,
+    compute_solution_fn: ComputeSolutionFn,
+    recompute_solution_fn: Option<ComputeSolutionFn>,
+    verify_solution_fn: VerifySolutionFn,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct InMemoryState {
+    config: Option<ProtocolConfig>,
+    eth_block_num: String,
+    blocks: Vec<Block>,
+    block_heights: HashMap<String, u32>,
+    algorithms: BTreeMap<String, Algorithm>,
+    benchmarks: BTreeMap<String, Benchmark>,
+    challenges: BTreeMap<String, Challenge>,
+    frauds: BTreeMap<String, Fraud>,
+    players: BTreeMap<String, Player>,
+    precommits: BTreeMap<String, Precommit>,
+    proofs: BTreeMap<String, Proof>,
+    topups: BTreeMap<String, TopUp>,
+    wasms: BTreeMap<String, Wasm>,
+    algorithm_block_data: HashMap<String, HashMap<String, AlgorithmBlockData>>,
+    challenge_block_data: HashMap<String, HashMap<String, ChallengeBlockData>>,
+    player_block_data: HashMap<String, HashMap<String, PlayerBlockData>>,
+    // id to use for the next block instead of the hash of its details
+    #[serde(skip)]
+    next_block_id: Option<String>,
+    // state before the open block transaction
+    #[serde(skip)]
+    snapshot: Option<Box<InMemoryState>>,
+}
+
+impl InMemoryContext {
+    pub fn new(config: ProtocolConfig) -> Self {
+        let details = BlockDetails {
+            prev_block_id: String::new(),
+            height: 0,
+            round: 1,
+            eth_block_num: Some("0".to_string()),
+            fees_paid: Some(PreciseNumber::from(0)),
+            num_confirmed_challenges: Some(0),
+            num_confirmed_algorithms: Some(0),
+            num_confirmed_benchmarks: Some(0),
+            num_confirmed_precommits: Some(0),
+            num_confirmed_proofs: Some(0),
+            num_confirmed_frauds: Some(0),
+            num_confirmed_topups: Some(0),
+            num_confirmed_wasms: Some(0),
+            num_active_challenges: Some(0),
+            num_active_algorithms: Some(0),
+            num_active_benchmarks: Some(0),
+            num_active_players: Some(0),
+            state_root: None,
+        };
+        let data = BlockData {
+            confirmed_challenge_ids: HashSet::new(),
+            confirmed_algorithm_ids: HashSet::new(),
+            confirmed_benchmark_ids: HashSet::new(),
+            confirmed_precommit_ids: HashSet::new(),
+            confirmed_proof_ids: HashSet::new(),
+            confirmed_fraud_ids: HashSet::new(),
+            confirmed_topup_ids: HashSet::new(),
+            confirmed_wasm_ids: HashSet::new(),
+            active_challenge_ids: HashSet::new(),
+            active_algorithm_ids: HashSet::new(),
+            active_benchmark_ids: HashSet::new(),
+            active_player_ids: HashSet::new(),
+        };
+        let mut state = InMemoryState {
+            eth_block_num: "0".to_string(),
+            config: Some(config.clone()),
+            ..Default::default()
+        };
+        state.insert_block(details, data, config);
+        Self {
+            state: RwLock::new(state),
+            get_transaction_fn: Box::new(|tx_hash| {
+                Err(anyhow!("Transaction '{}' not found", tx_hash))
+            }),
+            get_player_deposit_fn: Box::new(|_, _| Ok(None)),
+            compute_solution_fn: Box::new(|_, _, _, _| {
+                Err(anyhow!("compute_solution is not configured"))
+            }),
+            recompute_solution_fn: None,
+            verify_solution_fn: Box::new(|_, _, _, _| Ok(Ok(()))),
+        }
+    }
+
+    /// Called with `tx_hash`
+    pub fn with_get_transaction<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&String) -> ContextResult<Transaction> + Send + Sync + 'static,
+    {
+        self.get_transaction_fn = Box::new(f);
+        self
+    }
+
+    /// Called with `eth_block_num` and `player_id`
+    pub fn with_get_player_deposit<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&String, &String) -> ContextResult<Option<PreciseNumber>> + Send + Sync + 'static,
+    {
+        self.get_player_deposit_fn = Box::new(f);
+        self
+    }
+
+    /// Called with `settings`, `rand_hash` of the precommit, `nonce` and `wasm_vm_config`
+    pub fn with_compute_solution<F>(mut self, f: F) -> Self
+    where
+        F: Fn(
+                &BenchmarkSettings,
+                &String,
+                u64,
+                &WasmVMConfig,
+            ) -> ContextResult<anyhow::Result<OutputData>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.compute_solution_fn = Box::new(f);
+        self
+    }
+
+    /// Second verifier used for fraud appeals. Defaults to the `compute_solution` stub.
+    /// Called with `settings`, `rand_hash` of the precommit, `nonce` and `wasm_vm_config`
+    pub fn with_recompute_solution<F>(mut self, f: F) -> Self
+    where
+        F: Fn(
+                &BenchmarkSettings,
+                &String,
+                u64,
+                &WasmVMConfig,
+            ) -> ContextResult<anyhow::Result<OutputData>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.recompute_solution_fn = Some(Box::new(f));
+        self
+    }
+
+    /// Called with `settings`, `rand_hash` of the precommit, `nonce` and `solution`
+    pub fn with_verify_solution<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&BenchmarkSettings, &String, u64, &Solution) -> ContextResult<anyhow::Result<()>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.verify_solution_fn = Box::new(f);
+        self
+    }
+
+    /// Verifies solutions with the challenges in `tig_challenges::challenge_registry`
+    pub fn with_challenge_registry(self) -> Self {
+        self.with_verify_solution(|settings, rand_hash, nonce, solution| {
+            let challenge = match tig_challenges::challenge_registry().get(&settings.challenge_id) {
+                Some(challenge) => challenge,
+                None => {
+                    return Ok(Err(anyhow!(
+                        "Unknown challenge '{}'",
+                        settings.challenge_id
+                    )))
+                }
+            };
+            Ok(challenge
+                .generate_instance(settings.calc_seed(rand_hash, nonce), &settings.difficulty)
+                .and_then(|instance| {
+                    instance.verify_solution(&serde_json::Value::Object(solution.clone()))
+                }))
+        })
+    }
+
+    pub fn add_player(&self, player_id: &str, details: PlayerDetails) {
+        let _ = self.write_outside_block(|state| {
+            state.players.insert(
+                player_id.to_string(),
+                Player {
+                    id: player_id.to_string(),
+                    details: details.clone(),
+                    state: None,
+                    block_data: None,
+                },
+            );
+            Ok(())
+        });
+    }
+
+    /// Config used for the next block. Existing blocks keep the config they were created with
+    pub fn set_config(&self, config: ProtocolConfig) {
+        let _ = self.write_outside_block(|state| {
+            state.config = Some(config.clone());
+            Ok(())
+        });
+    }
+
+    pub fn set_eth_block_num(&self, eth_block_num: String) {
+        let _ = self.write_outside_block(|state| {
+            state.eth_block_num = eth_block_num.clone();
+            Ok(())
+        });
+    }
+
+    /// Serializes all data (excluding the stubs) so that it can be restored with `import_state`
+    pub fn export_state(&self) -> String {
+        jsonify(&*self.state.read().unwrap())
+    }
+
+    /// Replaces all data with the output of `export_state`. Stubs are kept as is
+    pub fn import_state(self, state: &str) -> ContextResult<Self> {
+        *self.state.write().unwrap() = dejsonify(state)?;
+        Ok(self)
+    }
+
+    /// Id to use for the next block instead of the hash of its details. Used when replaying
+    /// blocks that were recorded elsewhere
+    pub fn set_next_block_id(&self, block_id: String) {
+        self.state.write().unwrap().next_block_id = Some(block_id);
+    }
+
+    /// Inserts mempool items as is, keeping their ids. Existing items with the same id are
+    /// replaced
+    pub fn insert_mempool(&self, mempool: &Mempool) {
+        let mut state = self.state.write().unwrap();
+        for p in mempool.players.iter() {
+            state
+                .players
+                .entry(p.id.clone())
+                .or_insert_with(|| p.clone());
+        }
+        for c in mempool.challenges.iter() {
+            state.challenges.insert(c.id.clone(), c.clone());
+        }
+        for a in mempool.algorithms.iter() {
+            state.algorithms.insert(a.id.clone(), a.clone());
+        }
+        for p in mempool.precommits.iter() {
+            state.precommits.insert(p.benchmark_id.clone(), p.clone());
+        }
+        for b in mempool.benchmarks.iter() {
+            state.benchmarks.insert(b.id.clone(), b.clone());
+        }
+        for p in mempool.proofs.iter() {
+            state.proofs.insert(p.benchmark_id.clone(), p.clone());
+        }
+        for f in mempool.frauds.iter().chain(mempool.fraud_appeals.iter()) {
+            state.frauds.insert(f.benchmark_id.clone(), f.clone());
+        }
+        for t in mempool.topups.iter() {
+            state.topups.insert(t.id.clone(), t.clone());
+        }
+        for w in mempool.wasms.iter() {
+            state.wasms.insert(w.algorithm_id.clone(), w.clone());
+        }
+    }
+
+    // Applies a write that is not made by `add_block`, e.g. a mempool submission. It is also
+    // applied to the snapshot of an open block transaction, so that a rollback keeps it
+    fn write_outside_block<T>(
+        &self,
+        f: impl Fn(&mut InMemoryState) -> ContextResult<T>,
+    ) -> ContextResult<T> {
+        let mut state = self.state.write().unwrap();
+        if let Some(snapshot) = state.snapshot.as_deref_mut() {
+            f(snapshot)?;
+        }
+        f(&mut state)
+    }
+
+    fn get_rand_hash(&self, settings: &BenchmarkSettings) -> ContextResult<String> {
+        self.state
+            .read()
+            .unwrap()
+            .precommits
+            .values()
+            .find(|p| p.settings == *settings)
+            .and_then(|p| p.state.as_ref())
+            .and_then(|s| s.rand_hash.clone())
+            .ok_or_else(|| anyhow!("No confirmed precommit with settings '{:?}'", settings))
+    }
+}
+
+impl InMemoryState {
+    fn insert_block(
+        &mut self,
+        details: BlockDetails,
+        data: BlockData,
+        config: ProtocolConfig,
+    ) -> String {
+        let block_id = self
+            .next_block_id
+            .take()
+            .unwrap_or_else(|| hash_id(&jsonify(&details)));
+        self.block_heights.insert(block_id.clone(), details.height);
+        self.blocks.push(Block {
+            id: block_id.clone(),
+            details,
+            data: Some(data),
+            config: Some(config),
+        });
+        block_id
+    }
+
+    fn find_block(&self, filter: &BlockFilter) -> Option<&Block> {
+        match filter {
+            BlockFilter::Latest => self.blocks.last(),
+            BlockFilter::Height(height) => self.blocks.get(*height as usize),
+            BlockFilter::Id(id) => self
+                .block_heights
+                .get(id)
+                .and_then(|h| self.blocks.get(*h as usize)),
+            BlockFilter::Round(round) => {
+                self.blocks.iter().rev().find(|b| b.details.round == *round)
+            }
+        }
+    }
+
+    fn find_block_id(&self, filter: &Option<BlockFilter>) -> Option<String> {
+        filter
+            .as_ref()
+            .and_then(|f| self.find_block(f))
+            .map(|b| b.id.clone())
+    }
+
+    fn block_started(&self, benchmark_id: &String) -> Option<u32> {
+        self.precommits
+            .get(benchmark_id)
+            .map(|p| p.details.block_started)
+    }
+
+    fn started_from(&self, benchmark_id: &String, from_block_started: u32) -> bool {
+        self.block_started(benchmark_id)
+            .is_some_and(|b| b >= from_block_started)
+    }
+}
+
+fn hash_id(input: &str) -> String {
+    u8s_from_str(input)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+impl Context for InMemoryContext {
+    async fn get_algorithms(
+        &self,
+        filter: AlgorithmsFilter,
+        block_data: Option<BlockFilter>,
+        include_data: bool,
+    ) -> ContextResult<Vec<Algorithm>> {
+        let state = self.state.read().unwrap();
+        let block_id = state.find_block_id(&block_data);
+        Ok(state
+            .algorithms
+            .values()
+            .filter(|a| match &filter {
+                AlgorithmsFilter::Id(id) => a.id == *id,
+                AlgorithmsFilter::Name(name) => a.details.name == *name,
+                AlgorithmsFilter::TxHash(tx_hash) => a.details.tx_hash == *tx_hash,
+                AlgorithmsFilter::Mempool => a.state.is_none(),
+                AlgorithmsFilter::Confirmed => a.state.is_some(),
+            })
+            .map(|a| {
+                let mut a = a.clone();
+                a.block_data = block_id.as_ref().and_then(|block_id| {
+                    state
+                        .algorithm_block_data
+                        .get(block_id)
+                        .and_then(|d| d.get(&a.id))
+                        .cloned()
+                });
+                if !include_data {
+                    a.code = None;
+                }
+                a
+            })
+            .collect())
+    }
+
+    async fn get_benchmarks(
+        &self,
+        filter: BenchmarksFilter,
+        include_data: bool,
+    ) -> ContextResult<Vec<Benchmark>> {
+        let state = self.state.read().unwrap();
+        Ok(state
+            .benchmarks
+            .values()
+            .filter(|b| match &filter {
+                BenchmarksFilter::Id(id) => b.id == *id,
+                BenchmarksFilter::Mempool { from_block_started } => {
+                    b.state.is_none() && state.started_from(&b.id, *from_block_started)
+                }
+                BenchmarksFilter::Confirmed { from_block_started } => {
+                    b.state.is_some() && state.started_from(&b.id, *from_block_started)
+                }
+            })
+            .map(|b| {
+                let mut b = b.clone();
+                if !include_data {
+                    b.solution_nonces = None;
+                }
+                b
+            })
+            .collect())
+    }
+
+    async fn get_block(
+        &self,
+        filter: BlockFilter,
+        include_data: bool,
+    ) -> ContextResult<Option<Block>> {
+        let state = self.state.read().unwrap();
+        Ok(state.find_block(&filter).map(|b| {
+            let mut b = b.clone();
+            if !include_data {
+                b.data = None;
+            }
+            b
+        }))
+    }
+
+    async fn get_challenges(
+        &self,
+        filter: ChallengesFilter,
+        block_data: Option<BlockFilter>,
+    ) -> ContextResult<Vec<Challenge>> {
+        let state = self.state.read().unwrap();
+        let block_id = state.find_block_id(&block_data);
+        Ok(state
+            .challenges
+            .values()
+            .filter(|c| match &filter {
+                ChallengesFilter::Id(id) => c.id == *id,
+                ChallengesFilter::Name(name) => c.details.name == *name,
+                ChallengesFilter::Mempool => c.state.is_none(),
+                ChallengesFilter::Confirmed => c.state.is_some(),
+            })
+            .map(|c| {
+                let mut c = c.clone();
+                c.block_data = block_id.as_ref().and_then(|block_id| {
+                    state
+                        .challenge_block_data
+                        .get(block_id)
+                        .and_then(|d| d.get(&c.id))
+                        .cloned()
+                });
+                c
+            })
+            .collect())
+    }
+
+    async fn get_config(&self) -> ContextResult<ProtocolConfig> {
+        Ok(self.state.read().unwrap().config.clone().unwrap())
+    }
+
+    async fn get_frauds(
+        &self,
+        filter: FraudsFilter,
+        include_data: bool,
+    ) -> ContextResult<Vec<Fraud>> {
+        let state = self.state.read().unwrap();
+        Ok(state
+            .frauds
+            .values()
+            .filter(|f| match &filter {
+                FraudsFilter::BenchmarkId(id) => f.benchmark_id == *id,
+                FraudsFilter::Mempool { from_block_started } => {
+                    f.state.is_none() && state.started_from(&f.benchmark_id, *from_block_started)
+                }
+                FraudsFilter::Confirmed { from_block_started } => {
+                    f.state.is_some() && state.started_from(&f.benchmark_id, *from_block_started)
+                }
+            })
+            .map(|f| {
+                let mut f = f.clone();
+                if !include_data {
+                    f.allegation = None;
+                    f.evidence = None;
+                }
+                f
+            })
+            .collect())
+    }
+
+    async fn get_players(
+        &self,
+        filter: PlayersFilter,
+        block_data: Option<BlockFilter>,
+    ) -> ContextResult<Vec<Player>> {
+        let state = self.state.read().unwrap();
+        let block_id = state.find_block_id(&block_data);
+        let benchmarker_ids: HashSet<&String> = state
+            .precommits
+            .values()
+            .map(|p| &p.settings.player_id)
+            .collect();
+        let innovator_ids: HashSet<&String> = state
+            .algorithms
+            .values()
+            .map(|a| &a.details.player_id)
+            .collect();
+        Ok(state
+            .players
+            .values()
+            .filter(|p| match &filter {
+                PlayersFilter::Id(id) => p.id == *id,
+                PlayersFilter::Name(name) => p.details.name == *name,
+                PlayersFilter::Benchmarkers => benchmarker_ids.contains(&p.id),
+                PlayersFilter::Innovators => innovator_ids.contains(&p.id),
+            })
+            .map(|p| {
+                let mut p = p.clone();
+                p.block_data = block_id.as_ref().and_then(|block_id| {
+                    state
+                        .player_block_data
+                        .get(block_id)
+                        .and_then(|d| d.get(&p.id))
+                        .cloned()
+                });
+                p
+            })
+            .collect())
+    }
+
+    async fn get_precommits(&self, filter: PrecommitsFilter) -> ContextResult<Vec<Precommit>> {
+        let state = self.state.read().unwrap();
+        Ok(state
+            .precommits
+            .values()
+            .filter(|p| match &filter {
+                PrecommitsFilter::BenchmarkId(id) => p.benchmark_id == *id,
+                PrecommitsFilter::Settings(settings) => p.settings == *settings,
+                PrecommitsFilter::Mempool { from_block_started } => {
+                    p.state.is_none() && p.details.block_started >= *from_block_started
+                }
+                PrecommitsFilter::Confirmed { from_block_started } => {
+                    p.state.is_some() && p.details.block_started >= *from_block_started
+                }
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_proofs(
+        &self,
+        filter: ProofsFilter,
+        include_data: bool,
+    ) -> ContextResult<Vec<Proof>> {
+        let state = self.state.read().unwrap();
+        Ok(state
+            .proofs
+            .values()
+            .filter(|p| match &filter {
+                ProofsFilter::BenchmarkId(id) => p.benchmark_id == *id,
+                ProofsFilter::Mempool { from_block_started } => {
+                    p.state.is_none() && state.started_from(&p.benchmark_id, *from_block_started)
+                }
+                ProofsFilter::Confirmed { from_block_started } => {
+                    p.state.is_some() && state.started_from(&p.benchmark_id, *from_block_started)
+                }
+            })
+            .map(|p| {
+                let mut p = p.clone();
+                if !include_data {
+                    p.merkle_proofs = None;
+                }
+                p
+            })
+            .collect())
+    }
+
+    async fn get_topups(&self, filter: TopUpsFilter) -> ContextResult<Vec<TopUp>> {
+        let state = self.state.read().unwrap();
+        Ok(state
+            .topups
+            .values()
+            .filter(|t| match &filter {
+                TopUpsFilter::Id(id) => t.id == *id,
+                TopUpsFilter::PlayerId(player_id) => t.details.player_id == *player_id,
+                TopUpsFilter::Mempool => t.state.is_none(),
+                TopUpsFilter::Confirmed => t.state.is_some(),
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_wasms(&self, filter: WasmsFilter) -> ContextResult<Vec<Wasm>> {
+        let state = self.state.read().unwrap();
+        Ok(state
+            .wasms
+            .values()
+            .filter(|w| match &filter {
+                WasmsFilter::AlgorithmId(id) => w.algorithm_id == *id,
+                WasmsFilter::Mempool => w.state.is_none(),
+                WasmsFilter::Confirmed => w.state.is_some(),
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn verify_solution(
+        &self,
+        settings: &BenchmarkSettings,
+        nonce: u64,
+        solution: &Solution,
+    ) -> ContextResult<anyhow::Result<()>> {
+        let rand_hash = self.get_rand_hash(settings)?;
+        (self.verify_solution_fn)(settings, &rand_hash, nonce, solution)
+    }
+
+    async fn compute_solution(
+        &self,
+        settings: &BenchmarkSettings,
+        nonce: u64,
+        wasm_vm_config: &WasmVMConfig,
+    ) -> ContextResult<anyhow::Result<OutputData>> {
+        let rand_hash = self.get_rand_hash(settings)?;
+        (self.compute_solution_fn)(settings, &rand_hash, nonce, wasm_vm_config)
+    }
+
+    async fn recompute_solution(
+        &self,
+        settings: &BenchmarkSettings,
+        nonce: u64,
+        wasm_vm_config: &WasmVMConfig,
+    ) -> ContextResult<anyhow::Result<OutputData>> {
+        let rand_hash = self.get_rand_hash(settings)?;
+        let f = self
+            .recompute_solution_fn
+            .as_ref()
+            .unwrap_or(&self.compute_solution_fn);
+        f(settings, &rand_hash, nonce, wasm_vm_config)
+    }
+
+    async fn get_transaction(&self, tx_hash: &String) -> ContextResult<Transaction> {
+        (self.get_transaction_fn)(tx_hash)
+    }
+
+    async fn get_latest_eth_block_num(&self) -> ContextResult<String> {
+        Ok(self.state.read().unwrap().eth_block_num.clone())
+    }
+
+    async fn get_player_deposit(
+        &self,
+        eth_block_num: &String,
+        player_id: &String,
+    ) -> ContextResult<Option<PreciseNumber>> {
+        (self.get_player_deposit_fn)(eth_block_num, player_id)
+    }
+
+    // Mempool
+    async fn add_block(
+        &self,
+        details: BlockDetails,
+        data: BlockData,
+        config: ProtocolConfig,
+    ) -> ContextResult<String> {
+        let mut state = self.state.write().unwrap();
+        let expected_height = state.blocks.len() as u32;
+        if details.height != expected_height {
+            return Err(anyhow!(
+                "Invalid block height. Expected: '{}', Actual: '{}'",
+                expected_height,
+                details.height
+            ));
+        }
+        Ok(state.insert_block(details, data, config))
+    }
+
+    async fn add_challenge_to_mempool(&self, details: ChallengeDetails) -> ContextResult<String> {
+        self.write_outside_block(|state| {
+            let challenge_id = format!("c{:03}", state.challenges.len() + 1);
+            state.challenges.insert(
+                challenge_id.clone(),
+                Challenge {
+                    id: challenge_id.clone(),
+                    details: details.clone(),
+                    state: None,
+                    block_data: None,
+                },
+            );
+            Ok(challenge_id)
+        })
+    }
+
+    async fn add_algorithm_to_mempool(
+        &self,
+        details: AlgorithmDetails,
+        code: String,
+    ) -> ContextResult<String> {
+        self.write_outside_block(|state| {
+            let num_algorithms = state
+                .algorithms
+                .values()
+                .filter(|a| a.details.challenge_id == details.challenge_id)
+                .count();
+            let algorithm_id = format!("{}_a{:03}", details.challenge_id, num_algorithms + 1);
+            state.algorithms.insert(
+                algorithm_id.clone(),
+                Algorithm {
+                    id: algorithm_id.clone(),
+                    details: details.clone(),
+                    state: None,
+                    block_data: None,
+                    code: Some(code.clone()),
+                },
+            );
+            Ok(algorithm_id)
+        })
+    }
+
+    async fn add_benchmark_to_mempool(
+        &self,
+        benchmark_id: &String,
+        details: BenchmarkDetails,
+        solution_nonces: HashSet<u64>,
+    ) -> ContextResult<()> {
+        self.write_outside_block(|state| {
+            if state.benchmarks.contains_key(benchmark_id) {
+                return Err(anyhow!("Benchmark '{}' already exists", benchmark_id));
+            }
+            state.benchmarks.insert(
+                benchmark_id.clone(),
+                Benchmark {
+                    id: benchmark_id.clone(),
+                    details: details.clone(),
+                    state: None,
+                    solution_nonces: Some(solution_nonces.clone()),
+                },
+            );
+            Ok(())
+        })
+    }
+
+    async fn add_precommit_to_mempool(
+        &self,
+        settings: BenchmarkSettings,
+        details: PrecommitDetails,
+    ) -> ContextResult<String> {
+        self.write_outside_block(|state| {
+            let benchmark_id = hash_id(&jsonify(&settings));
+            if state.precommits.contains_key(&benchmark_id) {
+                return Err(anyhow!("Precommit '{}' already exists", benchmark_id));
+            }
+            state.precommits.insert(
+                benchmark_id.clone(),
+                Precommit {
+                    benchmark_id: benchmark_id.clone(),
+                    details: details.clone(),
+                    settings: settings.clone(),
+                    state: None,
+                },
+            );
+            Ok(benchmark_id)
+        })
+    }
+
+    async fn add_proof_to_mempool(
+        &self,
+        benchmark_id: &String,
+        merkle_proofs: Vec<MerkleProof>,
+    ) -> ContextResult<()> {
+        self.write_outside_block(|state| {
+            if state.proofs.contains_key(benchmark_id) {
+                return Err(anyhow!("Proof '{}' already exists", benchmark_id));
+            }
+            state.proofs.insert(
+                benchmark_id.clone(),
+                Proof {
+                    benchmark_id: benchmark_id.clone(),
+                    state: None,
+                    merkle_proofs: Some(merkle_proofs.clone()),
+                },
+            );
+            Ok(())
+        })
+    }
+
+    async fn add_fraud_to_mempool(
+        &self,
+        benchmark_id: &String,
+        allegation: String,
+    ) -> ContextResult<()> {
+        self.write_outside_block(|state| {
+            if state.frauds.contains_key(benchmark_id) {
+                return Err(anyhow!("Fraud '{}' already exists", benchmark_id));
+            }
+            state.frauds.insert(
+                benchmark_id.clone(),
+                Fraud {
+                    benchmark_id: benchmark_id.clone(),
+                    state: None,
+                    allegation: Some(allegation.clone()),
+                    appeal: None,
+                    challenger_id: None,
+                    evidence: None,
+                },
+            );
+            Ok(())
+        })
+    }
+
+    async fn add_fraud_challenge_to_mempool(
+        &self,
+        benchmark_id: &String,
+        challenger_id: &String,
+        allegation: String,
+        evidence: FraudEvidence,
+    ) -> ContextResult<()> {
+        self.write_outside_block(|state| {
+            if state.frauds.contains_key(benchmark_id) {
+                return Err(anyhow!("Fraud '{}' already exists", benchmark_id));
+            }
+            state.frauds.insert(
+                benchmark_id.clone(),
+                Fraud {
+                    benchmark_id: benchmark_id.clone(),
+                    state: None,
+                    allegation: Some(allegation.clone()),
+                    appeal: None,
+                    challenger_id: Some(challenger_id.clone()),
+                    evidence: Some(evidence.clone()),
+                },
+            );
+            Ok(())
+        })
+    }
+
+    async fn add_fraud_appeal_to_mempool(
+        &self,
+        benchmark_id: &String,
+        appeal: FraudAppeal,
+    ) -> ContextResult<()> {
+        self.write_outside_block(|state| {
+            let fraud = state
+                .frauds
+                .get_mut(benchmark_id)
+                .filter(|f| f.state.is_some())
+                .ok_or_else(|| anyhow!("Confirmed fraud '{}' not found", benchmark_id))?;
+            if fraud.appeal.is_some() {
+                return Err(anyhow!("Fraud '{}' already appealed", benchmark_id));
+            }
+            fraud.appeal = Some(appeal.clone());
+            Ok(())
+        })
+    }
+
+    async fn add_topup_to_mempool(
+        &self,
+        topup_id: &String,
+        details: TopUpDetails,
+    ) -> ContextResult<()> {
+        self.write_outside_block(|state| {
+            if state.topups.contains_key(topup_id) {
+                return Err(anyhow!("TopUp '{}' already exists", topup_id));
+            }
+            state.topups.insert(
+                topup_id.clone(),
+                TopUp {
+                    id: topup_id.clone(),
+                    details: details.clone(),
+                    state: None,
+                },
+            );
+            Ok(())
+        })
+    }
+
+    async fn add_wasm_to_mempool(
+        &self,
+        algorithm_id: &String,
+        details: WasmDetails,
+    ) -> ContextResult<()> {
+        self.write_outside_block(|state| {
+            if state.wasms.contains_key(algorithm_id) {
+                return Err(anyhow!("Wasm '{}' already exists", algorithm_id));
+            }
+            state.wasms.insert(
+                algorithm_id.clone(),
+                Wasm {
+                    algorithm_id: algorithm_id.clone(),
+                    details: details.clone(),
+                    state: None,
+                },
+            );
+            Ok(())
+        })
+    }
+
+    // Updates
+    async fn update_block_details(
+        &self,
+        block_id: &String,
+        details: BlockDetails,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let height = *s
+            .block_heights
+            .get(block_id)
+            .ok_or_else(|| anyhow!("Block '{}' not found", block_id))?;
+        s.blocks[height as usize].details = details;
+        Ok(())
+    }
+
+    async fn update_challenge_state(
+        &self,
+        challenge_id: &String,
+        state: ChallengeState,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let challenge = s
+            .challenges
+            .get_mut(challenge_id)
+            .ok_or_else(|| anyhow!("Challenge '{}' not found", challenge_id))?;
+        challenge.state = Some(state);
+        Ok(())
+    }
+
+    async fn update_challenge_block_data(
+        &self,
+        challenge_id: &String,
+        block_id: &String,
+        block_data: ChallengeBlockData,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        if !s.challenges.contains_key(challenge_id) {
+            return Err(anyhow!("Challenge '{}' not found", challenge_id));
+        }
+        s.challenge_block_data
+            .entry(block_id.clone())
+            .or_default()
+            .insert(challenge_id.clone(), block_data);
+        Ok(())
+    }
+
+    async fn update_algorithm_state(
+        &self,
+        algorithm_id: &String,
+        state: AlgorithmState,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let algorithm = s
+            .algorithms
+            .get_mut(algorithm_id)
+            .ok_or_else(|| anyhow!("Algorithm '{}' not found", algorithm_id))?;
+        algorithm.state = Some(state);
+        Ok(())
+    }
+
+    async fn update_algorithm_block_data(
+        &self,
+        algorithm_id: &String,
+        block_id: &String,
+        block_data: AlgorithmBlockData,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        if !s.algorithms.contains_key(algorithm_id) {
+            return Err(anyhow!("Algorithm '{}' not found", algorithm_id));
+        }
+        s.algorithm_block_data
+            .entry(block_id.clone())
+            .or_default()
+            .insert(algorithm_id.clone(), block_data);
+        Ok(())
+    }
+
+    async fn update_benchmark_state(
+        &self,
+        benchmark_id: &String,
+        state: BenchmarkState,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let benchmark = s
+            .benchmarks
+            .get_mut(benchmark_id)
+            .ok_or_else(|| anyhow!("Benchmark '{}' not found", benchmark_id))?;
+        benchmark.state = Some(state);
+        Ok(())
+    }
+
+    async fn update_player_state(
+        &self,
+        player_id: &String,
+        state: PlayerState,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let player = s
+            .players
+            .get_mut(player_id)
+            .ok_or_else(|| anyhow!("Player '{}' not found", player_id))?;
+        player.state = Some(state);
+        Ok(())
+    }
+
+    async fn update_precommit_state(
+        &self,
+        benchmark_id: &String,
+        state: PrecommitState,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let precommit = s
+            .precommits
+            .get_mut(benchmark_id)
+            .ok_or_else(|| anyhow!("Precommit '{}' not found", benchmark_id))?;
+        precommit.state = Some(state);
+        Ok(())
+    }
+
+    async fn update_proof_state(
+        &self,
+        benchmark_id: &String,
+        state: ProofState,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let proof = s
+            .proofs
+            .get_mut(benchmark_id)
+            .ok_or_else(|| anyhow!("Proof '{}' not found", benchmark_id))?;
+        proof.state = Some(state);
+        Ok(())
+    }
+
+    async fn update_fraud_state(
+        &self,
+        benchmark_id: &String,
+        state: FraudState,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let fraud = s
+            .frauds
+            .get_mut(benchmark_id)
+            .ok_or_else(|| anyhow!("Fraud '{}' not found", benchmark_id))?;
+        fraud.state = Some(state);
+        Ok(())
+    }
+
+    async fn update_topup_state(&self, topup_id: &String, state: TopUpState) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let topup = s
+            .topups
+            .get_mut(topup_id)
+            .ok_or_else(|| anyhow!("TopUp '{}' not found", topup_id))?;
+        topup.state = Some(state);
+        Ok(())
+    }
+
+    async fn update_player_block_data(
+        &self,
+        player_id: &String,
+        block_id: &String,
+        block_data: PlayerBlockData,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        if !s.players.contains_key(player_id) {
+            return Err(anyhow!("Player '{}' not found", player_id));
+        }
+        s.player_block_data
+            .entry(block_id.clone())
+            .or_default()
+            .insert(player_id.clone(), block_data);
+        Ok(())
+    }
+
+    async fn update_wasm_state(
+        &self,
+        algorithm_id: &String,
+        state: WasmState,
+    ) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let wasm = s
+            .wasms
+            .get_mut(algorithm_id)
+            .ok_or_else(|| anyhow!("Wasm '{}' not found", algorithm_id))?;
+        wasm.state = Some(state);
+        Ok(())
+    }
+
+    // Transactions
+    async fn begin_block_transaction(&self) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        if s.snapshot.is_some() {
+            return Err(anyhow!("Block transaction already in progress"));
+        }
+        s.snapshot = Some(Box::new(s.clone()));
+        Ok(())
+    }
+
+    async fn commit_block_transaction(&self) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        s.snapshot
+            .take()
+            .ok_or_else(|| anyhow!("No block transaction in progress"))?;
+        Ok(())
+    }
+
+    async fn rollback_block_transaction(&self) -> ContextResult<()> {
+        let mut s = self.state.write().unwrap();
+        let snapshot = s
+            .snapshot
+            .take()
+            .ok_or_else(|| anyhow!("No block transaction in progress"))?;
+        *s = *snapshot;
+        Ok(())
+    }
+}
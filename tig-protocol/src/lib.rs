@@ -1,14 +1,29 @@
 mod add_block;
 pub mod context;
 mod error;
+mod get_state_proof;
+#[cfg(feature = "in-memory")]
+mod in_memory;
+#[cfg(feature = "persistent")]
+mod persistent;
+#[cfg(feature = "in-memory")]
+mod replay;
 mod submit_algorithm;
 mod submit_benchmark;
+mod submit_fraud_appeal;
+mod submit_fraud_challenge;
 mod submit_precommit;
 mod submit_proof;
 mod submit_topup;
 mod verify_proof;
 use context::*;
 pub use error::*;
+#[cfg(feature = "in-memory")]
+pub use in_memory::*;
+#[cfg(feature = "persistent")]
+pub use persistent::*;
+#[cfg(feature = "in-memory")]
+pub use replay::*;
 use std::collections::HashSet;
 use tig_structs::core::*;
 
@@ -65,6 +80,29 @@ impl<'a, T: Context> Protocol<T> {
         submit_proof::execute(&self.ctx, player, benchmark_id, merkle_proofs).await
     }
 
+    /// Re-runs verification of a benchmark flagged as fraud with the context's second verifier.
+    /// The fraud is overturned when the appeal period ends if the second verifier passes
+    pub async fn submit_fraud_appeal(
+        &self,
+        player: &Player,
+        benchmark_id: &String,
+    ) -> ProtocolResult<Result<(), String>> {
+        submit_fraud_appeal::execute(&self.ctx, player, benchmark_id).await
+    }
+
+    /// Raises fraud against another player's confirmed benchmark. `evidence` must prove inclusion
+    /// of the benchmarker's leaf for `nonce` and carry output data recomputed by the protocol
+    /// that contradicts it. Earns a bounty from the benchmarker's penalty if the fraud is upheld
+    pub async fn submit_fraud_challenge(
+        &self,
+        player: &Player,
+        benchmark_id: &String,
+        nonce: u64,
+        evidence: FraudEvidence,
+    ) -> ProtocolResult<()> {
+        submit_fraud_challenge::execute(&self.ctx, player, benchmark_id, nonce, evidence).await
+    }
+
     pub async fn submit_topup(&self, player: &Player, tx_hash: String) -> ProtocolResult<()> {
         submit_topup::execute(&self.ctx, player, tx_hash).await
     }
@@ -73,6 +111,19 @@ impl<'a, T: Context> Protocol<T> {
         verify_proof::execute(&self.ctx, benchmark_id).await
     }
 
+    /// Inclusion proof of an active challenge, algorithm or player's block data against the
+    /// `state_root` of block `block_id`
+    pub async fn get_state_proof(
+        &self,
+        block_id: &String,
+        leaf_type: StateLeafType,
+        id: &String,
+    ) -> ProtocolResult<StateProof> {
+        get_state_proof::execute(&self.ctx, block_id, leaf_type, id).await
+    }
+
+    /// Panics if the block cannot be created. Its writes are rolled back first, so that
+    /// `add_block` can be called again
     pub async fn add_block(&self) -> String {
         add_block::execute(&self.ctx).await
     }
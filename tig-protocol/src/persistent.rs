@@ -0,0 +1,980 @@
+use crate::{context::*, InMemoryContext};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    future::Future,
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Mutex,
+    task::{Poll, Waker},
+};
+use tig_structs::{config::*, core::*};
+use tig_utils::*;
+
+/// A `Context` that persists every write to an append-only journal on disk.
+///
+/// Data is served from an `InMemoryContext` which is rebuilt by replaying the journal on `open`.
+/// Each line of the journal is one atomic unit: either a single write, or every write made by
+/// `add_block` during a block transaction. Writes from other callers are journaled immediately,
+/// even while a block transaction is open. A line that was only partially written (e.g. crash
+/// during `commit_block_transaction`) is discarded on the next `open`.
+///
+/// Only successful writes are journaled, together with the id they returned. `open` fails if
+/// replaying a write fails or returns a different id, e.g. for a journal written by an
+/// incompatible version.
+pub struct PersistentContext {
+    inner: InMemoryContext,
+    journal: Mutex<Journal>,
+}
+
+struct Journal {
+    file: File,
+    len: u64,
+    pending: Option<Vec<JournalRecord>>,
+    // a write was applied but could not be journaled. Later writes are refused, as the
+    // in-memory state is ahead of the journal until the context is reopened
+    diverged: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct JournalRecord {
+    entry: JournalEntry,
+    id: Option<String>,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    SetConfig {
+        config: ProtocolConfig,
+    },
+    SetEthBlockNum {
+        eth_block_num: String,
+    },
+    AddPlayer {
+        player_id: String,
+        details: PlayerDetails,
+    },
+    AddBlock {
+        details: BlockDetails,
+        data: BlockData,
+        config: ProtocolConfig,
+    },
+    AddChallenge {
+        details: ChallengeDetails,
+    },
+    AddAlgorithm {
+        details: AlgorithmDetails,
+        code: String,
+    },
+    AddBenchmark {
+        benchmark_id: String,
+        details: BenchmarkDetails,
+        solution_nonces: HashSet<u64>,
+    },
+    AddPrecommit {
+        settings: BenchmarkSettings,
+        details: PrecommitDetails,
+    },
+    AddProof {
+        benchmark_id: String,
+        merkle_proofs: Vec<MerkleProof>,
+    },
+    AddFraud {
+        benchmark_id: String,
+        allegation: String,
+    },
+    AddFraudChallenge {
+        benchmark_id: String,
+        challenger_id: String,
+        allegation: String,
+        evidence: FraudEvidence,
+    },
+    AddFraudAppeal {
+        benchmark_id: String,
+        appeal: FraudAppeal,
+    },
+    AddTopUp {
+        topup_id: String,
+        details: TopUpDetails,
+    },
+    AddWasm {
+        algorithm_id: String,
+        details: WasmDetails,
+    },
+    UpdateBlockDetails {
+        block_id: String,
+        details: BlockDetails,
+    },
+    UpdateChallengeState {
+        challenge_id: String,
+        state: ChallengeState,
+    },
+    UpdateChallengeBlockData {
+        challenge_id: String,
+        block_id: String,
+        block_data: ChallengeBlockData,
+    },
+    UpdateAlgorithmState {
+        algorithm_id: String,
+        state: AlgorithmState,
+    },
+    UpdateAlgorithmBlockData {
+        algorithm_id: String,
+        block_id: String,
+        block_data: AlgorithmBlockData,
+    },
+    UpdateBenchmarkState {
+        benchmark_id: String,
+        state: BenchmarkState,
+    },
+    UpdatePlayerState {
+        player_id: String,
+        state: PlayerState,
+    },
+    UpdatePrecommitState {
+        benchmark_id: String,
+        state: PrecommitState,
+    },
+    UpdateProofState {
+        benchmark_id: String,
+        state: ProofState,
+    },
+    UpdateFraudState {
+        benchmark_id: String,
+        state: FraudState,
+    },
+    UpdateTopUpState {
+        topup_id: String,
+        state: TopUpState,
+    },
+    UpdatePlayerBlockData {
+        player_id: String,
+        block_id: String,
+        block_data: PlayerBlockData,
+    },
+    UpdateWasmState {
+        algorithm_id: String,
+        state: WasmState,
+    },
+}
+
+impl JournalEntry {
+    // writes made by `add_block`, which join an open block transaction
+    fn is_block_write(&self) -> bool {
+        matches!(
+            self,
+            JournalEntry::AddBlock { .. }
+                | JournalEntry::UpdateBlockDetails { .. }
+                | JournalEntry::UpdateChallengeState { .. }
+                | JournalEntry::UpdateChallengeBlockData { .. }
+                | JournalEntry::UpdateAlgorithmState { .. }
+                | JournalEntry::UpdateAlgorithmBlockData { .. }
+                | JournalEntry::UpdateBenchmarkState { .. }
+                | JournalEntry::UpdatePlayerState { .. }
+                | JournalEntry::UpdatePrecommitState { .. }
+                | JournalEntry::UpdateProofState { .. }
+                | JournalEntry::UpdateFraudState { .. }
+                | JournalEntry::UpdateTopUpState { .. }
+                | JournalEntry::UpdatePlayerBlockData { .. }
+                | JournalEntry::UpdateWasmState { .. }
+        )
+    }
+}
+
+impl Journal {
+    fn append(&mut self, records: &[JournalRecord]) -> ContextResult<()> {
+        let line = format!("{}\n", jsonify(&records));
+        let result = self
+            .file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.sync_data());
+        match result {
+            Ok(_) => {
+                self.len += line.len() as u64;
+                Ok(())
+            }
+            Err(e) => {
+                // drop the partially written line so that later appends stay readable
+                self.file.set_len(self.len)?;
+                self.file.seek(SeekFrom::Start(self.len))?;
+                Err(e.into())
+            }
+        }
+    }
+}
+
+impl PersistentContext {
+    /// Opens the journal at `path`, creating it if it does not exist, and replays it onto `ctx`.
+    ///
+    /// `ctx` should be freshly created. Its stubs (`with_*` methods) are used as is.
+    pub async fn open<P: AsRef<Path>>(path: P, ctx: InMemoryContext) -> ContextResult<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.as_ref())?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        let mut len = 0u64;
+        let mut lines = Vec::new();
+        for line in contents.split_inclusive('\n') {
+            if !line.ends_with('\n') {
+                break;
+            }
+            let records = dejsonify::<Vec<JournalRecord>>(line.trim_end()).map_err(|e| {
+                anyhow!(
+                    "Corrupt journal '{}' at byte {}: {}",
+                    path.as_ref().display(),
+                    len,
+                    e
+                )
+            })?;
+            lines.push((len, records));
+            len += line.len() as u64;
+        }
+        if len != contents.len() as u64 {
+            // incomplete last line from an interrupted write
+            file.set_len(len)?;
+            file.sync_data()?;
+        }
+        file.seek(SeekFrom::Start(len))?;
+
+        for (offset, records) in lines {
+            for record in records {
+                let id = apply(&ctx, record.entry).await.map_err(|e| {
+                    anyhow!(
+                        "Journal '{}' at byte {} failed to replay: {}",
+                        path.as_ref().display(),
+                        offset,
+                        e
+                    )
+                })?;
+                if id != record.id {
+                    return Err(anyhow!(
+                        "Journal '{}' at byte {} replayed to id {:?}, expected {:?}",
+                        path.as_ref().display(),
+                        offset,
+                        id,
+                        record.id
+                    ));
+                }
+            }
+        }
+        Ok(Self {
+            inner: ctx,
+            journal: Mutex::new(Journal {
+                file,
+                len,
+                pending: None,
+                diverged: false,
+            }),
+        })
+    }
+
+    pub async fn add_player(&self, player_id: &str, details: PlayerDetails) -> ContextResult<()> {
+        self.execute(JournalEntry::AddPlayer {
+            player_id: player_id.to_string(),
+            details,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Config used for the next block. Existing blocks keep the config they were created with
+    pub async fn set_config(&self, config: ProtocolConfig) -> ContextResult<()> {
+        self.execute(JournalEntry::SetConfig { config })
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn set_eth_block_num(&self, eth_block_num: String) -> ContextResult<()> {
+        self.execute(JournalEntry::SetEthBlockNum { eth_block_num })
+            .await
+            .map(|_| ())
+    }
+
+    async fn execute(&self, entry: JournalEntry) -> ContextResult<Option<String>> {
+        let mut journal = self.journal.lock().unwrap();
+        if journal.diverged {
+            return Err(anyhow!(
+                "Journal is behind after a failed write. Reopen the context"
+            ));
+        }
+        // applied while holding the lock, so that writes are journaled in the order applied
+        let id = apply_now(&self.inner, entry.clone())?;
+        let record = JournalRecord {
+            entry,
+            id: id.clone(),
+        };
+        match journal.pending.as_mut() {
+            Some(pending) if record.entry.is_block_write() => pending.push(record),
+            _ => {
+                if let Err(e) = journal.append(std::slice::from_ref(&record)) {
+                    journal.diverged = true;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(id)
+    }
+}
+
+// `InMemoryContext` never awaits, so its writes complete on the first poll
+fn apply_now(ctx: &InMemoryContext, entry: JournalEntry) -> ContextResult<Option<String>> {
+    let mut future = std::pin::pin!(apply(ctx, entry));
+    match future
+        .as_mut()
+        .poll(&mut std::task::Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(result) => result,
+        Poll::Pending => unreachable!("InMemoryContext writes do not await"),
+    }
+}
+
+async fn apply(ctx: &InMemoryContext, entry: JournalEntry) -> ContextResult<Option<String>> {
+    match entry {
+        JournalEntry::SetConfig { config } => {
+            ctx.set_config(config);
+            Ok(None)
+        }
+        JournalEntry::SetEthBlockNum { eth_block_num } => {
+            ctx.set_eth_block_num(eth_block_num);
+            Ok(None)
+        }
+        JournalEntry::AddPlayer { player_id, details } => {
+            ctx.add_player(&player_id, details);
+            Ok(None)
+        }
+        JournalEntry::AddBlock {
+            details,
+            data,
+            config,
+        } => ctx.add_block(details, data, config).await.map(Some),
+        JournalEntry::AddChallenge { details } => {
+            ctx.add_challenge_to_mempool(details).await.map(Some)
+        }
+        JournalEntry::AddAlgorithm { details, code } => {
+            ctx.add_algorithm_to_mempool(details, code).await.map(Some)
+        }
+        JournalEntry::AddBenchmark {
+            benchmark_id,
+            details,
+            solution_nonces,
+        } => ctx
+            .add_benchmark_to_mempool(&benchmark_id, details, solution_nonces)
+            .await
+            .map(|_| None),
+        JournalEntry::AddPrecommit { settings, details } => ctx
+            .add_precommit_to_mempool(settings, details)
+            .await
+            .map(Some),
+        JournalEntry::AddProof {
+            benchmark_id,
+            merkle_proofs,
+        } => ctx
+            .add_proof_to_mempool(&benchmark_id, merkle_proofs)
+            .await
+            .map(|_| None),
+        JournalEntry::AddFraud {
+            benchmark_id,
+            allegation,
+        } => ctx
+            .add_fraud_to_mempool(&benchmark_id, allegation)
+            .await
+            .map(|_| None),
+        JournalEntry::AddFraudChallenge {
+            benchmark_id,
+            challenger_id,
+            allegation,
+            evidence,
+        } => ctx
+            .add_fraud_challenge_to_mempool(&benchmark_id, &challenger_id, allegation, evidence)
+            .await
+            .map(|_| None),
+        JournalEntry::AddFraudAppeal {
+            benchmark_id,
+            appeal,
+        } => ctx
+            .add_fraud_appeal_to_mempool(&benchmark_id, appeal)
+            .await
+            .map(|_| None),
+        JournalEntry::AddTopUp { topup_id, details } => ctx
+            .add_topup_to_mempool(&topup_id, details)
+            .await
+            .map(|_| None),
+        JournalEntry::AddWasm {
+            algorithm_id,
+            details,
+        } => ctx
+            .add_wasm_to_mempool(&algorithm_id, details)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateBlockDetails { block_id, details } => ctx
+            .update_block_details(&block_id, details)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateChallengeState {
+            challenge_id,
+            state,
+        } => ctx
+            .update_challenge_state(&challenge_id, state)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateChallengeBlockData {
+            challenge_id,
+            block_id,
+            block_data,
+        } => ctx
+            .update_challenge_block_data(&challenge_id, &block_id, block_data)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateAlgorithmState {
+            algorithm_id,
+            state,
+        } => ctx
+            .update_algorithm_state(&algorithm_id, state)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateAlgorithmBlockData {
+            algorithm_id,
+            block_id,
+            block_data,
+        } => ctx
+            .update_algorithm_block_data(&algorithm_id, &block_id, block_data)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateBenchmarkState {
+            benchmark_id,
+            state,
+        } => ctx
+            .update_benchmark_state(&benchmark_id, state)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdatePlayerState { player_id, state } => ctx
+            .update_player_state(&player_id, state)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdatePrecommitState {
+            benchmark_id,
+            state,
+        } => ctx
+            .update_precommit_state(&benchmark_id, state)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateProofState {
+            benchmark_id,
+            state,
+        } => ctx
+            .update_proof_state(&benchmark_id, state)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateFraudState {
+            benchmark_id,
+            state,
+        } => ctx
+            .update_fraud_state(&benchmark_id, state)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateTopUpState { topup_id, state } => {
+            ctx.update_topup_state(&topup_id, state).await.map(|_| None)
+        }
+        JournalEntry::UpdatePlayerBlockData {
+            player_id,
+            block_id,
+            block_data,
+        } => ctx
+            .update_player_block_data(&player_id, &block_id, block_data)
+            .await
+            .map(|_| None),
+        JournalEntry::UpdateWasmState {
+            algorithm_id,
+            state,
+        } => ctx
+            .update_wasm_state(&algorithm_id, state)
+            .await
+            .map(|_| None),
+    }
+}
+
+impl Context for PersistentContext {
+    async fn get_algorithms(
+        &self,
+        filter: AlgorithmsFilter,
+        block_data: Option<BlockFilter>,
+        include_data: bool,
+    ) -> ContextResult<Vec<Algorithm>> {
+        self.inner
+            .get_algorithms(filter, block_data, include_data)
+            .await
+    }
+
+    async fn get_benchmarks(
+        &self,
+        filter: BenchmarksFilter,
+        include_data: bool,
+    ) -> ContextResult<Vec<Benchmark>> {
+        self.inner.get_benchmarks(filter, include_data).await
+    }
+
+    async fn get_block(
+        &self,
+        filter: BlockFilter,
+        include_data: bool,
+    ) -> ContextResult<Option<Block>> {
+        self.inner.get_block(filter, include_data).await
+    }
+
+    async fn get_challenges(
+        &self,
+        filter: ChallengesFilter,
+        block_data: Option<BlockFilter>,
+    ) -> ContextResult<Vec<Challenge>> {
+        self.inner.get_challenges(filter, block_data).await
+    }
+
+    async fn get_config(&self) -> ContextResult<ProtocolConfig> {
+        self.inner.get_config().await
+    }
+
+    async fn get_frauds(
+        &self,
+        filter: FraudsFilter,
+        include_data: bool,
+    ) -> ContextResult<Vec<Fraud>> {
+        self.inner.get_frauds(filter, include_data).await
+    }
+
+    async fn get_players(
+        &self,
+        filter: PlayersFilter,
+        block_data: Option<BlockFilter>,
+    ) -> ContextResult<Vec<Player>> {
+        self.inner.get_players(filter, block_data).await
+    }
+
+    async fn get_precommits(&self, filter: PrecommitsFilter) -> ContextResult<Vec<Precommit>> {
+        self.inner.get_precommits(filter).await
+    }
+
+    async fn get_proofs(
+        &self,
+        filter: ProofsFilter,
+        include_data: bool,
+    ) -> ContextResult<Vec<Proof>> {
+        self.inner.get_proofs(filter, include_data).await
+    }
+
+    async fn get_topups(&self, filter: TopUpsFilter) -> ContextResult<Vec<TopUp>> {
+        self.inner.get_topups(filter).await
+    }
+
+    async fn get_wasms(&self, filter: WasmsFilter) -> ContextResult<Vec<Wasm>> {
+        self.inner.get_wasms(filter).await
+    }
+
+    async fn verify_solution(
+        &self,
+        settings: &BenchmarkSettings,
+        nonce: u64,
+        solution: &Solution,
+    ) -> ContextResult<anyhow::Result<()>> {
+        self.inner.verify_solution(settings, nonce, solution).await
+    }
+
+    async fn compute_solution(
+        &self,
+        settings: &BenchmarkSettings,
+        nonce: u64,
+        wasm_vm_config: &WasmVMConfig,
+    ) -> ContextResult<anyhow::Result<OutputData>> {
+        self.inner
+            .compute_solution(settings, nonce, wasm_vm_config)
+            .await
+    }
+
+    async fn recompute_solution(
+        &self,
+        settings: &BenchmarkSettings,
+        nonce: u64,
+        wasm_vm_config: &WasmVMConfig,
+    ) -> ContextResult<anyhow::Result<OutputData>> {
+        self.inner
+            .recompute_solution(settings, nonce, wasm_vm_config)
+            .await
+    }
+
+    async fn get_transaction(&self, tx_hash: &String) -> ContextResult<Transaction> {
+        self.inner.get_transaction(tx_hash).await
+    }
+
+    async fn get_latest_eth_block_num(&self) -> ContextResult<String> {
+        self.inner.get_latest_eth_block_num().await
+    }
+
+    async fn get_player_deposit(
+        &self,
+        eth_block_num: &String,
+        player_id: &String,
+    ) -> ContextResult<Option<PreciseNumber>> {
+        self.inner
+            .get_player_deposit(eth_block_num, player_id)
+            .await
+    }
+
+    // Mempool
+    async fn add_block(
+        &self,
+        details: BlockDetails,
+        data: BlockData,
+        config: ProtocolConfig,
+    ) -> ContextResult<String> {
+        self.execute(JournalEntry::AddBlock {
+            details,
+            data,
+            config,
+        })
+        .await
+        .map(Option::unwrap)
+    }
+
+    async fn add_challenge_to_mempool(&self, details: ChallengeDetails) -> ContextResult<String> {
+        self.execute(JournalEntry::AddChallenge { details })
+            .await
+            .map(Option::unwrap)
+    }
+
+    async fn add_algorithm_to_mempool(
+        &self,
+        details: AlgorithmDetails,
+        code: String,
+    ) -> ContextResult<String> {
+        self.execute(JournalEntry::AddAlgorithm { details, code })
+            .await
+            .map(Option::unwrap)
+    }
+
+    async fn add_benchmark_to_mempool(
+        &self,
+        benchmark_id: &String,
+        details: BenchmarkDetails,
+        solution_nonces: HashSet<u64>,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::AddBenchmark {
+            benchmark_id: benchmark_id.clone(),
+            details,
+            solution_nonces,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn add_precommit_to_mempool(
+        &self,
+        settings: BenchmarkSettings,
+        details: PrecommitDetails,
+    ) -> ContextResult<String> {
+        self.execute(JournalEntry::AddPrecommit { settings, details })
+            .await
+            .map(Option::unwrap)
+    }
+
+    async fn add_proof_to_mempool(
+        &self,
+        benchmark_id: &String,
+        merkle_proofs: Vec<MerkleProof>,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::AddProof {
+            benchmark_id: benchmark_id.clone(),
+            merkle_proofs,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn add_fraud_to_mempool(
+        &self,
+        benchmark_id: &String,
+        allegation: String,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::AddFraud {
+            benchmark_id: benchmark_id.clone(),
+            allegation,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn add_fraud_challenge_to_mempool(
+        &self,
+        benchmark_id: &String,
+        challenger_id: &String,
+        allegation: String,
+        evidence: FraudEvidence,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::AddFraudChallenge {
+            benchmark_id: benchmark_id.clone(),
+            challenger_id: challenger_id.clone(),
+            allegation,
+            evidence,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn add_fraud_appeal_to_mempool(
+        &self,
+        benchmark_id: &String,
+        appeal: FraudAppeal,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::AddFraudAppeal {
+            benchmark_id: benchmark_id.clone(),
+            appeal,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn add_topup_to_mempool(
+        &self,
+        topup_id: &String,
+        details: TopUpDetails,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::AddTopUp {
+            topup_id: topup_id.clone(),
+            details,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn add_wasm_to_mempool(
+        &self,
+        algorithm_id: &String,
+        details: WasmDetails,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::AddWasm {
+            algorithm_id: algorithm_id.clone(),
+            details,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    // Updates
+    async fn update_block_details(
+        &self,
+        block_id: &String,
+        details: BlockDetails,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateBlockDetails {
+            block_id: block_id.clone(),
+            details,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_challenge_state(
+        &self,
+        challenge_id: &String,
+        state: ChallengeState,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateChallengeState {
+            challenge_id: challenge_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_challenge_block_data(
+        &self,
+        challenge_id: &String,
+        block_id: &String,
+        block_data: ChallengeBlockData,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateChallengeBlockData {
+            challenge_id: challenge_id.clone(),
+            block_id: block_id.clone(),
+            block_data,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_algorithm_state(
+        &self,
+        algorithm_id: &String,
+        state: AlgorithmState,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateAlgorithmState {
+            algorithm_id: algorithm_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_algorithm_block_data(
+        &self,
+        algorithm_id: &String,
+        block_id: &String,
+        block_data: AlgorithmBlockData,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateAlgorithmBlockData {
+            algorithm_id: algorithm_id.clone(),
+            block_id: block_id.clone(),
+            block_data,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_benchmark_state(
+        &self,
+        benchmark_id: &String,
+        state: BenchmarkState,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateBenchmarkState {
+            benchmark_id: benchmark_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_player_state(
+        &self,
+        player_id: &String,
+        state: PlayerState,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdatePlayerState {
+            player_id: player_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_precommit_state(
+        &self,
+        benchmark_id: &String,
+        state: PrecommitState,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdatePrecommitState {
+            benchmark_id: benchmark_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_proof_state(
+        &self,
+        benchmark_id: &String,
+        state: ProofState,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateProofState {
+            benchmark_id: benchmark_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_fraud_state(
+        &self,
+        benchmark_id: &String,
+        state: FraudState,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateFraudState {
+            benchmark_id: benchmark_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_topup_state(&self, topup_id: &String, state: TopUpState) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateTopUpState {
+            topup_id: topup_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_player_block_data(
+        &self,
+        player_id: &String,
+        block_id: &String,
+        block_data: PlayerBlockData,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdatePlayerBlockData {
+            player_id: player_id.clone(),
+            block_id: block_id.clone(),
+            block_data,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_wasm_state(
+        &self,
+        algorithm_id: &String,
+        state: WasmState,
+    ) -> ContextResult<()> {
+        self.execute(JournalEntry::UpdateWasmState {
+            algorithm_id: algorithm_id.clone(),
+            state,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    // Transactions
+    async fn begin_block_transaction(&self) -> ContextResult<()> {
+        {
+            let mut journal = self.journal.lock().unwrap();
+            if journal.pending.is_some() {
+                return Err(anyhow!("Block transaction already in progress"));
+            }
+            journal.pending = Some(Vec::new());
+        }
+        self.inner.begin_block_transaction().await
+    }
+
+    async fn commit_block_transaction(&self) -> ContextResult<()> {
+        let result = {
+            let mut journal = self.journal.lock().unwrap();
+            let pending = journal
+                .pending
+                .take()
+                .ok_or_else(|| anyhow!("No block transaction in progress"))?;
+            journal.append(&pending)
+        };
+        match result {
+            Ok(_) => self.inner.commit_block_transaction().await,
+            Err(e) => {
+                self.inner.rollback_block_transaction().await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn rollback_block_transaction(&self) -> ContextResult<()> {
+        self.journal
+            .lock()
+            .unwrap()
+            .pending
+            .take()
+            .ok_or_else(|| anyhow!("No block transaction in progress"))?;
+        self.inner.rollback_block_transaction().await
+    }
+}
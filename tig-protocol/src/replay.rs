@@ -0,0 +1,306 @@
+use crate::{context::*, InMemoryContext, Protocol};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, RwLock},
+};
+use tig_structs::core::*;
+use tig_utils::{jsonify_internal_with_sets, PreciseNumber};
+
+/// Items waiting in the mempool right before a block was added
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Mempool {
+    /// Players registered since the previous block
+    #[serde(default)]
+    pub players: Vec<Player>,
+    #[serde(default)]
+    pub challenges: Vec<Challenge>,
+    #[serde(default)]
+    pub algorithms: Vec<Algorithm>,
+    #[serde(default)]
+    pub precommits: Vec<Precommit>,
+    #[serde(default)]
+    pub benchmarks: Vec<Benchmark>,
+    #[serde(default)]
+    pub proofs: Vec<Proof>,
+    #[serde(default)]
+    pub frauds: Vec<Fraud>,
+    /// Alleged frauds that have been appealed
+    #[serde(default)]
+    pub fraud_appeals: Vec<Fraud>,
+    #[serde(default)]
+    pub topups: Vec<TopUp>,
+    #[serde(default)]
+    pub wasms: Vec<Wasm>,
+}
+
+/// A block as stored by a `Context`, along with the mempool it was built from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RecordedBlock {
+    /// Must include `data` and `config`
+    pub block: Block,
+    pub mempool: Mempool,
+    pub challenges: HashMap<String, ChallengeBlockData>,
+    pub players: HashMap<String, PlayerBlockData>,
+    pub algorithms: HashMap<String, AlgorithmBlockData>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    /// One of `block`, `challenge`, `player` or `algorithm`
+    pub entity: String,
+    pub id: String,
+    pub field: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BlockDiff {
+    pub height: u32,
+    pub block_id: String,
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Reads the mempool of `ctx`. Players are not part of the `Context` mempool and are left empty
+pub async fn read_mempool<T: Context>(ctx: &T) -> ContextResult<Mempool> {
+    Ok(Mempool {
+        players: Vec::new(),
+        challenges: ctx.get_challenges(ChallengesFilter::Mempool, None).await?,
+        algorithms: ctx
+            .get_algorithms(AlgorithmsFilter::Mempool, None, true)
+            .await?,
+        precommits: ctx
+            .get_precommits(PrecommitsFilter::Mempool {
+                from_block_started: 0,
+            })
+            .await?,
+        benchmarks: ctx
+            .get_benchmarks(
+                BenchmarksFilter::Mempool {
+                    from_block_started: 0,
+                },
+                true,
+            )
+            .await?,
+        proofs: ctx
+            .get_proofs(
+                ProofsFilter::Mempool {
+                    from_block_started: 0,
+                },
+                true,
+            )
+            .await?,
+        frauds: ctx
+            .get_frauds(
+                FraudsFilter::Mempool {
+                    from_block_started: 0,
+                },
+                true,
+            )
+            .await?,
+        fraud_appeals: ctx
+            .get_frauds(
+                FraudsFilter::Confirmed {
+                    from_block_started: 0,
+                },
+                true,
+            )
+            .await?
+            .into_iter()
+            .filter(|f| {
+                f.appeal.is_some()
+                    && f.state
+                        .as_ref()
+                        .is_some_and(|s| s.status == Some(FraudStatus::Alleged))
+            })
+            .collect(),
+        topups: ctx.get_topups(TopUpsFilter::Mempool).await?,
+        wasms: ctx.get_wasms(WasmsFilter::Mempool).await?,
+    })
+}
+
+/// Reads block `block_id` and the block data of every challenge, algorithm, benchmarker and
+/// innovator from `ctx`
+pub async fn read_recorded_block<T: Context>(
+    ctx: &T,
+    block_id: &String,
+    mempool: Mempool,
+) -> ContextResult<RecordedBlock> {
+    let block = ctx
+        .get_block(BlockFilter::Id(block_id.clone()), true)
+        .await?
+        .ok_or_else(|| anyhow!("Block '{}' not found", block_id))?;
+    let block_filter = Some(BlockFilter::Id(block_id.clone()));
+    let challenges = ctx
+        .get_challenges(ChallengesFilter::Confirmed, block_filter.clone())
+        .await?
+        .into_iter()
+        .filter_map(|c| c.block_data.map(|d| (c.id, d)))
+        .collect();
+    let algorithms = ctx
+        .get_algorithms(AlgorithmsFilter::Confirmed, block_filter.clone(), false)
+        .await?
+        .into_iter()
+        .filter_map(|a| a.block_data.map(|d| (a.id, d)))
+        .collect();
+    let mut players = HashMap::new();
+    for filter in [PlayersFilter::Benchmarkers, PlayersFilter::Innovators] {
+        for player in ctx.get_players(filter, block_filter.clone()).await? {
+            if let Some(block_data) = player.block_data {
+                players.insert(player.id, block_data);
+            }
+        }
+    }
+    Ok(RecordedBlock {
+        block,
+        mempool,
+        challenges,
+        players,
+        algorithms,
+    })
+}
+
+/// Re-executes `add_block` on top of `parent_state` (output of `InMemoryContext::export_state`)
+/// for each of `blocks` in order, and compares the computed block with the recorded one.
+///
+/// Blocks are chained: each replayed block is built on top of the previously replayed block,
+/// so a mismatch will usually carry over into later blocks. Player deposits are taken from
+/// the recorded `PlayerBlockData`
+pub async fn replay_blocks(
+    parent_state: &str,
+    blocks: &[RecordedBlock],
+) -> ContextResult<Vec<BlockDiff>> {
+    let deposits = Arc::new(RwLock::new(HashMap::<String, PreciseNumber>::new()));
+    let ctx = {
+        let deposits = deposits.clone();
+        InMemoryContext::new(blocks.first().map_or_else(
+            || Err(anyhow!("No blocks to replay")),
+            |b| Ok(b.block.config().clone()),
+        )?)
+        .with_get_player_deposit(move |_, player_id| {
+            Ok(deposits.read().unwrap().get(player_id).cloned())
+        })
+        .import_state(parent_state)?
+    };
+    let protocol = Protocol::new(ctx);
+
+    let mut diffs = Vec::new();
+    for recorded in blocks.iter() {
+        *deposits.write().unwrap() = recorded
+            .players
+            .iter()
+            .filter_map(|(id, d)| d.deposit.map(|deposit| (id.clone(), deposit)))
+            .collect();
+        let ctx = &protocol.ctx;
+        ctx.insert_mempool(&recorded.mempool);
+        ctx.set_config(recorded.block.config().clone());
+        ctx.set_eth_block_num(recorded.block.details.eth_block_num().clone());
+        ctx.set_next_block_id(recorded.block.id.clone());
+
+        let block_id = protocol.add_block().await;
+        let actual = read_recorded_block(ctx, &block_id, recorded.mempool.clone()).await?;
+        diffs.push(BlockDiff {
+            height: recorded.block.details.height,
+            block_id: recorded.block.id.clone(),
+            mismatches: diff_blocks(recorded, &actual),
+        });
+    }
+    Ok(diffs)
+}
+
+fn diff_blocks(expected: &RecordedBlock, actual: &RecordedBlock) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    let block_id = &expected.block.id;
+    diff_entity(
+        "block",
+        block_id,
+        to_value(&expected.block.details),
+        to_value(&actual.block.details),
+        &mut mismatches,
+    );
+    diff_entity(
+        "block",
+        block_id,
+        to_value(&expected.block.data),
+        to_value(&actual.block.data),
+        &mut mismatches,
+    );
+    diff_entities(
+        "challenge",
+        &expected.challenges,
+        &actual.challenges,
+        &mut mismatches,
+    );
+    diff_entities(
+        "player",
+        &expected.players,
+        &actual.players,
+        &mut mismatches,
+    );
+    diff_entities(
+        "algorithm",
+        &expected.algorithms,
+        &actual.algorithms,
+        &mut mismatches,
+    );
+    mismatches
+}
+
+fn diff_entities<T: Serialize>(
+    entity: &str,
+    expected: &HashMap<String, T>,
+    actual: &HashMap<String, T>,
+    mismatches: &mut Vec<Mismatch>,
+) {
+    let ids: BTreeSet<&String> = expected.keys().chain(actual.keys()).collect();
+    for id in ids {
+        diff_entity(
+            entity,
+            id,
+            to_value(&expected.get(id)),
+            to_value(&actual.get(id)),
+            mismatches,
+        );
+    }
+}
+
+fn diff_entity(
+    entity: &str,
+    id: &str,
+    expected: Value,
+    actual: Value,
+    mismatches: &mut Vec<Mismatch>,
+) {
+    let mismatch = |field: &str, expected: &Value, actual: &Value| Mismatch {
+        entity: entity.to_string(),
+        id: id.to_string(),
+        field: field.to_string(),
+        expected: expected.clone(),
+        actual: actual.clone(),
+    };
+    match (&expected, &actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            let fields: BTreeSet<&String> = expected.keys().chain(actual.keys()).collect();
+            for field in fields {
+                let e = expected.get(field).unwrap_or(&Value::Null);
+                let a = actual.get(field).unwrap_or(&Value::Null);
+                if e != a {
+                    mismatches.push(mismatch(field, e, a));
+                }
+            }
+        }
+        _ if expected != actual => mismatches.push(mismatch("*", &expected, &actual)),
+        _ => {}
+    }
+}
+
+/// Serializes `obj` with sets sorted, so that equal sets compare equal
+fn to_value<T: Serialize>(obj: &T) -> Value {
+    jsonify_internal_with_sets(
+        &serde_json::to_value(obj).expect("to_value failed on serializable object"),
+        SET_FIELDS,
+    )
+}
@@ -0,0 +1,173 @@
+use crate::{context::*, error::*, submit_proof};
+use logging_timer::time;
+use tig_structs::core::*;
+
+#[time]
+pub(crate) async fn execute<T: Context>(
+    ctx: &T,
+    player: &Player,
+    benchmark_id: &String,
+) -> ProtocolResult<Result<(), String>> {
+    let fraud = get_fraud_by_id(ctx, benchmark_id).await?;
+    verify_fraud_is_appealable(&fraud)?;
+    let precommit = get_precommit_by_id(ctx, benchmark_id).await?;
+    verify_benchmark_ownership(player, &precommit.settings)?;
+    let benchmark = get_benchmark_by_id(ctx, benchmark_id).await?;
+    let proof = get_proof_by_benchmark_id(ctx, benchmark_id).await?;
+    let merkle_proofs = proof.merkle_proofs();
+    let mut verification_result =
+        submit_proof::verify_merkle_proofs(&precommit, &benchmark, merkle_proofs);
+    if verification_result.is_ok() {
+        verification_result =
+            submit_proof::verify_solutions_are_valid(ctx, &precommit, &benchmark, merkle_proofs)
+                .await;
+    }
+    if verification_result.is_ok() {
+        verification_result =
+            verify_solutions_with_second_verifier(ctx, &precommit, &proof, &fraud).await?;
+    }
+    ctx.add_fraud_appeal_to_mempool(
+        benchmark_id,
+        FraudAppeal {
+            verification_error: verification_result.as_ref().err().map(|e| e.to_string()),
+        },
+    )
+    .await
+    .unwrap_or_else(|e| panic!("add_fraud_appeal_to_mempool error: {:?}", e));
+    Ok(verification_result.map_err(|e| e.to_string()))
+}
+
+#[time]
+async fn get_fraud_by_id<T: Context>(ctx: &T, benchmark_id: &str) -> ProtocolResult<Fraud> {
+    ctx.get_frauds(FraudsFilter::BenchmarkId(benchmark_id.to_string()), true)
+        .await
+        .unwrap_or_else(|e| panic!("get_frauds error: {:?}", e))
+        .pop()
+        .filter(|f| f.state.is_some())
+        .ok_or_else(|| ProtocolError::InvalidFraud {
+            benchmark_id: benchmark_id.to_string(),
+        })
+}
+
+#[time]
+fn verify_fraud_is_appealable(fraud: &Fraud) -> ProtocolResult<()> {
+    if fraud.appeal.is_some() {
+        return Err(ProtocolError::DuplicateFraudAppeal {
+            benchmark_id: fraud.benchmark_id.clone(),
+        });
+    }
+    if fraud.state().status != Some(FraudStatus::Alleged) {
+        return Err(ProtocolError::FraudNotAppealable {
+            benchmark_id: fraud.benchmark_id.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[time]
+async fn get_precommit_by_id<T: Context>(ctx: &T, benchmark_id: &str) -> ProtocolResult<Precommit> {
+    ctx.get_precommits(PrecommitsFilter::BenchmarkId(benchmark_id.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("get_precommits error: {:?}", e))
+        .pop()
+        .filter(|p| p.state.is_some())
+        .ok_or_else(|| ProtocolError::InvalidPrecommit {
+            benchmark_id: benchmark_id.to_string(),
+        })
+}
+
+#[time]
+fn verify_benchmark_ownership(player: &Player, settings: &BenchmarkSettings) -> ProtocolResult<()> {
+    let expected_player_id = settings.player_id.clone();
+    if player.id != expected_player_id {
+        return Err(ProtocolError::InvalidSubmittingPlayer {
+            actual_player_id: player.id.to_string(),
+            expected_player_id,
+        });
+    }
+    Ok(())
+}
+
+#[time]
+async fn get_benchmark_by_id<T: Context>(
+    ctx: &T,
+    benchmark_id: &String,
+) -> ProtocolResult<Benchmark> {
+    ctx.get_benchmarks(BenchmarksFilter::Id(benchmark_id.clone()), true)
+        .await
+        .unwrap_or_else(|e| panic!("get_benchmarks error: {:?}", e))
+        .pop()
+        .filter(|b| b.state.is_some())
+        .ok_or_else(|| ProtocolError::InvalidBenchmark {
+            benchmark_id: benchmark_id.to_string(),
+        })
+}
+
+#[time]
+async fn get_proof_by_benchmark_id<T: Context>(
+    ctx: &T,
+    benchmark_id: &String,
+) -> ProtocolResult<Proof> {
+    Ok(ctx
+        .get_proofs(ProofsFilter::BenchmarkId(benchmark_id.clone()), true)
+        .await
+        .unwrap_or_else(|e| panic!("get_proofs error: {:?}", e))
+        .pop()
+        .unwrap_or_else(|| panic!("Expecting proof for benchmark {} to exist", benchmark_id)))
+}
+
+// Errors if the second verifier could not be run, in which case the appeal is not recorded
+#[time]
+async fn verify_solutions_with_second_verifier<T: Context>(
+    ctx: &T,
+    precommit: &Precommit,
+    proof: &Proof,
+    fraud: &Fraud,
+) -> ProtocolResult<ProtocolResult<()>> {
+    let settings = &precommit.settings;
+    let wasm_vm_config = ctx
+        .get_block(BlockFilter::Id(settings.block_id.clone()), false)
+        .await
+        .unwrap_or_else(|e| panic!("get_block error: {:?}", e))
+        .unwrap_or_else(|| panic!("Expecting block {} to exist", settings.block_id))
+        .config
+        .unwrap()
+        .wasm_vm;
+
+    // nonce challenged by another player, which is usually not one of the sampled nonces
+    if let Some(evidence) = fraud.evidence.as_ref() {
+        let nonce = evidence.leaf.nonce;
+        let is_match = ctx
+            .recompute_solution(settings, nonce, &wasm_vm_config)
+            .await
+            .map_err(|e| ProtocolError::VerifierUnavailable { msg: e.to_string() })?
+            .is_ok_and(|actual_solution_data| {
+                OutputMetaData::from(actual_solution_data) == evidence.leaf
+            });
+        if !is_match {
+            return Ok(Err(ProtocolError::InvalidSolutionData {
+                algorithm_id: settings.algorithm_id.clone(),
+                nonce,
+            }));
+        }
+    }
+
+    for merkle_proof in proof.merkle_proofs() {
+        if let Ok(actual_solution_data) = ctx
+            .recompute_solution(settings, merkle_proof.leaf.nonce, &wasm_vm_config)
+            .await
+            .map_err(|e| ProtocolError::VerifierUnavailable { msg: e.to_string() })?
+        {
+            if actual_solution_data == merkle_proof.leaf {
+                continue;
+            }
+        }
+
+        return Ok(Err(ProtocolError::InvalidSolutionData {
+            algorithm_id: settings.algorithm_id.clone(),
+            nonce: merkle_proof.leaf.nonce,
+        }));
+    }
+
+    Ok(Ok(()))
+}
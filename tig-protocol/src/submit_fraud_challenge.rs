@@ -0,0 +1,163 @@
+use crate::{context::*, error::*};
+use logging_timer::time;
+use tig_structs::core::*;
+use tig_utils::MerkleHash;
+
+#[time]
+pub(crate) async fn execute<T: Context>(
+    ctx: &T,
+    player: &Player,
+    benchmark_id: &String,
+    nonce: u64,
+    evidence: FraudEvidence,
+) -> ProtocolResult<()> {
+    verify_fraud_not_already_raised(ctx, benchmark_id).await?;
+    let precommit = get_precommit_by_id(ctx, benchmark_id).await?;
+    verify_challenger_is_not_benchmarker(player, &precommit)?;
+    let benchmark = get_benchmark_by_id(ctx, benchmark_id).await?;
+    verify_evidence_nonce(&precommit, nonce, &evidence)?;
+    verify_merkle_branch(&precommit, &benchmark, &evidence)?;
+    verify_evidence_contradicts_leaf(&evidence)?;
+    verify_recomputed_output(ctx, &precommit, &evidence).await?;
+    let allegation = ProtocolError::InvalidSolutionData {
+        algorithm_id: precommit.settings.algorithm_id.clone(),
+        nonce,
+    };
+    ctx.add_fraud_challenge_to_mempool(benchmark_id, &player.id, allegation.to_string(), evidence)
+        .await
+        .unwrap_or_else(|e| panic!("add_fraud_challenge_to_mempool error: {:?}", e));
+    Ok(())
+}
+
+#[time]
+async fn verify_fraud_not_already_raised<T: Context>(
+    ctx: &T,
+    benchmark_id: &str,
+) -> ProtocolResult<()> {
+    if !ctx
+        .get_frauds(FraudsFilter::BenchmarkId(benchmark_id.to_string()), false)
+        .await
+        .unwrap_or_else(|e| panic!("get_frauds error: {:?}", e))
+        .is_empty()
+    {
+        return Err(ProtocolError::FlaggedAsFraud {
+            benchmark_id: benchmark_id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[time]
+async fn get_precommit_by_id<T: Context>(ctx: &T, benchmark_id: &str) -> ProtocolResult<Precommit> {
+    ctx.get_precommits(PrecommitsFilter::BenchmarkId(benchmark_id.to_string()))
+        .await
+        .unwrap_or_else(|e| panic!("get_precommits error: {:?}", e))
+        .pop()
+        .filter(|p| p.state.is_some())
+        .ok_or_else(|| ProtocolError::InvalidPrecommit {
+            benchmark_id: benchmark_id.to_string(),
+        })
+}
+
+#[time]
+fn verify_challenger_is_not_benchmarker(
+    player: &Player,
+    precommit: &Precommit,
+) -> ProtocolResult<()> {
+    if player.id == precommit.settings.player_id {
+        return Err(ProtocolError::InvalidFraudChallenger {
+            benchmark_id: precommit.benchmark_id.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[time]
+async fn get_benchmark_by_id<T: Context>(ctx: &T, benchmark_id: &str) -> ProtocolResult<Benchmark> {
+    ctx.get_benchmarks(BenchmarksFilter::Id(benchmark_id.to_string()), false)
+        .await
+        .unwrap_or_else(|e| panic!("get_benchmarks error: {:?}", e))
+        .pop()
+        .filter(|b| b.state.is_some())
+        .ok_or_else(|| ProtocolError::InvalidBenchmark {
+            benchmark_id: benchmark_id.to_string(),
+        })
+}
+
+#[time]
+fn verify_evidence_nonce(
+    precommit: &Precommit,
+    nonce: u64,
+    evidence: &FraudEvidence,
+) -> ProtocolResult<()> {
+    if nonce >= *precommit.details.num_nonces.as_ref().unwrap() as u64 {
+        return Err(ProtocolError::InvalidBenchmarkNonce { nonce });
+    }
+    if evidence.leaf.nonce != nonce || evidence.output_data.nonce != nonce {
+        return Err(ProtocolError::InvalidFraudEvidence { nonce });
+    }
+    Ok(())
+}
+
+#[time]
+fn verify_merkle_branch(
+    precommit: &Precommit,
+    benchmark: &Benchmark,
+    evidence: &FraudEvidence,
+) -> ProtocolResult<()> {
+    let nonce = evidence.leaf.nonce;
+    let max_branch_len =
+        (64 - (*precommit.details.num_nonces.as_ref().unwrap() - 1).leading_zeros()) as usize;
+    if evidence.branch.0.len() > max_branch_len
+        || evidence
+            .branch
+            .0
+            .iter()
+            .any(|(d, _)| *d as usize > max_branch_len)
+    {
+        return Err(ProtocolError::InvalidMerkleProof { nonce });
+    }
+    let expected_merkle_root = benchmark.details.merkle_root.clone().unwrap();
+    let hash = MerkleHash::from(evidence.leaf.clone());
+    let result = evidence.branch.calc_merkle_root(&hash, nonce as usize);
+    if !result.is_ok_and(|actual_merkle_root| actual_merkle_root == expected_merkle_root) {
+        return Err(ProtocolError::InvalidMerkleProof { nonce });
+    }
+    Ok(())
+}
+
+#[time]
+fn verify_evidence_contradicts_leaf(evidence: &FraudEvidence) -> ProtocolResult<()> {
+    if OutputMetaData::from(evidence.output_data.clone()) == evidence.leaf {
+        return Err(ProtocolError::InvalidFraudEvidence {
+            nonce: evidence.leaf.nonce,
+        });
+    }
+    Ok(())
+}
+
+#[time]
+async fn verify_recomputed_output<T: Context>(
+    ctx: &T,
+    precommit: &Precommit,
+    evidence: &FraudEvidence,
+) -> ProtocolResult<()> {
+    let settings = &precommit.settings;
+    let wasm_vm_config = ctx
+        .get_block(BlockFilter::Id(settings.block_id.clone()), false)
+        .await
+        .unwrap_or_else(|e| panic!("get_block error: {:?}", e))
+        .unwrap_or_else(|| panic!("Expecting block {} to exist", settings.block_id))
+        .config
+        .unwrap()
+        .wasm_vm;
+    let nonce = evidence.output_data.nonce;
+    match ctx
+        .compute_solution(settings, nonce, &wasm_vm_config)
+        .await
+        .unwrap_or_else(|e| panic!("compute_solution error: {:?}", e))
+    {
+        Ok(actual_output_data) if actual_output_data == evidence.output_data => Ok(()),
+        _ => Err(ProtocolError::InvalidFraudEvidence { nonce }),
+    }
+}
@@ -18,7 +18,8 @@ pub(crate) async fn execute<T: Context>(
     verify_sampled_nonces(&benchmark, &merkle_proofs)?;
     let mut verification_result = verify_merkle_proofs(&precommit, &benchmark, &merkle_proofs);
     if verification_result.is_ok() {
-        verification_result = verify_solutions_are_valid(ctx, &precommit, &merkle_proofs).await;
+        verification_result =
+            verify_solutions_are_valid(ctx, &precommit, &benchmark, &merkle_proofs).await;
     };
     ctx.add_proof_to_mempool(benchmark_id, merkle_proofs)
         .await
@@ -94,7 +95,7 @@ fn verify_benchmark_ownership(player: &Player, settings: &BenchmarkSettings) ->
 }
 
 #[time]
-fn verify_merkle_proofs(
+pub(crate) fn verify_merkle_proofs(
     precommit: &Precommit,
     benchmark: &Benchmark,
     merkle_proofs: &Vec<MerkleProof>,
@@ -145,19 +146,28 @@ fn verify_sampled_nonces(
 }
 
 #[time]
-async fn verify_solutions_are_valid<T: Context>(
+pub(crate) async fn verify_solutions_are_valid<T: Context>(
     ctx: &T,
     precommit: &Precommit,
+    benchmark: &Benchmark,
     merkle_proofs: &Vec<MerkleProof>,
 ) -> ProtocolResult<()> {
+    let solution_nonces = benchmark.solution_nonces.as_ref().unwrap();
     for p in merkle_proofs.iter() {
-        if ctx
+        let result = ctx
             .verify_solution(&precommit.settings, p.leaf.nonce, &p.leaf.solution)
             .await
-            .unwrap_or_else(|e| panic!("verify_solution error: {:?}", e))
-            .is_err()
-        {
-            return Err(ProtocolError::InvalidSolution {
+            .unwrap_or_else(|e| panic!("verify_solution error: {:?}", e));
+        if solution_nonces.contains(&p.leaf.nonce) {
+            if let Err(e) = result {
+                return Err(ProtocolError::InvalidSolution {
+                    nonce: p.leaf.nonce,
+                    reason: VerificationError::from_anyhow(&e),
+                });
+            }
+        } else if result.is_ok() {
+            // sampled non-solutions must not be solutions, else solutions are being withheld
+            return Err(ProtocolError::WithheldSolution {
                 nonce: p.leaf.nonce,
             });
         }
@@ -0,0 +1,159 @@
+#![allow(dead_code)]
+
+use serde_json::{json, Map};
+use std::collections::HashMap;
+use tig_protocol::{context::*, InMemoryContext, Protocol};
+use tig_structs::{config::ProtocolConfig, core::*};
+use tig_utils::{dejsonify, PreciseNumber};
+
+pub const PLAYER_ID: &str = "0x0000000000000000000000000000000000000001";
+pub const BURN_ADDRESS: &str = "0x000000000000000000000000000000000000dead";
+
+pub fn test_config() -> ProtocolConfig {
+    let config = json!({
+        "erc20": {
+            "rpc_url": "",
+            "chain_id": "",
+            "token_address": "",
+            "burn_address": BURN_ADDRESS,
+        },
+        "benchmark_submissions": {
+            "min_num_solutions": 1,
+            "submission_delay_multiplier": 1.0,
+            "max_samples": 2,
+            "lifespan_period": 100,
+        },
+        "precommit_submissions": {
+            "min_per_nonce_fee": PreciseNumber::from(1),
+            "min_base_fee": PreciseNumber::from(10),
+            "max_fee_percentage_delta": 0.5,
+            "target_num_precommits": 1,
+            "topup_amount": PreciseNumber::from(1000),
+        },
+        "wasm_vm": {
+            "max_memory": 1000000000u64,
+            "max_fuel": 2000000000u64,
+        },
+        "solution_signature": {
+            "max_percent_delta": 0.1,
+            "threshold_decay": 0.99,
+            "equilibrium_rate_multiplier": 1.0,
+            "percent_error_multiplier": 1.0,
+        },
+        "qualifiers": {
+            "cutoff_phase_in_period": 0,
+            "cutoff_multiplier": 2.0,
+            "total_qualifiers_threshold": 1000,
+            "min_cutoff": 5,
+        },
+        "difficulty": {
+            "max_scaling_factor": 10.0,
+            "parameters": {
+                "c001": [
+                    { "name": "x", "min_value": 10, "max_value": 100 },
+                    { "name": "y", "min_value": 10, "max_value": 100 },
+                ],
+                "c002": [
+                    { "name": "x", "min_value": 10, "max_value": 100 },
+                    { "name": "y", "min_value": 10, "max_value": 100 },
+                ],
+            },
+        },
+        "optimisable_proof_of_work": {
+            "imbalance_multiplier": 3.0,
+            "avg_percent_qualifiers_multiplier": 1.5,
+            "enable_proof_of_deposit": false,
+            "rolling_deposit_decay": 0.9,
+        },
+        "rounds": {
+            "blocks_per_round": 10,
+        },
+        "algorithm_submissions": {
+            "submission_fee": PreciseNumber::from(100),
+            "adoption_threshold": 0.25,
+            "merge_points_threshold": 5,
+            "push_delay": 2,
+        },
+        "rewards": {
+            "distribution": {
+                "benchmarkers": 0.75,
+                "optimisations": 0.2,
+                "breakthroughs": 0.05,
+            },
+            "schedule": [
+                { "block_reward": 100.0, "round_start": 1 },
+            ],
+        },
+    });
+    dejsonify(&config.to_string()).unwrap()
+}
+
+/// Nonces `0..6` are solutions. Other nonces have an empty solution, which fails verification
+pub fn test_output_data(nonce: u64) -> OutputData {
+    let solution = if nonce < 6 {
+        json!({ "nonce": nonce }).as_object().unwrap().clone()
+    } else {
+        Map::new()
+    };
+    OutputData {
+        nonce,
+        runtime_signature: nonce * 7,
+        fuel_consumed: 100 + nonce,
+        solution,
+    }
+}
+
+pub fn test_context() -> InMemoryContext {
+    test_context_with_config(test_config())
+}
+
+pub fn test_context_with_config(config: ProtocolConfig) -> InMemoryContext {
+    let transactions = HashMap::from([
+        (
+            "0xalgorithm".to_string(),
+            Transaction {
+                sender: PLAYER_ID.to_string(),
+                receiver: BURN_ADDRESS.to_string(),
+                amount: config.algorithm_submissions.submission_fee,
+            },
+        ),
+        (
+            "0xtopup".to_string(),
+            Transaction {
+                sender: PLAYER_ID.to_string(),
+                receiver: BURN_ADDRESS.to_string(),
+                amount: config.precommit_submissions().topup_amount,
+            },
+        ),
+    ]);
+    InMemoryContext::new(config)
+        .with_get_transaction(move |tx_hash| {
+            transactions
+                .get(tx_hash)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Transaction '{}' not found", tx_hash))
+        })
+        .with_compute_solution(|_, _, nonce, _| Ok(Ok(test_output_data(nonce))))
+        .with_verify_solution(|_, _, _, solution| {
+            if solution.is_empty() {
+                Ok(Err(anyhow::anyhow!("Invalid solution")))
+            } else {
+                Ok(Ok(()))
+            }
+        })
+}
+
+pub async fn add_blocks_until(protocol: &Protocol<InMemoryContext>, height: u32) -> Block {
+    loop {
+        let latest_block = protocol
+            .ctx
+            .get_block(BlockFilter::Latest, true)
+            .await
+            .unwrap()
+            .unwrap();
+        if latest_block.details.height >= height {
+            return latest_block;
+        }
+        protocol.add_block().await;
+    }
+}
@@ -0,0 +1,774 @@
+mod common;
+
+use common::*;
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tig_protocol::{context::*, InMemoryContext, Protocol, ProtocolError, VerificationError};
+use tig_structs::{
+    config::{FraudConfig, ProtocolConfig},
+    core::*,
+};
+use tig_utils::{MerkleTree, PreciseNumber};
+
+const CHALLENGER_ID: &str = "0x0000000000000000000000000000000000000002";
+
+fn test_config_with_fraud(appeal_period: u32) -> ProtocolConfig {
+    let mut config = test_config();
+    config.fraud = Some(FraudConfig {
+        appeal_period,
+        slash_period: 5,
+        slash_percent: 0.5,
+        challenge_bounty_percent: 0.5,
+    });
+    config
+}
+
+/// Output data that does not match what the benchmarker committed to
+fn faulty_output_data(nonce: u64) -> OutputData {
+    OutputData {
+        runtime_signature: 0,
+        ..test_output_data(nonce)
+    }
+}
+
+/// Evidence that the benchmarker's leaf for `nonce` recomputes to `faulty_output_data`
+fn faulty_output_evidence(nonce: u64) -> FraudEvidence {
+    let tree = MerkleTree::new((0..8).map(|n| test_output_data(n).into()).collect(), 8).unwrap();
+    FraudEvidence {
+        leaf: test_output_data(nonce).into(),
+        branch: tree.calc_merkle_branch(nonce as usize).unwrap(),
+        output_data: faulty_output_data(nonce),
+    }
+}
+
+async fn setup_protocol(ctx: InMemoryContext) -> (Protocol<InMemoryContext>, String) {
+    for name in ["satisfiability", "vehicle_routing"] {
+        ctx.add_challenge_to_mempool(ChallengeDetails {
+            name: name.to_string(),
+        })
+        .await
+        .unwrap();
+    }
+    ctx.add_player(
+        PLAYER_ID,
+        PlayerDetails {
+            name: "player".to_string(),
+            is_multisig: false,
+        },
+    );
+    let protocol = Protocol::new(ctx);
+    protocol.add_block().await;
+    for challenge_id in ["c001", "c002"] {
+        protocol
+            .ctx
+            .update_challenge_state(
+                &challenge_id.to_string(),
+                ChallengeState {
+                    block_confirmed: Some(1),
+                    round_active: Some(1),
+                },
+            )
+            .await
+            .unwrap();
+    }
+    let player = get_player(&protocol).await;
+    let algorithm_id = protocol
+        .submit_algorithm(
+            &player,
+            AlgorithmDetails {
+                name: "test_algorithm".to_string(),
+                player_id: PLAYER_ID.to_string(),
+                challenge_id: "c001".to_string(),
+                tx_hash: "0xalgorithm".to_string(),
+            },
+            "code".to_string(),
+        )
+        .await
+        .unwrap();
+    protocol
+        .ctx
+        .add_wasm_to_mempool(
+            &algorithm_id,
+            WasmDetails {
+                compile_success: true,
+                download_url: None,
+            },
+        )
+        .await
+        .unwrap();
+    protocol
+        .submit_topup(&player, "0xtopup".to_string())
+        .await
+        .unwrap();
+    // algorithm becomes active in round 2
+    add_blocks_until(&protocol, 11).await;
+    (protocol, algorithm_id)
+}
+
+async fn get_player(protocol: &Protocol<InMemoryContext>) -> Player {
+    protocol
+        .ctx
+        .get_players(PlayersFilter::Id(PLAYER_ID.to_string()), None)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+async fn get_player_block_data(protocol: &Protocol<InMemoryContext>) -> PlayerBlockData {
+    protocol
+        .ctx
+        .get_players(
+            PlayersFilter::Id(PLAYER_ID.to_string()),
+            Some(BlockFilter::Latest),
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+        .block_data
+        .unwrap()
+}
+
+/// Runs `num_benchmarks` benchmarks, each started from a different recent block, through to
+/// having a submitted proof
+async fn submit_benchmarks(
+    protocol: &Protocol<InMemoryContext>,
+    algorithm_id: &str,
+    num_benchmarks: u32,
+) -> Vec<String> {
+    submit_benchmarks_with_solutions(protocol, algorithm_id, num_benchmarks, (0..6).collect())
+        .await
+        .into_iter()
+        .map(|(benchmark_id, proof_result)| {
+            assert_eq!(proof_result, Ok(()));
+            benchmark_id
+        })
+        .collect()
+}
+
+/// Same as `submit_benchmarks`, but submits `solution_nonces` as the solutions of each
+/// benchmark. Returns the result of each proof submission
+async fn submit_benchmarks_with_solutions(
+    protocol: &Protocol<InMemoryContext>,
+    algorithm_id: &str,
+    num_benchmarks: u32,
+    solution_nonces: HashSet<u64>,
+) -> Vec<(String, Result<(), String>)> {
+    let latest_block = protocol
+        .ctx
+        .get_block(BlockFilter::Latest, false)
+        .await
+        .unwrap()
+        .unwrap();
+    let player = get_player(protocol).await;
+    let mut benchmark_ids = Vec::new();
+    for i in 0..num_benchmarks {
+        let block = protocol
+            .ctx
+            .get_block(BlockFilter::Height(latest_block.details.height - i), false)
+            .await
+            .unwrap()
+            .unwrap();
+        let settings = BenchmarkSettings {
+            player_id: PLAYER_ID.to_string(),
+            block_id: block.id,
+            challenge_id: "c001".to_string(),
+            algorithm_id: algorithm_id.to_string(),
+            difficulty: vec![10, 10],
+        };
+        benchmark_ids.push(
+            protocol
+                .submit_precommit(&player, settings, 8)
+                .await
+                .unwrap(),
+        );
+    }
+    protocol.add_block().await;
+
+    let tree = MerkleTree::new((0..8).map(|n| test_output_data(n).into()).collect(), 8).unwrap();
+    for benchmark_id in benchmark_ids.iter() {
+        protocol
+            .submit_benchmark(
+                &player,
+                benchmark_id,
+                tree.calc_merkle_root(),
+                solution_nonces.clone(),
+            )
+            .await
+            .unwrap();
+    }
+    protocol.add_block().await;
+
+    let mut results = Vec::new();
+    for benchmark_id in benchmark_ids {
+        let benchmark = protocol
+            .ctx
+            .get_benchmarks(BenchmarksFilter::Id(benchmark_id.clone()), true)
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        let merkle_proofs = benchmark
+            .state()
+            .sampled_nonces()
+            .iter()
+            .map(|&n| MerkleProof {
+                leaf: test_output_data(n),
+                branch: Some(tree.calc_merkle_branch(n as usize).unwrap()),
+            })
+            .collect();
+        let result = protocol
+            .submit_proof(&player, &benchmark_id, merkle_proofs)
+            .await
+            .unwrap();
+        results.push((benchmark_id, result));
+    }
+    protocol.add_block().await;
+    results
+}
+
+#[tokio::test]
+async fn test_fraud_slashes_player() {
+    let (protocol, algorithm_id) =
+        setup_protocol(test_context_with_config(test_config_with_fraud(0))).await;
+    let benchmark_ids = submit_benchmarks(&protocol, &algorithm_id, 2).await;
+
+    let block = add_blocks_until(&protocol, 18).await;
+    for benchmark_id in benchmark_ids.iter() {
+        assert!(block.data().active_benchmark_ids.contains(benchmark_id));
+    }
+    let data = get_player_block_data(&protocol).await;
+    let full_reward = *data.reward();
+    assert_eq!(*data.influence(), PreciseNumber::from(1));
+    assert_eq!(*data.fraud_penalty(), PreciseNumber::from(0));
+
+    protocol
+        .ctx
+        .add_fraud_to_mempool(&benchmark_ids[0], "invalid solution".to_string())
+        .await
+        .unwrap();
+    let player = get_player(&protocol).await;
+    assert_eq!(
+        protocol
+            .submit_fraud_appeal(&player, &benchmark_ids[0])
+            .await,
+        Err(ProtocolError::InvalidFraud {
+            benchmark_id: benchmark_ids[0].clone()
+        })
+    );
+    let block = add_blocks_until(&protocol, 19).await;
+    assert!(block.data().confirmed_fraud_ids.contains(&benchmark_ids[0]));
+    // frauds are upheld immediately without an appeal period
+    assert_eq!(
+        protocol
+            .submit_fraud_appeal(&player, &benchmark_ids[0])
+            .await,
+        Err(ProtocolError::FraudNotAppealable {
+            benchmark_id: benchmark_ids[0].clone()
+        })
+    );
+    // solutions of the fraudulent benchmark are removed in the block the fraud is confirmed
+    assert!(!block
+        .data()
+        .active_benchmark_ids
+        .contains(&benchmark_ids[0]));
+    assert!(block
+        .data()
+        .active_benchmark_ids
+        .contains(&benchmark_ids[1]));
+    let data = get_player_block_data(&protocol).await;
+    let half = PreciseNumber::from_f64(0.5);
+    assert_eq!(*data.influence(), half);
+    assert_eq!(*data.reward(), full_reward * half);
+    assert_eq!(*data.fraud_penalty(), full_reward * half);
+
+    // slashed until `slash_period` blocks after the fraud was upheld
+    add_blocks_until(&protocol, 23).await;
+    assert_eq!(*get_player_block_data(&protocol).await.influence(), half);
+    add_blocks_until(&protocol, 24).await;
+    let data = get_player_block_data(&protocol).await;
+    assert_eq!(*data.influence(), PreciseNumber::from(1));
+    assert_eq!(*data.fraud_penalty(), PreciseNumber::from(0));
+}
+
+#[tokio::test]
+async fn test_no_slashing_without_config() {
+    let (protocol, algorithm_id) =
+        setup_protocol(test_context_with_config(test_config_with_fraud(0))).await;
+    let mut config = protocol.ctx.get_config().await.unwrap();
+    config.fraud = None;
+    protocol.ctx.set_config(config);
+    let benchmark_ids = submit_benchmarks(&protocol, &algorithm_id, 2).await;
+    add_blocks_until(&protocol, 18).await;
+
+    protocol
+        .ctx
+        .add_fraud_to_mempool(&benchmark_ids[0], "invalid solution".to_string())
+        .await
+        .unwrap();
+    let block = add_blocks_until(&protocol, 19).await;
+    assert!(!block
+        .data()
+        .active_benchmark_ids
+        .contains(&benchmark_ids[0]));
+    let data = get_player_block_data(&protocol).await;
+    assert_eq!(*data.influence(), PreciseNumber::from(1));
+    assert!(data.fraud_penalty.is_none());
+}
+
+async fn get_fraud_state(protocol: &Protocol<InMemoryContext>, benchmark_id: &str) -> FraudState {
+    protocol
+        .ctx
+        .get_frauds(FraudsFilter::BenchmarkId(benchmark_id.to_string()), false)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+        .state
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_appeal_overturns_fraud() {
+    // primary verifier is faulty, second verifier is not
+    let ctx = test_context_with_config(test_config_with_fraud(4))
+        .with_compute_solution(|_, _, nonce, _| Ok(Ok(faulty_output_data(nonce))))
+        .with_recompute_solution(|_, _, nonce, _| Ok(Ok(test_output_data(nonce))));
+    ctx.add_player(
+        "0x0000000000000000000000000000000000000002",
+        PlayerDetails {
+            name: "other".to_string(),
+            is_multisig: false,
+        },
+    );
+    let (protocol, algorithm_id) = setup_protocol(ctx).await;
+    let benchmark_ids = submit_benchmarks(&protocol, &algorithm_id, 2).await;
+    assert!(protocol
+        .verify_proof(&benchmark_ids[1])
+        .await
+        .unwrap()
+        .is_err());
+    add_blocks_until(&protocol, 15).await;
+    let state = get_fraud_state(&protocol, &benchmark_ids[1]).await;
+    assert_eq!(state.status, Some(FraudStatus::Alleged));
+    assert_eq!(*state.block_confirmed(), 15);
+
+    let other = protocol
+        .ctx
+        .get_players(
+            PlayersFilter::Id("0x0000000000000000000000000000000000000002".to_string()),
+            None,
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert!(matches!(
+        protocol
+            .submit_fraud_appeal(&other, &benchmark_ids[1])
+            .await,
+        Err(ProtocolError::InvalidSubmittingPlayer { .. })
+    ));
+    let player = get_player(&protocol).await;
+    assert_eq!(
+        protocol
+            .submit_fraud_appeal(&player, &benchmark_ids[1])
+            .await,
+        Ok(Ok(()))
+    );
+    assert_eq!(
+        protocol
+            .submit_fraud_appeal(&player, &benchmark_ids[1])
+            .await,
+        Err(ProtocolError::DuplicateFraudAppeal {
+            benchmark_id: benchmark_ids[1].clone()
+        })
+    );
+    add_blocks_until(&protocol, 16).await;
+    let state = get_fraud_state(&protocol, &benchmark_ids[1]).await;
+    assert_eq!(state.status, Some(FraudStatus::Disputed));
+
+    // solutions stay removed while the fraud is disputed, but the player is not slashed
+    let block = add_blocks_until(&protocol, 18).await;
+    assert!(block
+        .data()
+        .active_benchmark_ids
+        .contains(&benchmark_ids[0]));
+    assert!(!block
+        .data()
+        .active_benchmark_ids
+        .contains(&benchmark_ids[1]));
+    assert_eq!(
+        *get_player_block_data(&protocol).await.influence(),
+        PreciseNumber::from(1)
+    );
+
+    // resolved when the appeal period ends
+    let block = add_blocks_until(&protocol, 19).await;
+    let state = get_fraud_state(&protocol, &benchmark_ids[1]).await;
+    assert_eq!(state.status, Some(FraudStatus::Overturned));
+    assert_eq!(*state.block_resolved(), 19);
+    assert!(block
+        .data()
+        .active_benchmark_ids
+        .contains(&benchmark_ids[1]));
+    let data = get_player_block_data(&protocol).await;
+    assert_eq!(*data.influence(), PreciseNumber::from(1));
+    assert_eq!(*data.fraud_penalty(), PreciseNumber::from(0));
+}
+
+#[tokio::test]
+async fn test_failed_appeal_upholds_fraud() {
+    // second verifier defaults to the primary verifier
+    let ctx = test_context_with_config(test_config_with_fraud(4))
+        .with_compute_solution(|_, _, nonce, _| Ok(Ok(faulty_output_data(nonce))));
+    let (protocol, algorithm_id) = setup_protocol(ctx).await;
+    let benchmark_ids = submit_benchmarks(&protocol, &algorithm_id, 2).await;
+    assert!(protocol
+        .verify_proof(&benchmark_ids[1])
+        .await
+        .unwrap()
+        .is_err());
+    add_blocks_until(&protocol, 15).await;
+
+    let player = get_player(&protocol).await;
+    assert!(protocol
+        .submit_fraud_appeal(&player, &benchmark_ids[1])
+        .await
+        .unwrap()
+        .is_err());
+    add_blocks_until(&protocol, 18).await;
+    assert_eq!(
+        *get_player_block_data(&protocol).await.influence(),
+        PreciseNumber::from(1)
+    );
+
+    let block = add_blocks_until(&protocol, 19).await;
+    let state = get_fraud_state(&protocol, &benchmark_ids[1]).await;
+    assert_eq!(state.status, Some(FraudStatus::Upheld));
+    assert_eq!(*state.block_resolved(), 19);
+    assert!(!block
+        .data()
+        .active_benchmark_ids
+        .contains(&benchmark_ids[1]));
+    // slashing starts once the fraud is upheld
+    assert_eq!(
+        *get_player_block_data(&protocol).await.influence(),
+        PreciseNumber::from_f64(0.5)
+    );
+}
+
+#[tokio::test]
+async fn test_appeal_is_not_recorded_when_second_verifier_fails() {
+    let ctx = test_context_with_config(test_config_with_fraud(4))
+        .with_compute_solution(|_, _, nonce, _| Ok(Ok(faulty_output_data(nonce))))
+        .with_recompute_solution(|_, _, _, _| Err(anyhow::anyhow!("verifier offline")));
+    let (protocol, algorithm_id) = setup_protocol(ctx).await;
+    let benchmark_ids = submit_benchmarks(&protocol, &algorithm_id, 1).await;
+    assert!(protocol
+        .verify_proof(&benchmark_ids[0])
+        .await
+        .unwrap()
+        .is_err());
+    add_blocks_until(&protocol, 15).await;
+
+    let player = get_player(&protocol).await;
+    assert!(matches!(
+        protocol
+            .submit_fraud_appeal(&player, &benchmark_ids[0])
+            .await,
+        Err(ProtocolError::VerifierUnavailable { .. })
+    ));
+    // the benchmarker can appeal again once the verifier is back
+    let fraud = protocol
+        .ctx
+        .get_frauds(FraudsFilter::BenchmarkId(benchmark_ids[0].clone()), false)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert!(fraud.appeal.is_none());
+    assert_eq!(fraud.state().status, Some(FraudStatus::Alleged));
+}
+
+#[tokio::test]
+async fn test_fraud_challenge_pays_bounty() {
+    // protocol recomputes different output data from what the benchmarker committed to
+    let ctx = test_context_with_config(test_config_with_fraud(0))
+        .with_compute_solution(|_, _, nonce, _| Ok(Ok(faulty_output_data(nonce))));
+    ctx.add_player(
+        CHALLENGER_ID,
+        PlayerDetails {
+            name: "challenger".to_string(),
+            is_multisig: false,
+        },
+    );
+    let (protocol, algorithm_id) = setup_protocol(ctx).await;
+    let benchmark_ids = submit_benchmarks(&protocol, &algorithm_id, 2).await;
+    add_blocks_until(&protocol, 18).await;
+    let full_reward = *get_player_block_data(&protocol).await.reward();
+
+    let evidence = faulty_output_evidence(7);
+    let player = get_player(&protocol).await;
+    let challenger = protocol
+        .ctx
+        .get_players(PlayersFilter::Id(CHALLENGER_ID.to_string()), None)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(
+        protocol
+            .submit_fraud_challenge(&player, &benchmark_ids[0], 7, evidence.clone())
+            .await,
+        Err(ProtocolError::InvalidFraudChallenger {
+            benchmark_id: benchmark_ids[0].clone()
+        })
+    );
+    assert_eq!(
+        protocol
+            .submit_fraud_challenge(&challenger, &benchmark_ids[0], 8, evidence.clone())
+            .await,
+        Err(ProtocolError::InvalidBenchmarkNonce { nonce: 8 })
+    );
+    assert_eq!(
+        protocol
+            .submit_fraud_challenge(
+                &challenger,
+                &benchmark_ids[0],
+                7,
+                FraudEvidence {
+                    branch: faulty_output_evidence(6).branch,
+                    ..evidence.clone()
+                }
+            )
+            .await,
+        Err(ProtocolError::InvalidMerkleProof { nonce: 7 })
+    );
+    assert_eq!(
+        protocol
+            .submit_fraud_challenge(
+                &challenger,
+                &benchmark_ids[0],
+                7,
+                FraudEvidence {
+                    output_data: test_output_data(7),
+                    ..evidence.clone()
+                }
+            )
+            .await,
+        Err(ProtocolError::InvalidFraudEvidence { nonce: 7 })
+    );
+    assert_eq!(
+        protocol
+            .submit_fraud_challenge(&challenger, &benchmark_ids[0], 7, evidence.clone())
+            .await,
+        Ok(())
+    );
+    assert_eq!(
+        protocol
+            .submit_fraud_challenge(&challenger, &benchmark_ids[0], 7, evidence)
+            .await,
+        Err(ProtocolError::FlaggedAsFraud {
+            benchmark_id: benchmark_ids[0].clone()
+        })
+    );
+
+    let block = add_blocks_until(&protocol, 19).await;
+    assert!(block.data().confirmed_fraud_ids.contains(&benchmark_ids[0]));
+    let fraud = protocol
+        .ctx
+        .get_frauds(FraudsFilter::BenchmarkId(benchmark_ids[0].clone()), false)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(fraud.challenger_id, Some(CHALLENGER_ID.to_string()));
+    assert_eq!(fraud.state().status, Some(FraudStatus::Upheld));
+    let fraud_penalty = *get_player_block_data(&protocol).await.fraud_penalty();
+    let half = PreciseNumber::from_f64(0.5);
+    assert_eq!(fraud_penalty, full_reward * half);
+
+    // bounty accrues from the penalty of every block the benchmarker is slashed, and is credited
+    // to the challenger's fee balance
+    let get_challenger_state = || async {
+        protocol
+            .ctx
+            .get_players(PlayersFilter::Id(CHALLENGER_ID.to_string()), None)
+            .await
+            .unwrap()
+            .pop()
+            .unwrap()
+            .state()
+            .clone()
+    };
+    let state = get_challenger_state().await;
+    assert_eq!(*state.available_fee_balance(), fraud_penalty * half);
+    assert_eq!(*state.total_fraud_bounties(), fraud_penalty * half);
+    add_blocks_until(&protocol, 20).await;
+    let state = get_challenger_state().await;
+    assert_eq!(*state.available_fee_balance(), fraud_penalty);
+    assert_eq!(*state.total_fraud_bounties(), fraud_penalty);
+    // bounty comes out of the benchmarker's forfeited reward
+    let reward = *get_player_block_data(&protocol).await.reward();
+    assert!(reward + fraud_penalty * half <= full_reward);
+}
+
+#[tokio::test]
+async fn test_withheld_solution_is_flagged() {
+    let mut config = test_config_with_fraud(0);
+    config.benchmark_submissions.max_non_solution_samples = Some(3);
+    let (protocol, algorithm_id) = setup_protocol(test_context_with_config(config)).await;
+    // nonce 5 has a valid solution, but is not submitted as one
+    let (benchmark_id, proof_result) =
+        submit_benchmarks_with_solutions(&protocol, &algorithm_id, 1, (0..5).collect())
+            .await
+            .pop()
+            .unwrap();
+    assert_eq!(
+        proof_result,
+        Err(ProtocolError::WithheldSolution { nonce: 5 }.to_string())
+    );
+
+    let benchmark = protocol
+        .ctx
+        .get_benchmarks(BenchmarksFilter::Id(benchmark_id.clone()), true)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let sampled_nonces = benchmark.state().sampled_nonces();
+    // 2 solutions and all 3 non-solutions are sampled
+    assert_eq!(sampled_nonces.len(), 5);
+    assert!([5, 6, 7].iter().all(|n| sampled_nonces.contains(n)));
+    let fraud = protocol
+        .ctx
+        .get_frauds(FraudsFilter::BenchmarkId(benchmark_id), true)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(fraud.allegation, Some(proof_result.unwrap_err()));
+}
+
+#[tokio::test]
+async fn test_invalid_solution_is_flagged_with_reason() {
+    let ctx =
+        test_context_with_config(test_config_with_fraud(0)).with_verify_solution(|_, _, _, _| {
+            Ok(Err(
+                VerificationError::UnsatisfiedClause { clause: 3 }.into()
+            ))
+        });
+    let (protocol, algorithm_id) = setup_protocol(ctx).await;
+    let (benchmark_id, proof_result) =
+        submit_benchmarks_with_solutions(&protocol, &algorithm_id, 1, (0..8).collect())
+            .await
+            .pop()
+            .unwrap();
+
+    let benchmark = protocol
+        .ctx
+        .get_benchmarks(BenchmarksFilter::Id(benchmark_id.clone()), true)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let expected = ProtocolError::InvalidSolution {
+        // proofs are submitted, and so verified, in the order of the sampled nonces
+        nonce: *benchmark.state().sampled_nonces().iter().next().unwrap(),
+        reason: VerificationError::UnsatisfiedClause { clause: 3 },
+    };
+    assert!(expected.to_string().ends_with("Clause '3' not satisfied"));
+    assert_eq!(proof_result, Err(expected.to_string()));
+    let fraud = protocol
+        .ctx
+        .get_frauds(FraudsFilter::BenchmarkId(benchmark_id), true)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(fraud.allegation, Some(expected.to_string()));
+}
+
+#[tokio::test]
+async fn test_appeal_rechecks_challenged_nonce() {
+    // only the challenged nonce, which is not sampled, recomputes differently from what was
+    // committed
+    let challenged_nonce = Arc::new(AtomicU64::new(u64::MAX));
+    let ctx = test_context_with_config(test_config_with_fraud(4)).with_compute_solution({
+        let challenged_nonce = challenged_nonce.clone();
+        move |_, _, nonce, _| {
+            if nonce == challenged_nonce.load(Ordering::Relaxed) {
+                Ok(Ok(faulty_output_data(nonce)))
+            } else {
+                Ok(Ok(test_output_data(nonce)))
+            }
+        }
+    });
+    ctx.add_player(
+        CHALLENGER_ID,
+        PlayerDetails {
+            name: "challenger".to_string(),
+            is_multisig: false,
+        },
+    );
+    let (protocol, algorithm_id) = setup_protocol(ctx).await;
+    let benchmark_ids = submit_benchmarks(&protocol, &algorithm_id, 1).await;
+    let benchmark = protocol
+        .ctx
+        .get_benchmarks(BenchmarksFilter::Id(benchmark_ids[0].clone()), false)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    // faulty output data for nonce 0 is the same as its committed output data
+    let nonce = (1..8)
+        .find(|n| !benchmark.state().sampled_nonces().contains(n))
+        .unwrap();
+    challenged_nonce.store(nonce, Ordering::Relaxed);
+
+    let evidence = faulty_output_evidence(nonce);
+    let challenger = protocol
+        .ctx
+        .get_players(PlayersFilter::Id(CHALLENGER_ID.to_string()), None)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    protocol
+        .submit_fraud_challenge(&challenger, &benchmark_ids[0], nonce, evidence.clone())
+        .await
+        .unwrap();
+    add_blocks_until(&protocol, 15).await;
+    let fraud = protocol
+        .ctx
+        .get_frauds(FraudsFilter::BenchmarkId(benchmark_ids[0].clone()), true)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(fraud.evidence, Some(evidence));
+    assert_eq!(fraud.state().status, Some(FraudStatus::Alleged));
+
+    // sampled nonces all pass, but the challenged nonce does not
+    let player = get_player(&protocol).await;
+    assert_eq!(
+        protocol
+            .submit_fraud_appeal(&player, &benchmark_ids[0])
+            .await,
+        Ok(Err(ProtocolError::InvalidSolutionData {
+            algorithm_id,
+            nonce
+        }
+        .to_string()))
+    );
+    add_blocks_until(&protocol, 19).await;
+    let state = get_fraud_state(&protocol, &benchmark_ids[0]).await;
+    assert_eq!(state.status, Some(FraudStatus::Upheld));
+}
@@ -0,0 +1,307 @@
+mod common;
+
+use common::*;
+use std::collections::HashSet;
+use tig_protocol::{context::*, Protocol};
+use tig_structs::{config::ChallengesConfig, core::*};
+use tig_utils::{MerkleTree, PreciseNumber};
+
+#[tokio::test]
+async fn test_filters() {
+    let ctx = test_context();
+    let challenge_id = ctx
+        .add_challenge_to_mempool(ChallengeDetails {
+            name: "satisfiability".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(challenge_id, "c001");
+    assert_eq!(
+        ctx.get_challenges(ChallengesFilter::Mempool, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(ctx
+        .get_challenges(ChallengesFilter::Confirmed, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let protocol = Protocol::new(ctx);
+    let block_id = protocol.add_block().await;
+    let ctx = &protocol.ctx;
+    assert!(ctx
+        .get_challenges(ChallengesFilter::Mempool, None)
+        .await
+        .unwrap()
+        .is_empty());
+    let challenges = ctx
+        .get_challenges(ChallengesFilter::Name("satisfiability".to_string()), None)
+        .await
+        .unwrap();
+    assert_eq!(*challenges[0].state().block_confirmed(), 1);
+    assert!(challenges[0].state().round_active.is_none());
+
+    let block = ctx
+        .get_block(BlockFilter::Id(block_id.clone()), false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(block.data.is_none());
+    assert_eq!(block.details.height, 1);
+    let block = ctx
+        .get_block(BlockFilter::Round(1), true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(block.id, block_id);
+    assert!(block.data().confirmed_challenge_ids.contains("c001"));
+    assert!(ctx
+        .get_block(BlockFilter::Height(2), false)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_challenges_are_activated_by_add_block() {
+    let mut config = test_config();
+    config.challenges = Some(ChallengesConfig {
+        activation_delay: 1,
+    });
+    let ctx = test_context_with_config(config);
+    ctx.add_challenge_to_mempool(ChallengeDetails {
+        name: "satisfiability".to_string(),
+    })
+    .await
+    .unwrap();
+    let protocol = Protocol::new(ctx);
+    protocol.add_block().await;
+    let challenge = protocol
+        .ctx
+        .get_challenges(ChallengesFilter::Id("c001".to_string()), None)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(challenge.state().round_active, Some(2));
+}
+
+#[tokio::test]
+async fn test_benchmark_lifecycle() {
+    let ctx = test_context();
+    for name in ["satisfiability", "vehicle_routing"] {
+        ctx.add_challenge_to_mempool(ChallengeDetails {
+            name: name.to_string(),
+        })
+        .await
+        .unwrap();
+    }
+    ctx.add_player(
+        PLAYER_ID,
+        PlayerDetails {
+            name: "player".to_string(),
+            is_multisig: false,
+        },
+    );
+    let protocol = Protocol::new(ctx);
+    protocol.add_block().await;
+    for challenge_id in ["c001", "c002"] {
+        protocol
+            .ctx
+            .update_challenge_state(
+                &challenge_id.to_string(),
+                ChallengeState {
+                    block_confirmed: Some(1),
+                    round_active: Some(1),
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    let player = protocol
+        .ctx
+        .get_players(PlayersFilter::Id(PLAYER_ID.to_string()), None)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let algorithm_id = protocol
+        .submit_algorithm(
+            &player,
+            AlgorithmDetails {
+                name: "test_algorithm".to_string(),
+                player_id: PLAYER_ID.to_string(),
+                challenge_id: "c001".to_string(),
+                tx_hash: "0xalgorithm".to_string(),
+            },
+            "code".to_string(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(algorithm_id, "c001_a001");
+    protocol
+        .ctx
+        .add_wasm_to_mempool(
+            &algorithm_id,
+            WasmDetails {
+                compile_success: true,
+                download_url: None,
+            },
+        )
+        .await
+        .unwrap();
+    protocol
+        .submit_topup(&player, "0xtopup".to_string())
+        .await
+        .unwrap();
+    assert!(protocol
+        .submit_topup(&player, "0xtopup".to_string())
+        .await
+        .is_err());
+
+    // algorithm becomes active in round 2
+    let block = add_blocks_until(&protocol, 10).await;
+    assert!(block.data().active_algorithm_ids.contains(&algorithm_id));
+
+    let player = protocol
+        .ctx
+        .get_players(PlayersFilter::Id(PLAYER_ID.to_string()), None)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let settings = BenchmarkSettings {
+        player_id: PLAYER_ID.to_string(),
+        block_id: block.id.clone(),
+        challenge_id: "c001".to_string(),
+        algorithm_id: algorithm_id.clone(),
+        difficulty: vec![10, 10],
+    };
+    let benchmark_id = protocol
+        .submit_precommit(&player, settings.clone(), 8)
+        .await
+        .unwrap();
+    protocol.add_block().await;
+
+    let solution_nonces: HashSet<u64> = (0..6).collect();
+    let tree = MerkleTree::new((0..8).map(|n| test_output_data(n).into()).collect(), 8).unwrap();
+    protocol
+        .submit_benchmark(
+            &player,
+            &benchmark_id,
+            tree.calc_merkle_root(),
+            solution_nonces,
+        )
+        .await
+        .unwrap();
+    protocol.add_block().await;
+
+    let benchmark = protocol
+        .ctx
+        .get_benchmarks(BenchmarksFilter::Id(benchmark_id.clone()), true)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let sampled_nonces = benchmark.state().sampled_nonces();
+    assert_eq!(sampled_nonces.len(), 4);
+    let merkle_proofs = sampled_nonces
+        .iter()
+        .map(|&n| MerkleProof {
+            leaf: test_output_data(n),
+            branch: Some(tree.calc_merkle_branch(n as usize).unwrap()),
+        })
+        .collect();
+    assert_eq!(
+        protocol
+            .submit_proof(&player, &benchmark_id, merkle_proofs)
+            .await
+            .unwrap(),
+        Ok(())
+    );
+    assert_eq!(protocol.verify_proof(&benchmark_id).await.unwrap(), Ok(()));
+    protocol.add_block().await;
+    let proof = protocol
+        .ctx
+        .get_proofs(ProofsFilter::BenchmarkId(benchmark_id.clone()), false)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(*proof.state().submission_delay(), 3);
+
+    // solutions become active after the submission delay
+    let block = add_blocks_until(&protocol, 16).await;
+    assert!(block.data().active_benchmark_ids.contains(&benchmark_id));
+    let player = protocol
+        .ctx
+        .get_players(
+            PlayersFilter::Id(PLAYER_ID.to_string()),
+            Some(BlockFilter::Latest),
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let data = player.block_data();
+    assert_eq!(data.num_qualifiers_by_challenge()["c001"], 5);
+    assert_eq!(*data.influence(), PreciseNumber::from(1));
+    assert!(*data.reward() > PreciseNumber::from(0));
+    let algorithm = protocol
+        .ctx
+        .get_algorithms(
+            AlgorithmsFilter::Id(algorithm_id),
+            Some(BlockFilter::Latest),
+            false,
+        )
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert!(algorithm.code.is_none());
+    assert_eq!(*algorithm.block_data().adoption(), PreciseNumber::from(1));
+    assert_eq!(
+        protocol
+            .ctx
+            .get_players(PlayersFilter::Benchmarkers, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // state root commits to the block data of active challenges, algorithms & players
+    let state_root = block.details.state_root().clone();
+    let state_proof = protocol
+        .get_state_proof(&block.id, StateLeafType::Player, &PLAYER_ID.to_string())
+        .await
+        .unwrap();
+    assert!(state_proof.verify(&state_root));
+    match &state_proof.leaf {
+        StateLeaf::Player { block_data, .. } => assert_eq!(block_data, data),
+        _ => panic!("Expecting player leaf"),
+    }
+    let mut tampered = state_proof.clone();
+    if let StateLeaf::Player { block_data, .. } = &mut tampered.leaf {
+        block_data.reward = Some(PreciseNumber::from(0));
+    }
+    assert!(!tampered.verify(&state_root));
+    for (leaf_type, id) in [
+        (StateLeafType::Challenge, "c001"),
+        (StateLeafType::Algorithm, "c001_a001"),
+    ] {
+        assert!(protocol
+            .get_state_proof(&block.id, leaf_type, &id.to_string())
+            .await
+            .unwrap()
+            .verify(&state_root));
+    }
+    assert!(protocol
+        .get_state_proof(&block.id, StateLeafType::Player, &"0xunknown".to_string())
+        .await
+        .is_err());
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use common::*;
+use std::{fs, io::Write, panic::AssertUnwindSafe, path::PathBuf};
+use tig_protocol::{context::*, PersistentContext, Protocol};
+use tig_structs::core::*;
+
+fn journal_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "tig-protocol-{}-{}.jsonl",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+async fn open(path: &PathBuf) -> PersistentContext {
+    PersistentContext::open(path, test_context()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_reopen_restores_state() {
+    let path = journal_path("reopen");
+    let ctx = open(&path).await;
+    ctx.add_challenge_to_mempool(ChallengeDetails {
+        name: "satisfiability".to_string(),
+    })
+    .await
+    .unwrap();
+    ctx.add_player(
+        PLAYER_ID,
+        PlayerDetails {
+            name: "player".to_string(),
+            is_multisig: false,
+        },
+    )
+    .await
+    .unwrap();
+    let protocol = Protocol::new(ctx);
+    for _ in 0..3 {
+        protocol.add_block().await;
+    }
+    let latest_block = protocol
+        .ctx
+        .get_block(BlockFilter::Latest, true)
+        .await
+        .unwrap()
+        .unwrap();
+    drop(protocol);
+
+    let ctx = open(&path).await;
+    assert_eq!(
+        ctx.get_block(BlockFilter::Latest, true).await.unwrap(),
+        Some(latest_block)
+    );
+    let challenges = ctx
+        .get_challenges(ChallengesFilter::Confirmed, None)
+        .await
+        .unwrap();
+    assert_eq!(challenges.len(), 1);
+    assert_eq!(*challenges[0].state().block_confirmed(), 1);
+    assert_eq!(
+        ctx.get_players(PlayersFilter::Id(PLAYER_ID.to_string()), None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // journal keeps growing after a reopen
+    let protocol = Protocol::new(ctx);
+    protocol.add_block().await;
+    drop(protocol);
+    let ctx = open(&path).await;
+    assert_eq!(
+        ctx.get_block(BlockFilter::Latest, false)
+            .await
+            .unwrap()
+            .unwrap()
+            .details
+            .height,
+        4
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_torn_write_is_discarded() {
+    let path = journal_path("torn");
+    let protocol = Protocol::new(open(&path).await);
+    protocol.add_block().await;
+    drop(protocol);
+    let len = fs::metadata(&path).unwrap().len();
+
+    // crash midway through writing the next block
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(br#"[{"op":"add_block","details":{"#)
+        .unwrap();
+    drop(file);
+
+    let protocol = Protocol::new(open(&path).await);
+    assert_eq!(fs::metadata(&path).unwrap().len(), len);
+    assert!(protocol
+        .ctx
+        .get_block(BlockFilter::Height(2), false)
+        .await
+        .unwrap()
+        .is_none());
+    protocol.add_block().await;
+    drop(protocol);
+
+    let ctx = open(&path).await;
+    assert!(ctx
+        .get_block(BlockFilter::Height(2), false)
+        .await
+        .unwrap()
+        .is_some());
+    fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_rollback_is_not_persisted() {
+    let path = journal_path("rollback");
+    let ctx = open(&path).await;
+    let challenge_id = ctx
+        .add_challenge_to_mempool(ChallengeDetails {
+            name: "satisfiability".to_string(),
+        })
+        .await
+        .unwrap();
+    ctx.begin_block_transaction().await.unwrap();
+    ctx.update_challenge_state(
+        &challenge_id,
+        ChallengeState {
+            block_confirmed: Some(1),
+            round_active: None,
+        },
+    )
+    .await
+    .unwrap();
+    // submissions made while the block is being added are not part of it
+    ctx.add_challenge_to_mempool(ChallengeDetails {
+        name: "vehicle_routing".to_string(),
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        ctx.get_challenges(ChallengesFilter::Mempool, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    ctx.rollback_block_transaction().await.unwrap();
+    assert_eq!(
+        ctx.get_challenges(ChallengesFilter::Mempool, None)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    drop(ctx);
+
+    let ctx = open(&path).await;
+    assert_eq!(
+        ctx.get_challenges(ChallengesFilter::Mempool, None)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_failed_block_is_rolled_back() {
+    let path = journal_path("failed_block");
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let protocol = Protocol::new(runtime.block_on(open(&path)));
+    // without a block reward for round 1, the block fails after it has been written
+    let mut config = test_config();
+    config.rewards.schedule[0].round_start = 2;
+    runtime.block_on(protocol.ctx.set_config(config)).unwrap();
+    let result =
+        std::panic::catch_unwind(AssertUnwindSafe(|| runtime.block_on(protocol.add_block())));
+    assert!(result.is_err());
+    let latest_height = || {
+        runtime
+            .block_on(protocol.ctx.get_block(BlockFilter::Latest, false))
+            .unwrap()
+            .unwrap()
+            .details
+            .height
+    };
+    assert_eq!(latest_height(), 0);
+
+    runtime
+        .block_on(protocol.ctx.set_config(test_config()))
+        .unwrap();
+    runtime.block_on(protocol.add_block());
+    assert_eq!(latest_height(), 1);
+    drop(protocol);
+
+    let ctx = runtime.block_on(open(&path));
+    assert_eq!(
+        runtime
+            .block_on(ctx.get_block(BlockFilter::Latest, false))
+            .unwrap()
+            .unwrap()
+            .details
+            .height,
+        1
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_diverging_replay_is_rejected() {
+    let path = journal_path("diverging");
+    let ctx = open(&path).await;
+    ctx.add_challenge_to_mempool(ChallengeDetails {
+        name: "satisfiability".to_string(),
+    })
+    .await
+    .unwrap();
+    drop(ctx);
+
+    let journal = fs::read_to_string(&path).unwrap();
+    assert!(journal.contains(r#""id":"c001""#));
+    fs::write(&path, journal.replace(r#""id":"c001""#, r#""id":"c002""#)).unwrap();
+    assert!(PersistentContext::open(&path, test_context())
+        .await
+        .is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_corrupt_journal_is_rejected() {
+    let path = journal_path("corrupt");
+    fs::write(&path, "not json\n").unwrap();
+    let result = tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(PersistentContext::open(&path, test_context()));
+    assert!(result.is_err());
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use common::*;
+use std::collections::HashSet;
+use tig_protocol::{context::*, *};
+use tig_structs::core::*;
+use tig_utils::{MerkleTree, PreciseNumber};
+
+async fn add_block(protocol: &Protocol<InMemoryContext>, recorded: &mut Vec<RecordedBlock>) {
+    let mempool = read_mempool(&protocol.ctx).await.unwrap();
+    let block_id = protocol.add_block().await;
+    recorded.push(
+        read_recorded_block(&protocol.ctx, &block_id, mempool)
+            .await
+            .unwrap(),
+    );
+}
+
+async fn get_player(protocol: &Protocol<InMemoryContext>) -> Player {
+    protocol
+        .ctx
+        .get_players(PlayersFilter::Id(PLAYER_ID.to_string()), None)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap()
+}
+
+/// Records blocks 2 to 16 of a player benchmarking their own algorithm
+async fn record_blocks() -> (String, Vec<RecordedBlock>) {
+    let ctx = test_context();
+    ctx.add_player(
+        PLAYER_ID,
+        PlayerDetails {
+            name: "player".to_string(),
+            is_multisig: false,
+        },
+    );
+    for name in ["satisfiability", "vehicle_routing"] {
+        ctx.add_challenge_to_mempool(ChallengeDetails {
+            name: name.to_string(),
+        })
+        .await
+        .unwrap();
+    }
+    let protocol = Protocol::new(ctx);
+    protocol.add_block().await;
+    for challenge_id in ["c001", "c002"] {
+        protocol
+            .ctx
+            .update_challenge_state(
+                &challenge_id.to_string(),
+                ChallengeState {
+                    block_confirmed: Some(1),
+                    round_active: Some(1),
+                },
+            )
+            .await
+            .unwrap();
+    }
+    // challenges are activated outside of add_block, so recording starts after activation
+    let parent_state = protocol.ctx.export_state();
+    let mut recorded = Vec::new();
+
+    let player = get_player(&protocol).await;
+    let algorithm_id = protocol
+        .submit_algorithm(
+            &player,
+            AlgorithmDetails {
+                name: "test_algorithm".to_string(),
+                player_id: PLAYER_ID.to_string(),
+                challenge_id: "c001".to_string(),
+                tx_hash: "0xalgorithm".to_string(),
+            },
+            "code".to_string(),
+        )
+        .await
+        .unwrap();
+    protocol
+        .ctx
+        .add_wasm_to_mempool(
+            &algorithm_id,
+            WasmDetails {
+                compile_success: true,
+                download_url: None,
+            },
+        )
+        .await
+        .unwrap();
+    protocol
+        .submit_topup(&player, "0xtopup".to_string())
+        .await
+        .unwrap();
+    while recorded.len() < 9 {
+        add_block(&protocol, &mut recorded).await;
+    }
+
+    let player = get_player(&protocol).await;
+    let settings = BenchmarkSettings {
+        player_id: PLAYER_ID.to_string(),
+        block_id: recorded.last().unwrap().block.id.clone(),
+        challenge_id: "c001".to_string(),
+        algorithm_id,
+        difficulty: vec![10, 10],
+    };
+    let benchmark_id = protocol
+        .submit_precommit(&player, settings, 8)
+        .await
+        .unwrap();
+    add_block(&protocol, &mut recorded).await;
+    let tree = MerkleTree::new((0..8).map(|n| test_output_data(n).into()).collect(), 8).unwrap();
+    protocol
+        .submit_benchmark(
+            &player,
+            &benchmark_id,
+            tree.calc_merkle_root(),
+            (0..6).collect::<HashSet<u64>>(),
+        )
+        .await
+        .unwrap();
+    add_block(&protocol, &mut recorded).await;
+    let benchmark = protocol
+        .ctx
+        .get_benchmarks(BenchmarksFilter::Id(benchmark_id.clone()), false)
+        .await
+        .unwrap()
+        .pop()
+        .unwrap();
+    let merkle_proofs = benchmark
+        .state()
+        .sampled_nonces()
+        .iter()
+        .map(|&n| MerkleProof {
+            leaf: test_output_data(n),
+            branch: Some(tree.calc_merkle_branch(n as usize).unwrap()),
+        })
+        .collect();
+    protocol
+        .submit_proof(&player, &benchmark_id, merkle_proofs)
+        .await
+        .unwrap()
+        .unwrap();
+    while recorded.len() < 15 {
+        add_block(&protocol, &mut recorded).await;
+    }
+    (parent_state, recorded)
+}
+
+#[tokio::test]
+async fn test_replay_matches_recording() {
+    let (parent_state, recorded) = record_blocks().await;
+    let last = recorded.last().unwrap();
+    assert!(*last.players[PLAYER_ID].reward() > PreciseNumber::from(0));
+
+    let diffs = replay_blocks(&parent_state, &recorded).await.unwrap();
+    assert_eq!(diffs.len(), 15);
+    for (diff, recorded) in diffs.iter().zip(recorded.iter()) {
+        assert_eq!(diff.height, recorded.block.details.height);
+        assert_eq!(diff.mismatches, Vec::new());
+    }
+}
+
+#[tokio::test]
+async fn test_replay_reports_mismatches() {
+    let (parent_state, mut recorded) = record_blocks().await;
+    let tampered = recorded.last_mut().unwrap();
+    let data = tampered.players.get_mut(PLAYER_ID).unwrap();
+    let actual_reward = data.reward.unwrap();
+    data.reward = Some(actual_reward + PreciseNumber::from(1));
+    tampered.challenges.get_mut("c001").unwrap().num_qualifiers = Some(0);
+
+    let diffs = replay_blocks(&parent_state, &recorded).await.unwrap();
+    assert!(diffs[..14].iter().all(|d| d.mismatches.is_empty()));
+    let mismatches = &diffs[14].mismatches;
+    assert_eq!(mismatches.len(), 2);
+    assert_eq!(
+        mismatches[0],
+        Mismatch {
+            entity: "challenge".to_string(),
+            id: "c001".to_string(),
+            field: "num_qualifiers".to_string(),
+            expected: 0.into(),
+            actual: 5.into(),
+        }
+    );
+    assert_eq!(mismatches[1].entity, "player");
+    assert_eq!(mismatches[1].field, "reward");
+    assert_eq!(
+        mismatches[1].actual,
+        serde_json::to_value(actual_reward).unwrap()
+    );
+}
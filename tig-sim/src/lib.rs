@@ -0,0 +1,4 @@
+mod scenario;
+mod simulator;
+pub use scenario::*;
+pub use simulator::*;
@@ -0,0 +1,61 @@
+use anyhow::{anyhow, Result};
+use clap::{arg, Command};
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+use tig_sim::{Scenario, Simulator};
+use tig_utils::dejsonify;
+use tokio::runtime::Runtime;
+
+fn cli() -> Command {
+    Command::new("tig-sim")
+        .about("Simulates the protocol over many blocks using a scenario file")
+        .arg(
+            arg!(<SCENARIO> "Path to a scenario json file")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--output [OUTPUT] "Optional path to write block data to. Defaults to stdout")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+}
+
+fn main() {
+    let matches = cli().get_matches();
+
+    if let Err(e) = simulate(
+        matches.get_one::<PathBuf>("SCENARIO").unwrap().clone(),
+        matches.get_one::<PathBuf>("output").cloned(),
+    ) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn simulate(scenario_path: PathBuf, output_path: Option<PathBuf>) -> Result<()> {
+    let scenario = fs::read_to_string(&scenario_path).map_err(|e| {
+        anyhow!(
+            "Failed to read scenario file {}: {}",
+            scenario_path.display(),
+            e
+        )
+    })?;
+    let scenario =
+        dejsonify::<Scenario>(&scenario).map_err(|e| anyhow!("Failed to parse scenario: {}", e))?;
+    let mut output: Box<dyn Write> = match output_path {
+        Some(path) => Box::new(fs::File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let runtime = Runtime::new()?;
+    runtime.block_on(async {
+        let mut simulator = Simulator::new(scenario).await?;
+        while let Some(report) = simulator.next_block().await {
+            writeln!(output, "{}", report.to_json_line())?;
+        }
+        output.flush()?;
+        Ok(())
+    })
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use tig_structs::config::ProtocolConfig;
+use tig_utils::PreciseNumber;
+
+/// Inputs to a simulation. Heights refer to the latest block at the time of submission, i.e.
+/// anything submitted at height `h` is confirmed in block `h + 1`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub config: ProtocolConfig,
+    /// Number of blocks to add after the genesis block
+    pub num_blocks: u32,
+    /// Seed used when sampling difficulties
+    #[serde(default)]
+    pub seed: u64,
+    /// Challenge names. Ids are assigned in order (`c001`, `c002`, ...)
+    pub challenges: Vec<String>,
+    pub players: Vec<ScenarioPlayer>,
+    #[serde(default)]
+    pub algorithms: Vec<ScenarioAlgorithm>,
+    #[serde(default)]
+    pub benchmarkers: Vec<ScenarioBenchmarker>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScenarioPlayer {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub is_multisig: bool,
+    /// Returned as the player's deposit for every eth block
+    #[serde(default)]
+    pub deposit: Option<PreciseNumber>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScenarioAlgorithm {
+    pub name: String,
+    pub player_id: String,
+    pub challenge_id: String,
+    #[serde(default)]
+    pub submit_height: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScenarioBenchmarker {
+    pub player_id: String,
+    /// Name of an algorithm in `Scenario::algorithms`
+    pub algorithm: String,
+    pub num_nonces: u32,
+    /// Nonces `0..num_solutions` are solutions
+    pub num_solutions: u32,
+    #[serde(default = "default_precommits_per_block")]
+    pub precommits_per_block: u32,
+    /// Sampled from the challenge's base frontier when not set. Requires `precommits_per_block`
+    /// of 1 when set, as every precommit in a block would have the same settings
+    #[serde(default)]
+    pub difficulty: Option<Vec<i32>>,
+    #[serde(default)]
+    pub start_height: u32,
+    #[serde(default)]
+    pub end_height: Option<u32>,
+}
+
+fn default_precommits_per_block() -> u32 {
+    1
+}
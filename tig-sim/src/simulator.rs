@@ -0,0 +1,543 @@
+use crate::scenario::*;
+use anyhow::{anyhow, Result};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+use tig_protocol::{context::*, InMemoryContext, Protocol};
+use tig_structs::core::*;
+use tig_utils::*;
+
+/// Data computed for a block, plus any submissions the protocol rejected while building it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BlockReport {
+    pub height: u32,
+    pub round: u32,
+    pub block_id: String,
+    pub challenges: HashMap<String, ChallengeBlockData>,
+    pub players: HashMap<String, PlayerBlockData>,
+    pub algorithms: HashMap<String, AlgorithmBlockData>,
+    pub rejections: Vec<Rejection>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Rejection {
+    pub player_id: String,
+    pub submission: String,
+    pub error: String,
+}
+
+impl BlockReport {
+    /// Single line of json with sorted keys and sorted sets, so that identical runs produce
+    /// identical output
+    pub fn to_json_line(&self) -> String {
+        jsonify_internal_with_sets(&serde_json::to_value(self).unwrap(), SET_FIELDS).to_string()
+    }
+}
+
+enum Stage {
+    Precommitted,
+    Benchmarked(MerkleTree),
+}
+
+struct PendingBenchmark {
+    benchmark_id: String,
+    settings: BenchmarkSettings,
+    num_nonces: u32,
+    num_solutions: u32,
+    stage: Stage,
+}
+
+/// Drives `Protocol::add_block` using the submissions described by a `Scenario`.
+///
+/// Benchmarkers go through the full precommit -> benchmark -> proof flow with synthetic
+/// solution data. Fee balances are topped up automatically whenever they drop below one topup
+pub struct Simulator {
+    scenario: Scenario,
+    protocol: Protocol<InMemoryContext>,
+    rng: StdRng,
+    algorithm_ids: HashMap<String, String>,
+    num_topups: HashMap<String, u32>,
+    pending: Vec<PendingBenchmark>,
+    /// Number of solutions of each precommitted benchmark, keyed by its jsonified settings
+    num_solutions: Arc<RwLock<HashMap<String, u32>>>,
+}
+
+/// Solution data for a nonce. Used both when benchmarking and when the protocol recomputes a
+/// sampled nonce during `verify_proof`. Only nonces `0..num_solutions` have a non-empty solution
+pub fn sim_output_data(settings: &BenchmarkSettings, nonce: u64, num_solutions: u32) -> OutputData {
+    let mut solution = Map::new();
+    if nonce < num_solutions as u64 {
+        solution.insert("nonce".to_string(), Value::from(nonce));
+    }
+    OutputData {
+        nonce,
+        runtime_signature: u64s_from_str(&format!("{}|{}", jsonify(settings), nonce))[0],
+        fuel_consumed: 0,
+        solution,
+    }
+}
+
+impl Simulator {
+    pub async fn new(scenario: Scenario) -> Result<Self> {
+        verify_scenario(&scenario)?;
+        let config = scenario.config.clone();
+        let burn_address = config.erc20.burn_address.clone();
+        let submission_fee = config.algorithm_submissions.submission_fee;
+        let topup_amount = config.precommit_submissions().topup_amount;
+        let deposits: HashMap<String, PreciseNumber> = scenario
+            .players
+            .iter()
+            .filter_map(|p| p.deposit.map(|d| (p.id.clone(), d)))
+            .collect();
+
+        let num_solutions = Arc::new(RwLock::new(HashMap::new()));
+        let ctx = InMemoryContext::new(config)
+            .with_get_transaction(move |tx_hash| {
+                // tx hashes are generated by the simulator: "<kind>|<player_id>|<suffix>"
+                let parts: Vec<&str> = tx_hash.split('|').collect();
+                let amount = match parts.first() {
+                    Some(&"sim_algorithm") => submission_fee,
+                    Some(&"sim_topup") => topup_amount,
+                    _ => return Err(anyhow!("Transaction '{}' not found", tx_hash)),
+                };
+                Ok(Transaction {
+                    sender: parts.get(1).unwrap_or(&"").to_string(),
+                    receiver: burn_address.clone(),
+                    amount,
+                })
+            })
+            .with_get_player_deposit(move |_, player_id| Ok(deposits.get(player_id).cloned()))
+            .with_compute_solution({
+                let num_solutions = num_solutions.clone();
+                move |settings, _, nonce, _| {
+                    let num_solutions = num_solutions.read().unwrap()[&jsonify(settings)];
+                    Ok(Ok(sim_output_data(settings, nonce, num_solutions)))
+                }
+            })
+            .with_verify_solution(|_, _, _, solution| {
+                if solution.is_empty() {
+                    Ok(Err(anyhow!("Invalid solution")))
+                } else {
+                    Ok(Ok(()))
+                }
+            });
+        for name in scenario.challenges.iter() {
+            ctx.add_challenge_to_mempool(ChallengeDetails { name: name.clone() })
+                .await?;
+        }
+        for player in scenario.players.iter() {
+            ctx.add_player(
+                &player.id,
+                PlayerDetails {
+                    name: player.name.clone().unwrap_or_else(|| player.id.clone()),
+                    is_multisig: player.is_multisig,
+                },
+            );
+        }
+
+        Ok(Self {
+            rng: StdRng::seed_from_u64(scenario.seed),
+            scenario,
+            protocol: Protocol::new(ctx),
+            algorithm_ids: HashMap::new(),
+            num_topups: HashMap::new(),
+            pending: Vec::new(),
+            num_solutions,
+        })
+    }
+
+    pub fn ctx(&self) -> &InMemoryContext {
+        &self.protocol.ctx
+    }
+
+    /// Adds the next block. Returns `None` once `Scenario::num_blocks` have been added
+    pub async fn next_block(&mut self) -> Option<BlockReport> {
+        let latest_block = self.get_block(BlockFilter::Latest).await;
+        let height = latest_block.details.height;
+        if height >= self.scenario.num_blocks {
+            return None;
+        }
+        let mut rejections = Vec::new();
+        self.submit_algorithms(height, &mut rejections).await;
+        self.submit_topups(&mut rejections).await;
+        self.advance_benchmarks(&mut rejections).await;
+        self.submit_precommits(&latest_block, &mut rejections).await;
+
+        let block_id = self.protocol.add_block().await;
+        Some(self.report(block_id, rejections).await)
+    }
+
+    pub async fn run(&mut self) -> Vec<BlockReport> {
+        let mut reports = Vec::new();
+        while let Some(report) = self.next_block().await {
+            reports.push(report);
+        }
+        reports
+    }
+
+    async fn get_block(&self, filter: BlockFilter) -> Block {
+        self.ctx()
+            .get_block(filter, true)
+            .await
+            .unwrap_or_else(|e| panic!("get_block error: {:?}", e))
+            .expect("Expecting block to exist")
+    }
+
+    async fn get_player(&self, player_id: &str) -> Player {
+        self.ctx()
+            .get_players(PlayersFilter::Id(player_id.to_string()), None)
+            .await
+            .unwrap_or_else(|e| panic!("get_players error: {:?}", e))
+            .pop()
+            .expect("Expecting scenario player to exist")
+    }
+
+    async fn submit_algorithms(&mut self, height: u32, rejections: &mut Vec<Rejection>) {
+        let algorithms: Vec<ScenarioAlgorithm> = self
+            .scenario
+            .algorithms
+            .iter()
+            .filter(|a| a.submit_height == height)
+            .cloned()
+            .collect();
+        for algorithm in algorithms {
+            let player = self.get_player(&algorithm.player_id).await;
+            let details = AlgorithmDetails {
+                name: algorithm.name.clone(),
+                player_id: algorithm.player_id.clone(),
+                challenge_id: algorithm.challenge_id.clone(),
+                tx_hash: format!("sim_algorithm|{}|{}", algorithm.player_id, algorithm.name),
+            };
+            match self
+                .protocol
+                .submit_algorithm(&player, details, String::new())
+                .await
+            {
+                Ok(algorithm_id) => {
+                    self.ctx()
+                        .add_wasm_to_mempool(
+                            &algorithm_id,
+                            WasmDetails {
+                                compile_success: true,
+                                download_url: None,
+                            },
+                        )
+                        .await
+                        .unwrap_or_else(|e| panic!("add_wasm_to_mempool error: {:?}", e));
+                    self.algorithm_ids.insert(algorithm.name, algorithm_id);
+                }
+                Err(e) => rejections.push(Rejection {
+                    player_id: algorithm.player_id,
+                    submission: "algorithm".to_string(),
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    async fn submit_topups(&mut self, rejections: &mut Vec<Rejection>) {
+        let topup_amount = self.scenario.config.precommit_submissions().topup_amount;
+        let mut player_ids: Vec<String> = Vec::new();
+        for b in self.scenario.benchmarkers.iter() {
+            if !player_ids.contains(&b.player_id) {
+                player_ids.push(b.player_id.clone());
+            }
+        }
+        for player_id in player_ids {
+            if self
+                .ctx()
+                .get_topups(TopUpsFilter::PlayerId(player_id.clone()))
+                .await
+                .unwrap_or_else(|e| panic!("get_topups error: {:?}", e))
+                .iter()
+                .any(|t| t.state.is_none())
+            {
+                continue;
+            }
+            let player = self.get_player(&player_id).await;
+            let balance = player
+                .state
+                .as_ref()
+                .and_then(|s| s.available_fee_balance)
+                .unwrap_or(PreciseNumber::from(0));
+            if balance >= topup_amount {
+                continue;
+            }
+            let num_topups = self.num_topups.entry(player_id.clone()).or_default();
+            *num_topups += 1;
+            let tx_hash = format!("sim_topup|{}|{}", player_id, num_topups);
+            if let Err(e) = self.protocol.submit_topup(&player, tx_hash).await {
+                rejections.push(Rejection {
+                    player_id,
+                    submission: "topup".to_string(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    async fn advance_benchmarks(&mut self, rejections: &mut Vec<Rejection>) {
+        for mut pending in std::mem::take(&mut self.pending) {
+            let player = self.get_player(&pending.settings.player_id).await;
+            match &pending.stage {
+                Stage::Precommitted => {
+                    if self
+                        .ctx()
+                        .get_precommits(PrecommitsFilter::BenchmarkId(pending.benchmark_id.clone()))
+                        .await
+                        .unwrap_or_else(|e| panic!("get_precommits error: {:?}", e))
+                        .pop()
+                        .filter(|p| p.state.is_some())
+                        .is_none()
+                    {
+                        self.pending.push(pending);
+                        continue;
+                    }
+                    let hashes = (0..pending.num_nonces as u64)
+                        .map(|n| {
+                            MerkleHash::from(sim_output_data(
+                                &pending.settings,
+                                n,
+                                pending.num_solutions,
+                            ))
+                        })
+                        .collect();
+                    let batch_size = pending.num_nonces.next_power_of_two() as usize;
+                    let tree = MerkleTree::new(hashes, batch_size).unwrap();
+                    let solution_nonces: HashSet<u64> = (0..pending.num_solutions as u64).collect();
+                    match self
+                        .protocol
+                        .submit_benchmark(
+                            &player,
+                            &pending.benchmark_id,
+                            tree.calc_merkle_root(),
+                            solution_nonces,
+                        )
+                        .await
+                    {
+                        Ok(()) => {
+                            pending.stage = Stage::Benchmarked(tree);
+                            self.pending.push(pending);
+                        }
+                        Err(e) => {
+                            rejections.push(reject(&pending.settings, "benchmark", e.to_string()))
+                        }
+                    }
+                }
+                Stage::Benchmarked(tree) => {
+                    let benchmark = match self
+                        .ctx()
+                        .get_benchmarks(BenchmarksFilter::Id(pending.benchmark_id.clone()), false)
+                        .await
+                        .unwrap_or_else(|e| panic!("get_benchmarks error: {:?}", e))
+                        .pop()
+                        .filter(|b| b.state.is_some())
+                    {
+                        Some(benchmark) => benchmark,
+                        None => {
+                            self.pending.push(pending);
+                            continue;
+                        }
+                    };
+                    let mut sampled_nonces: Vec<u64> =
+                        benchmark.state().sampled_nonces().iter().cloned().collect();
+                    sampled_nonces.sort();
+                    let merkle_proofs = sampled_nonces
+                        .into_iter()
+                        .map(|n| MerkleProof {
+                            leaf: sim_output_data(&pending.settings, n, pending.num_solutions),
+                            branch: Some(tree.calc_merkle_branch(n as usize).unwrap()),
+                        })
+                        .collect();
+                    let result = match self
+                        .protocol
+                        .submit_proof(&player, &pending.benchmark_id, merkle_proofs)
+                        .await
+                    {
+                        Ok(Ok(())) => self.protocol.verify_proof(&pending.benchmark_id).await,
+                        result => result,
+                    };
+                    match result {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => rejections.push(reject(&pending.settings, "fraud", e)),
+                        Err(e) => {
+                            rejections.push(reject(&pending.settings, "proof", e.to_string()))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    async fn submit_precommits(&mut self, latest_block: &Block, rejections: &mut Vec<Rejection>) {
+        let height = latest_block.details.height;
+        let benchmarkers: Vec<ScenarioBenchmarker> = self
+            .scenario
+            .benchmarkers
+            .iter()
+            .filter(|b| height >= b.start_height && b.end_height.is_none_or(|h| height <= h))
+            .cloned()
+            .collect();
+        for benchmarker in benchmarkers {
+            let algorithm_id = match self.algorithm_ids.get(&benchmarker.algorithm) {
+                Some(id) if latest_block.data().active_algorithm_ids.contains(id) => id.clone(),
+                _ => continue,
+            };
+            let challenge_id = self
+                .scenario
+                .algorithms
+                .iter()
+                .find(|a| a.name == benchmarker.algorithm)
+                .unwrap()
+                .challenge_id
+                .clone();
+            let player = self.get_player(&benchmarker.player_id).await;
+            for _ in 0..benchmarker.precommits_per_block {
+                let difficulty = match &benchmarker.difficulty {
+                    Some(difficulty) => difficulty.clone(),
+                    None => match self
+                        .ctx()
+                        .get_challenges(
+                            ChallengesFilter::Id(challenge_id.clone()),
+                            Some(BlockFilter::Id(latest_block.id.clone())),
+                        )
+                        .await
+                        .unwrap_or_else(|e| panic!("get_challenges error: {:?}", e))
+                        .pop()
+                        .and_then(|c| c.block_data)
+                    {
+                        Some(block_data) => block_data.base_frontier().sample(&mut self.rng),
+                        None => continue,
+                    },
+                };
+                let settings = BenchmarkSettings {
+                    player_id: benchmarker.player_id.clone(),
+                    block_id: latest_block.id.clone(),
+                    challenge_id: challenge_id.clone(),
+                    algorithm_id: algorithm_id.clone(),
+                    difficulty,
+                };
+                match self
+                    .protocol
+                    .submit_precommit(&player, settings.clone(), benchmarker.num_nonces)
+                    .await
+                {
+                    Ok(benchmark_id) => {
+                        self.num_solutions
+                            .write()
+                            .unwrap()
+                            .insert(jsonify(&settings), benchmarker.num_solutions);
+                        self.pending.push(PendingBenchmark {
+                            benchmark_id,
+                            settings,
+                            num_nonces: benchmarker.num_nonces,
+                            num_solutions: benchmarker.num_solutions,
+                            stage: Stage::Precommitted,
+                        })
+                    }
+                    Err(e) => rejections.push(Rejection {
+                        player_id: benchmarker.player_id.clone(),
+                        submission: "precommit".to_string(),
+                        error: e.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+
+    async fn report(&self, block_id: String, rejections: Vec<Rejection>) -> BlockReport {
+        let block = self.get_block(BlockFilter::Id(block_id.clone())).await;
+        let block_filter = Some(BlockFilter::Id(block_id.clone()));
+        let challenges = self
+            .ctx()
+            .get_challenges(ChallengesFilter::Confirmed, block_filter.clone())
+            .await
+            .unwrap_or_else(|e| panic!("get_challenges error: {:?}", e))
+            .into_iter()
+            .filter_map(|c| c.block_data.map(|d| (c.id, d)))
+            .collect();
+        let algorithms = self
+            .ctx()
+            .get_algorithms(AlgorithmsFilter::Confirmed, block_filter.clone(), false)
+            .await
+            .unwrap_or_else(|e| panic!("get_algorithms error: {:?}", e))
+            .into_iter()
+            .filter_map(|a| a.block_data.map(|d| (a.id, d)))
+            .collect();
+        let mut players = HashMap::new();
+        for player in self.scenario.players.iter() {
+            if let Some(block_data) = self
+                .ctx()
+                .get_players(PlayersFilter::Id(player.id.clone()), block_filter.clone())
+                .await
+                .unwrap_or_else(|e| panic!("get_players error: {:?}", e))
+                .pop()
+                .and_then(|p| p.block_data)
+            {
+                players.insert(player.id.clone(), block_data);
+            }
+        }
+        BlockReport {
+            height: block.details.height,
+            round: block.details.round,
+            block_id,
+            challenges,
+            players,
+            algorithms,
+            rejections,
+        }
+    }
+}
+
+fn reject(settings: &BenchmarkSettings, submission: &str, error: String) -> Rejection {
+    Rejection {
+        player_id: settings.player_id.clone(),
+        submission: submission.to_string(),
+        error,
+    }
+}
+
+fn verify_scenario(scenario: &Scenario) -> Result<()> {
+    if scenario.config.challenges.is_none() {
+        return Err(anyhow!(
+            "config.challenges must be set for challenges to become active"
+        ));
+    }
+    let player_ids: HashSet<&String> = scenario.players.iter().map(|p| &p.id).collect();
+    let mut algorithm_names = HashSet::new();
+    for algorithm in scenario.algorithms.iter() {
+        if !algorithm_names.insert(&algorithm.name) {
+            return Err(anyhow!("Duplicate algorithm name '{}'", algorithm.name));
+        }
+        if !player_ids.contains(&algorithm.player_id) {
+            return Err(anyhow!(
+                "Algorithm '{}' submitted by unknown player '{}'",
+                algorithm.name,
+                algorithm.player_id
+            ));
+        }
+    }
+    for benchmarker in scenario.benchmarkers.iter() {
+        if !player_ids.contains(&benchmarker.player_id) {
+            return Err(anyhow!("Unknown benchmarker '{}'", benchmarker.player_id));
+        }
+        if !algorithm_names.contains(&benchmarker.algorithm) {
+            return Err(anyhow!(
+                "Benchmarker '{}' uses unknown algorithm '{}'",
+                benchmarker.player_id,
+                benchmarker.algorithm
+            ));
+        }
+        if benchmarker.difficulty.is_some() && benchmarker.precommits_per_block > 1 {
+            return Err(anyhow!(
+                "Benchmarker '{}' would submit duplicate precommits with a fixed difficulty",
+                benchmarker.player_id
+            ));
+        }
+    }
+    Ok(())
+}
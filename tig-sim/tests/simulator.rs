@@ -0,0 +1,63 @@
+use tig_sim::*;
+use tig_utils::{dejsonify, PreciseNumber};
+
+fn example_scenario() -> Scenario {
+    dejsonify(include_str!("../scenarios/example.json")).unwrap()
+}
+
+async fn run(scenario: Scenario) -> Vec<BlockReport> {
+    Simulator::new(scenario).await.unwrap().run().await
+}
+
+#[tokio::test]
+async fn test_simulation_is_deterministic() {
+    let mut scenario = example_scenario();
+    scenario.num_blocks = 25;
+    let a = run(scenario.clone()).await;
+    let b = run(scenario).await;
+    assert_eq!(a.len(), 25);
+    assert_eq!(
+        a.iter().map(|r| r.to_json_line()).collect::<Vec<_>>(),
+        b.iter().map(|r| r.to_json_line()).collect::<Vec<_>>()
+    );
+    for (i, report) in a.iter().enumerate() {
+        assert_eq!(report.height, i as u32 + 1);
+        assert!(report.rejections.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_rewards_follow_qualifiers() {
+    let reports = run(example_scenario()).await;
+    let last = reports.last().unwrap();
+    let alice = &last.players["0x0000000000000000000000000000000000000001"];
+    let bob = &last.players["0x0000000000000000000000000000000000000002"];
+    assert!(*alice.reward() > *bob.reward());
+    assert!(*bob.reward() > PreciseNumber::from(0));
+    assert_eq!(*last.challenges["c001"].num_qualifiers(), 100);
+    assert!(last.algorithms.contains_key("c002_a001"));
+}
+
+#[tokio::test]
+async fn test_rejections_are_reported() {
+    let mut scenario = example_scenario();
+    scenario.num_blocks = 15;
+    scenario.benchmarkers[0].num_solutions = 32;
+    let reports = run(scenario).await;
+    assert!(reports
+        .iter()
+        .flat_map(|r| r.rejections.iter())
+        .any(|r| r.submission == "benchmark"));
+}
+
+#[tokio::test]
+async fn test_invalid_scenario() {
+    let mut scenario = example_scenario();
+    scenario.benchmarkers[0].algorithm = "unknown".to_string();
+    assert!(Simulator::new(scenario).await.is_err());
+
+    let mut scenario = example_scenario();
+    scenario.benchmarkers[0].difficulty = Some(vec![50, 300]);
+    scenario.benchmarkers[0].precommits_per_block = 2;
+    assert!(Simulator::new(scenario).await.is_err());
+}
@@ -17,6 +17,8 @@ serializable_struct_with_getters! {
         rounds: RoundsConfig,
         algorithm_submissions: AlgorithmSubmissionsConfig,
         rewards: RewardsConfig,
+        fraud: Option<FraudConfig>,
+        challenges: Option<ChallengesConfig>,
     }
 }
 serializable_struct_with_getters! {
@@ -32,6 +34,8 @@ serializable_struct_with_getters! {
         min_num_solutions: u32,
         submission_delay_multiplier: f64,
         max_samples: usize,
+        /// Number of non-solution nonces to sample. Defaults to `max_samples`
+        max_non_solution_samples: Option<usize>,
         lifespan_period: u32,
     }
 }
@@ -131,3 +135,18 @@ serializable_struct_with_getters! {
         round_start: u32,
     }
 }
+serializable_struct_with_getters! {
+    ChallengesConfig {
+        /// Rounds after confirmation before a challenge becomes active
+        activation_delay: u32,
+    }
+}
+serializable_struct_with_getters! {
+    FraudConfig {
+        appeal_period: u32,
+        slash_period: u32,
+        slash_percent: f64,
+        /// Share of a slashed player's penalty paid to challengers of their frauds
+        challenge_bounty_percent: f64,
+    }
+}
@@ -2,9 +2,30 @@ use crate::{config::ProtocolConfig, serializable_struct_with_getters};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
-use tig_utils::{jsonify, u64s_from_str, u8s_from_str};
+use tig_utils::{jsonify, jsonify_internal_with_sets, u64s_from_str, u8s_from_str, MerkleTree};
 pub use tig_utils::{Frontier, MerkleBranch, MerkleHash, Point, PreciseNumber, Transaction, U256};
 
+/// Fields serialized from a `HashSet`, for use with `tig_utils::jsonify_internal_with_sets`
+pub const SET_FIELDS: &[&str] = &[
+    "solution_nonces",
+    "sampled_nonces",
+    "confirmed_challenge_ids",
+    "confirmed_algorithm_ids",
+    "confirmed_benchmark_ids",
+    "confirmed_precommit_ids",
+    "confirmed_proof_ids",
+    "confirmed_fraud_ids",
+    "confirmed_topup_ids",
+    "confirmed_wasm_ids",
+    "active_challenge_ids",
+    "active_algorithm_ids",
+    "active_benchmark_ids",
+    "active_player_ids",
+    "qualifier_difficulties",
+    "base_frontier",
+    "scaled_frontier",
+];
+
 serializable_struct_with_getters! {
     Algorithm {
         id: String,
@@ -72,6 +93,11 @@ serializable_struct_with_getters! {
         benchmark_id: String,
         state: Option<FraudState>,
         allegation: Option<String>,
+        appeal: Option<FraudAppeal>,
+        /// Player who raised the fraud. None if raised by the protocol
+        challenger_id: Option<String>,
+        /// Evidence the challenger submitted. Re-checked if the fraud is appealed
+        evidence: Option<FraudEvidence>,
     }
 }
 serializable_struct_with_getters! {
@@ -193,6 +219,7 @@ serializable_struct_with_getters! {
         num_active_algorithms: Option<u32>,
         num_active_benchmarks: Option<u32>,
         num_active_players: Option<u32>,
+        state_root: Option<MerkleHash>,
     }
 }
 serializable_struct_with_getters! {
@@ -248,6 +275,8 @@ serializable_struct_with_getters! {
     PlayerState {
         total_fees_paid: Option<PreciseNumber>,
         available_fee_balance: Option<PreciseNumber>,
+        /// Sum of the fraud bounties credited to `available_fee_balance`
+        total_fraud_bounties: Option<PreciseNumber>,
     }
 }
 serializable_struct_with_getters! {
@@ -262,6 +291,7 @@ serializable_struct_with_getters! {
         influence: Option<PreciseNumber>,
         reward: Option<PreciseNumber>,
         round_earnings: Option<PreciseNumber>,
+        fraud_penalty: Option<PreciseNumber>,
     }
 }
 
@@ -303,9 +333,37 @@ impl OutputData {
 }
 
 // Fraud child structs
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FraudStatus {
+    /// Confirmed and can be appealed until the appeal period ends
+    Alleged,
+    /// Appealed by the benchmarker. Resolved when the appeal period ends
+    Disputed,
+    Upheld,
+    Overturned,
+}
 serializable_struct_with_getters! {
     FraudState {
         block_confirmed: Option<u32>,
+        status: Option<FraudStatus>,
+        block_resolved: Option<u32>,
+    }
+}
+serializable_struct_with_getters! {
+    FraudAppeal {
+        /// Error from re-running verification with the second verifier. None if it passed
+        verification_error: Option<String>,
+    }
+}
+serializable_struct_with_getters! {
+    FraudEvidence {
+        /// Leaf the benchmarker committed to for the challenged nonce
+        leaf: OutputMetaData,
+        /// Branch proving inclusion of `leaf` in the benchmark's merkle root
+        branch: MerkleBranch,
+        /// Output data recomputed by the challenger
+        output_data: OutputData,
     }
 }
 
@@ -334,3 +392,100 @@ serializable_struct_with_getters! {
         block_confirmed: Option<u32>,
     }
 }
+
+// State root structs
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum StateLeafType {
+    Challenge,
+    Algorithm,
+    Player,
+}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateLeaf {
+    Challenge {
+        id: String,
+        block_data: ChallengeBlockData,
+    },
+    Algorithm {
+        id: String,
+        block_data: AlgorithmBlockData,
+    },
+    Player {
+        id: String,
+        block_data: PlayerBlockData,
+    },
+}
+impl StateLeaf {
+    pub fn leaf_type(&self) -> StateLeafType {
+        match self {
+            StateLeaf::Challenge { .. } => StateLeafType::Challenge,
+            StateLeaf::Algorithm { .. } => StateLeafType::Algorithm,
+            StateLeaf::Player { .. } => StateLeafType::Player,
+        }
+    }
+    pub fn id(&self) -> &String {
+        match self {
+            StateLeaf::Challenge { id, .. }
+            | StateLeaf::Algorithm { id, .. }
+            | StateLeaf::Player { id, .. } => id,
+        }
+    }
+}
+impl From<&StateLeaf> for MerkleHash {
+    // sets are hashed in the same canonical order as everywhere else, see `SET_FIELDS`
+    fn from(leaf: &StateLeaf) -> Self {
+        let value = serde_json::to_value(leaf).unwrap();
+        MerkleHash(u8s_from_str(
+            &jsonify_internal_with_sets(&value, SET_FIELDS).to_string(),
+        ))
+    }
+}
+/// Merkle tree over the block data of every active challenge, algorithm and player in a block.
+/// Leafs are ordered by type (challenges, algorithms, players) then id
+pub struct StateTree {
+    pub leafs: Vec<StateLeaf>,
+    tree: MerkleTree,
+}
+impl StateTree {
+    pub fn new(mut leafs: Vec<StateLeaf>) -> Self {
+        leafs.sort_by(|a, b| (a.leaf_type(), a.id()).cmp(&(b.leaf_type(), b.id())));
+        let hashes: Vec<MerkleHash> = leafs.iter().map(MerkleHash::from).collect();
+        let tree = MerkleTree::new(hashes, leafs.len().next_power_of_two()).unwrap();
+        Self { leafs, tree }
+    }
+    /// Null hash if there are no leafs
+    pub fn calc_state_root(&self) -> MerkleHash {
+        if self.leafs.is_empty() {
+            MerkleHash::null()
+        } else {
+            self.tree.calc_merkle_root()
+        }
+    }
+    pub fn calc_state_proof(&self, leaf_type: StateLeafType, id: &String) -> Option<StateProof> {
+        let leaf_idx = self
+            .leafs
+            .iter()
+            .position(|l| l.leaf_type() == leaf_type && l.id() == id)?;
+        Some(StateProof {
+            leaf_idx: leaf_idx as u32,
+            leaf: self.leafs[leaf_idx].clone(),
+            branch: self.tree.calc_merkle_branch(leaf_idx).unwrap(),
+        })
+    }
+}
+serializable_struct_with_getters! {
+    StateProof {
+        leaf_idx: u32,
+        leaf: StateLeaf,
+        branch: MerkleBranch,
+    }
+}
+impl StateProof {
+    pub fn verify(&self, state_root: &MerkleHash) -> bool {
+        self.branch
+            .calc_merkle_root(&MerkleHash::from(&self.leaf), self.leaf_idx as usize)
+            .is_ok_and(|root| root == *state_root)
+    }
+}
@@ -1,5 +1,6 @@
 use serde_json::json;
-use tig_structs::core::{BenchmarkSettings, OutputData};
+use std::collections::HashSet;
+use tig_structs::core::{BenchmarkSettings, OutputData, StateLeaf, StateLeafType, StateTree};
 use tig_utils::MerkleHash;
 
 #[test]
@@ -74,3 +75,55 @@ fn test_outputdata_to_merklehash() {
         ])
     );
 }
+
+#[test]
+fn test_state_tree() {
+    let challenge_leaf = |points: Vec<Vec<i32>>| StateLeaf::Challenge {
+        id: "c001".to_string(),
+        block_data: serde_json::from_value(json!({
+            "num_qualifiers": 3,
+            "qualifier_difficulties": points.into_iter().collect::<HashSet<_>>(),
+        }))
+        .unwrap(),
+    };
+    let points: Vec<Vec<i32>> = (0..20).map(|i| vec![i, 20 - i]).collect();
+    let mut reversed = points.clone();
+    reversed.reverse();
+    // leaf hash does not depend on set order
+    assert_eq!(
+        MerkleHash::from(&challenge_leaf(points.clone())),
+        MerkleHash::from(&challenge_leaf(reversed))
+    );
+
+    let leafs = vec![
+        StateLeaf::Player {
+            id: "0x01".to_string(),
+            block_data: serde_json::from_value(json!({})).unwrap(),
+        },
+        challenge_leaf(points),
+        StateLeaf::Algorithm {
+            id: "c001_a001".to_string(),
+            block_data: serde_json::from_value(json!({})).unwrap(),
+        },
+    ];
+    let tree = StateTree::new(leafs.clone());
+    let state_root = tree.calc_state_root();
+    let mut rev_leafs = leafs;
+    rev_leafs.reverse();
+    assert_eq!(StateTree::new(rev_leafs).calc_state_root(), state_root);
+
+    let proof = tree
+        .calc_state_proof(StateLeafType::Algorithm, &"c001_a001".to_string())
+        .unwrap();
+    assert_eq!(proof.leaf_idx, 1);
+    assert!(proof.verify(&state_root));
+    assert!(!proof.verify(&MerkleHash::null()));
+    assert!(tree
+        .calc_state_proof(StateLeafType::Player, &"c001_a001".to_string())
+        .is_none());
+
+    assert_eq!(
+        StateTree::new(Vec::new()).calc_state_root(),
+        MerkleHash::null()
+    );
+}
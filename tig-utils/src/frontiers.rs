@@ -1,6 +1,6 @@
 use rand::Rng;
 use std::cmp::min;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 pub type Point = Vec<i32>;
 pub type Frontier<P = Point> = HashSet<P>;
@@ -173,47 +173,145 @@ impl FrontierOps for Frontier {
         }
     }
     fn sample<R: Rng>(&self, rng: &mut R) -> Self::Point {
-        // FIXME only works for 2 dimensional points
-        // Potential strategy for >2d: triangulate -> sample triangle -> sample point in triangle
-        match self.iter().next() {
+        let num_dims = match self.iter().next() {
             None => panic!("Frontier is empty"),
-            Some(point) => {
-                if point.len() != 2 {
-                    panic!("Only 2 dimensional points are supported");
-                }
-            }
+            Some(point) => point.len(),
         };
-        // randomly pick a dimension
-        let dim = (rng.next_u32() % 2) as usize;
-        let dim2 = (dim + 1) % 2;
-
-        // sort points by that dimension
-        let mut sorted_points: Vec<&Point> = self.iter().collect();
-        sorted_points.sort_by(|a, b| a[dim].cmp(&b[dim]));
-
-        // sample value in that dimension
-        let min_v = sorted_points.first().unwrap()[dim];
-        let max_v = sorted_points.last().unwrap()[dim];
-        let rand_v = rng.gen_range(min_v..=max_v);
-
-        // interpolate value in the other dimension
-        match sorted_points.binary_search_by(|point| point[dim].cmp(&rand_v)) {
-            Ok(idx) => sorted_points[idx].clone(),
-            Err(idx) => {
-                let a = sorted_points[idx - 1];
-                let b = sorted_points[idx];
-                let ratio = (rand_v - a[dim]) as f64 / (b[dim] - a[dim]) as f64;
-                let rand_v2 = (a[dim2] as f64 + ratio * (b[dim2] - a[dim2]) as f64).ceil() as i32;
-                // a is smaller than b in dim, but larger in dim2
-                if rand_v2 == a[dim2] {
-                    a.clone()
-                } else {
-                    (0..2)
-                        .into_iter()
-                        .map(|i| if i == dim { rand_v } else { rand_v2 })
-                        .collect()
-                }
+        match num_dims {
+            0 | 1 => self.iter().max().unwrap().clone(),
+            2 => sample_2d(self, rng),
+            _ => sample_nd(self, rng),
+        }
+    }
+}
+
+fn sample_2d<R: Rng>(frontier: &Frontier, rng: &mut R) -> Point {
+    // randomly pick a dimension
+    let dim = (rng.next_u32() % 2) as usize;
+    let dim2 = (dim + 1) % 2;
+
+    // sort points by that dimension
+    let mut sorted_points: Vec<&Point> = frontier.iter().collect();
+    sorted_points.sort_by(|a, b| a[dim].cmp(&b[dim]));
+
+    // sample value in that dimension
+    let min_v = sorted_points.first().unwrap()[dim];
+    let max_v = sorted_points.last().unwrap()[dim];
+    let rand_v = rng.gen_range(min_v..=max_v);
+
+    // interpolate value in the other dimension
+    match sorted_points.binary_search_by(|point| point[dim].cmp(&rand_v)) {
+        Ok(idx) => sorted_points[idx].clone(),
+        Err(idx) => {
+            let a = sorted_points[idx - 1];
+            let b = sorted_points[idx];
+            let ratio = (rand_v - a[dim]) as f64 / (b[dim] - a[dim]) as f64;
+            let rand_v2 = (a[dim2] as f64 + ratio * (b[dim2] - a[dim2]) as f64).ceil() as i32;
+            // a is smaller than b in dim, but larger in dim2
+            if rand_v2 == a[dim2] {
+                a.clone()
+            } else {
+                (0..2)
+                    .map(|i| if i == dim { rand_v } else { rand_v2 })
+                    .collect()
             }
         }
     }
 }
+
+const MAX_SAMPLE_ATTEMPTS: usize = 100;
+
+fn sample_nd<R: Rng>(frontier: &Frontier, rng: &mut R) -> Point {
+    // sort points so that sampling is deterministic for a given rng
+    let mut points: Vec<&Point> = frontier.iter().collect();
+    points.sort();
+    let num_dims = points[0].len();
+    let min_point: Point = (0..num_dims)
+        .map(|i| points.iter().map(|p| p[i]).min().unwrap())
+        .collect();
+    let max_point: Point = (0..num_dims)
+        .map(|i| points.iter().map(|p| p[i]).max().unwrap())
+        .collect();
+
+    for _ in 0..MAX_SAMPLE_ATTEMPTS {
+        // sample a point within the bounds of the frontier
+        let dim = rng.gen_range(0..num_dims);
+        let mut point: Point = (0..num_dims)
+            .map(|i| rng.gen_range(min_point[i]..=max_point[i]))
+            .collect();
+        // lift the point along `dim` until no frontier point dominates it
+        point[dim] = points
+            .iter()
+            .filter(|p| (0..num_dims).all(|i| i == dim || p[i] >= point[i]))
+            .map(|p| p[dim] + 1)
+            .max()
+            .unwrap_or(min_point[dim]);
+        // reject points that are beyond the frontier
+        if point[dim] <= max_point[dim]
+            && !points
+                .iter()
+                .any(|p| point.pareto_compare(p) == ParetoCompare::ADominatesB)
+        {
+            return point;
+        }
+    }
+    points[rng.gen_range(0..points.len())].clone()
+}
+
+fn find_smallest_range_dimension(points: &Frontier) -> usize {
+    let num_dims = points.iter().next().map_or(0, |p| p.len());
+    (0..num_dims)
+        .min_by_key(|&d| {
+            let (min, max) = points
+                .iter()
+                .map(|p| p[d])
+                .fold((i32::MAX, i32::MIN), |(min, max), val| {
+                    (min.min(val), max.max(val))
+                });
+            max - min
+        })
+        .unwrap()
+}
+
+/// Splits `points` into successive pareto frontiers, hardest first. Stops after the first
+/// frontier if `only_one` is set
+pub fn pareto_algorithm(points: Frontier, only_one: bool) -> Vec<Frontier> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let dimension = find_smallest_range_dimension(&points);
+
+    // a point on the frontier must be on the frontier of the points sharing its value in
+    // `dimension`, so only those need to be compared across buckets
+    let mut buckets: HashMap<i32, Frontier> = HashMap::new();
+    for point in points {
+        buckets.entry(point[dimension]).or_default().insert(point);
+    }
+    let mut bucket_frontiers: HashMap<i32, Frontier> = buckets
+        .iter()
+        .map(|(&v, bucket)| (v, bucket.pareto_frontier()))
+        .collect();
+    let mut result = Vec::new();
+    while !buckets.is_empty() {
+        let points: Frontier = bucket_frontiers.values().flatten().cloned().collect();
+        let frontier = points.pareto_frontier();
+        let mut updated_buckets = HashSet::new();
+        for point in frontier.iter() {
+            buckets.get_mut(&point[dimension]).unwrap().remove(point);
+            updated_buckets.insert(point[dimension]);
+        }
+        for v in updated_buckets {
+            if buckets[&v].is_empty() {
+                buckets.remove(&v);
+                bucket_frontiers.remove(&v);
+            } else {
+                bucket_frontiers.insert(v, buckets[&v].pareto_frontier());
+            }
+        }
+        result.push(frontier);
+        if only_one {
+            break;
+        }
+    }
+    result
+}
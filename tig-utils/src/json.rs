@@ -40,6 +40,34 @@ pub fn jsonify_internal(json_value: &Value) -> Value {
     }
 }
 
+/// Same as `jsonify_internal`, but also sorts the arrays of `set_fields`. These are serialized
+/// from `HashSet`s in an arbitrary order, so that equal sets produce identical json
+pub fn jsonify_internal_with_sets(json_value: &Value, set_fields: &[&str]) -> Value {
+    match json_value {
+        Value::Object(obj) => {
+            let mut sorted_map = Map::new();
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            for key in keys {
+                let mut value = jsonify_internal_with_sets(&obj[key], set_fields);
+                if let Value::Array(arr) = &mut value {
+                    if set_fields.contains(&key.as_str()) {
+                        arr.sort_by_key(|v| v.to_string());
+                    }
+                }
+                sorted_map.insert(key.clone(), value);
+            }
+            Value::Object(sorted_map)
+        }
+        Value::Array(arr) => Value::Array(
+            arr.iter()
+                .map(|v| jsonify_internal_with_sets(v, set_fields))
+                .collect(),
+        ),
+        _ => json_value.clone(),
+    }
+}
+
 pub fn decompress_obj<T>(input: &[u8]) -> anyhow::Result<T>
 where
     T: DeserializeOwned,
@@ -1,4 +1,8 @@
-use tig_utils::{Frontier, FrontierOps, ParetoCompare, PointCompareFrontiers, PointOps};
+use proptest::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use tig_utils::{
+    pareto_algorithm, Frontier, FrontierOps, ParetoCompare, Point, PointCompareFrontiers, PointOps,
+};
 
 #[test]
 fn test_pareto_compare() {
@@ -106,3 +110,139 @@ fn test_within() {
         PointCompareFrontiers::Below
     );
 }
+
+#[test]
+fn test_pareto_algorithm() {
+    let points: Frontier = vec![
+        vec![3, 1],
+        vec![1, 0],
+        vec![0, 1],
+        vec![1, 1],
+        vec![0, 0],
+        vec![2, 2],
+        vec![2, 1],
+        vec![1, 3],
+    ]
+    .into_iter()
+    .collect();
+    let layers: Vec<Frontier> = vec![
+        vec![vec![2, 2], vec![3, 1], vec![1, 3]],
+        vec![vec![2, 1]],
+        vec![vec![1, 1]],
+        vec![vec![1, 0], vec![0, 1]],
+        vec![vec![0, 0]],
+    ]
+    .into_iter()
+    .map(|layer| layer.into_iter().collect())
+    .collect();
+    assert_eq!(pareto_algorithm(points.clone(), false), layers);
+    assert_eq!(pareto_algorithm(points, true), layers[..1].to_vec());
+
+    let points: Frontier = vec![vec![1, 1, 1], vec![2, 0, 0], vec![0, 0, 2], vec![0, 1, 0]]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        pareto_algorithm(points, false),
+        vec![
+            vec![vec![1, 1, 1], vec![2, 0, 0], vec![0, 0, 2]]
+                .into_iter()
+                .collect::<Frontier>(),
+            vec![vec![0, 1, 0]].into_iter().collect::<Frontier>(),
+        ]
+    );
+}
+
+#[test]
+fn test_sample_3d() {
+    let frontier: Frontier = vec![vec![5, 0, 0], vec![0, 5, 0], vec![0, 0, 5], vec![2, 2, 2]]
+        .into_iter()
+        .collect();
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let point = frontier.sample(&mut rng);
+        assert_eq!(point.len(), 3);
+        assert_eq!(
+            point.within(&frontier, &frontier),
+            PointCompareFrontiers::Within
+        );
+    }
+}
+
+/// Pareto frontier of 1 to 20 random points with 2 to 4 dimensions
+fn frontier_strategy() -> impl Strategy<Value = Frontier> {
+    (2usize..=4).prop_flat_map(|num_dims| {
+        prop::collection::hash_set(prop::collection::vec(0i32..50, num_dims), 1..20)
+            .prop_map(|points| points.pareto_frontier())
+    })
+}
+
+fn bounds(frontier: &Frontier) -> (Point, Point) {
+    let num_dims = frontier.iter().next().unwrap().len();
+    (vec![0; num_dims], vec![100; num_dims])
+}
+
+proptest! {
+    #[test]
+    fn prop_pareto_algorithm_layers_are_frontiers(
+        points in (2usize..=4).prop_flat_map(|num_dims| {
+            prop::collection::hash_set(prop::collection::vec(0i32..10, num_dims), 1..30)
+        })
+    ) {
+        let mut remaining = points.clone();
+        for layer in pareto_algorithm(points, false) {
+            prop_assert_eq!(&layer, &remaining.pareto_frontier());
+            remaining.retain(|p| !layer.contains(p));
+        }
+        prop_assert!(remaining.is_empty());
+    }
+
+    #[test]
+    fn prop_sample_is_between_base_and_harder_scaled_frontier(
+        frontier in frontier_strategy(),
+        multiplier in 1.01f64..3.0,
+        seed: u64,
+    ) {
+        let (min_point, max_point) = bounds(&frontier);
+        let scaled_frontier = frontier.scale(&min_point, &max_point, multiplier);
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..10 {
+            let point = frontier.sample(&mut rng);
+            prop_assert_eq!(
+                point.within(&frontier, &scaled_frontier),
+                PointCompareFrontiers::Within
+            );
+        }
+    }
+
+    #[test]
+    fn prop_sample_is_between_easier_scaled_and_base_frontier(
+        frontier in frontier_strategy(),
+        multiplier in 0.1f64..0.99,
+        seed: u64,
+    ) {
+        let (min_point, max_point) = bounds(&frontier);
+        let scaled_frontier = frontier.scale(&min_point, &max_point, multiplier);
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..10 {
+            let point = frontier.sample(&mut rng);
+            prop_assert_eq!(
+                point.within(&scaled_frontier, &frontier),
+                PointCompareFrontiers::Within
+            );
+        }
+    }
+
+    #[test]
+    fn prop_scaled_frontier_stays_within_bounds(
+        frontier in frontier_strategy(),
+        multiplier in 0.1f64..3.0,
+    ) {
+        let (min_point, max_point) = bounds(&frontier);
+        for point in frontier.scale(&min_point, &max_point, multiplier) {
+            prop_assert_eq!(point.len(), min_point.len());
+            for (i, v) in point.iter().enumerate() {
+                prop_assert!(min_point[i] <= *v && *v <= max_point[i]);
+            }
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use serde_json::json;
+use tig_utils::jsonify_internal_with_sets;
+
+#[test]
+fn test_jsonify_internal_with_sets() {
+    let value = json!({
+        "frontier": [[2, 1], [1, 2]],
+        "route": [[2, 1], [1, 2]],
+        "nested": {"frontier": [3, 1, 2], "point": [3, 1, 2]},
+    });
+    assert_eq!(
+        jsonify_internal_with_sets(&value, &["frontier"]).to_string(),
+        r#"{"frontier":[[1,2],[2,1]],"nested":{"frontier":[1,2,3],"point":[3,1,2]},"route":[[2,1],[1,2]]}"#
+    );
+}
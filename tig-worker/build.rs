@@ -0,0 +1,46 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    let algorithms_dir = Path::new("../tig-algorithms/src");
+    let lib_rs = algorithms_dir.join("lib.rs");
+    println!("cargo:rerun-if-changed={}", lib_rs.display());
+    let lib_rs = fs::read_to_string(&lib_rs).expect("Failed to read tig-algorithms/src/lib.rs");
+
+    // Challenges are aliased in lib.rs as `pub use <challenge> as c00X;` and their
+    // algorithms in <challenge>/mod.rs as `pub use <algorithm> as c00X_a00Y;`
+    let mut registry = String::from("native_registry! {\n");
+    for (challenge, challenge_id) in parse_aliases(&lib_rs) {
+        let mod_rs = algorithms_dir.join(&challenge).join("mod.rs");
+        println!("cargo:rerun-if-changed={}", mod_rs.display());
+        let mod_rs = fs::read_to_string(&mod_rs)
+            .unwrap_or_else(|_| panic!("Failed to read tig-algorithms/src/{}/mod.rs", challenge));
+        let algorithm_ids: Vec<String> = parse_aliases(&mod_rs)
+            .into_iter()
+            .map(|(_, algorithm_id)| algorithm_id)
+            .filter(|algorithm_id| algorithm_id.starts_with(&format!("{}_", challenge_id)))
+            .collect();
+        registry.push_str(&format!(
+            "    {} => [{}],\n",
+            challenge,
+            algorithm_ids.join(", ")
+        ));
+    }
+    registry.push_str("}\n");
+
+    // Write the generated code to a file
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let dest_path = Path::new(&out_dir).join("native_registry.rs");
+    fs::write(dest_path, registry).expect("Failed to write native_registry.rs");
+}
+
+fn parse_aliases(code: &str) -> Vec<(String, String)> {
+    code.lines()
+        .filter_map(|line| {
+            let line = line.trim().strip_prefix("pub use ")?.strip_suffix(';')?;
+            let (name, alias) = line.split_once(" as ")?;
+            Some((name.trim().to_string(), alias.trim().to_string()))
+        })
+        .collect()
+}
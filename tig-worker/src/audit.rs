@@ -0,0 +1,177 @@
+use crate::{
+    error::WorkerError,
+    worker::{self, BenchmarkSettings},
+};
+use serde::{Deserialize, Serialize};
+use tig_challenges::VerificationError;
+use tig_structs::core::MerkleProof;
+use tig_utils::MerkleHash;
+use wasmi::Module;
+
+/// Merkle proofs claimed by another party, as output by `compute_batch` or `prove`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchProofs {
+    pub merkle_proofs: Vec<MerkleProof>,
+    /// Nonces claimed to be solutions. If missing, any leaf with a non-empty solution is
+    /// treated as claimed
+    #[serde(default)]
+    pub solution_nonces: Option<Vec<u64>>,
+}
+
+/// Result of auditing a single Merkle proof. The proof passes if `errors` is empty
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NonceAudit {
+    pub nonce: u64,
+    pub valid_merkle_branch: bool,
+    pub claimed_solution: bool,
+    pub valid_solution: bool,
+    /// Why a claimed solution failed verification
+    #[serde(default)]
+    pub invalid_solution_reason: Option<VerificationError>,
+    /// Whether recomputing the nonce gave the same output data. None if no WASM was given
+    pub matches_recomputed: Option<bool>,
+    pub errors: Vec<String>,
+}
+
+impl NonceAudit {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Offline equivalent of the protocol's `submit_proof` and `verify_proof` checks for a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchAudit {
+    pub settings: BenchmarkSettings,
+    pub rand_hash: String,
+    pub merkle_root: MerkleHash,
+    pub start_nonce: u64,
+    /// If set, branches deeper than the batch's Merkle tree are rejected
+    pub batch_size: Option<u64>,
+    pub max_memory: u64,
+    pub max_fuel: u64,
+}
+
+impl BatchAudit {
+    /// Audits every proof. If `module` is given, each leaf is also recomputed and compared
+    pub fn verify(&self, proofs: &BatchProofs, module: Option<&Module>) -> Vec<NonceAudit> {
+        proofs
+            .merkle_proofs
+            .iter()
+            .map(|merkle_proof| self.verify_proof(merkle_proof, proofs, module))
+            .collect()
+    }
+
+    fn verify_proof(
+        &self,
+        merkle_proof: &MerkleProof,
+        proofs: &BatchProofs,
+        module: Option<&Module>,
+    ) -> NonceAudit {
+        let leaf = &merkle_proof.leaf;
+        let nonce = leaf.nonce;
+        let mut errors = Vec::new();
+
+        let valid_merkle_branch = match self.verify_merkle_branch(merkle_proof) {
+            Ok(()) => true,
+            Err(e) => {
+                errors.push(e);
+                false
+            }
+        };
+
+        let claimed_solution = match &proofs.solution_nonces {
+            Some(solution_nonces) => solution_nonces.contains(&nonce),
+            None => !leaf.solution.is_empty(),
+        };
+        let mut invalid_solution_reason = None;
+        let valid_solution =
+            match worker::verify_solution(&self.settings, &self.rand_hash, nonce, &leaf.solution) {
+                Ok(()) => true,
+                Err(e) => {
+                    if claimed_solution {
+                        errors.push(e.to_string());
+                        if let WorkerError::InvalidSolution { reason } = e {
+                            invalid_solution_reason = Some(reason);
+                        }
+                    }
+                    false
+                }
+            };
+        if valid_solution && !claimed_solution {
+            errors.push("Valid solution was not claimed as a solution".to_string());
+        }
+
+        let matches_recomputed = module.map(|module| {
+            match worker::compute_solution_with_module(
+                &self.settings,
+                &self.rand_hash,
+                nonce,
+                module,
+                self.max_memory,
+                self.max_fuel,
+            ) {
+                Ok((output_data, _)) if output_data == *leaf => true,
+                Ok((output_data, _)) => {
+                    let mut fields = Vec::new();
+                    if output_data.runtime_signature != leaf.runtime_signature {
+                        fields.push("runtime_signature");
+                    }
+                    if output_data.fuel_consumed != leaf.fuel_consumed {
+                        fields.push("fuel_consumed");
+                    }
+                    if output_data.solution != leaf.solution {
+                        fields.push("solution");
+                    }
+                    errors.push(format!(
+                        "Recomputed output data differs in {}",
+                        fields.join(", ")
+                    ));
+                    false
+                }
+                Err(e) => {
+                    errors.push(format!("Failed to recompute: {}", e));
+                    false
+                }
+            }
+        });
+
+        NonceAudit {
+            nonce,
+            valid_merkle_branch,
+            claimed_solution,
+            valid_solution,
+            invalid_solution_reason,
+            matches_recomputed,
+            errors,
+        }
+    }
+
+    fn verify_merkle_branch(&self, merkle_proof: &MerkleProof) -> Result<(), String> {
+        let nonce = merkle_proof.leaf.nonce;
+        let branch = merkle_proof
+            .branch
+            .as_ref()
+            .ok_or_else(|| "Merkle proof is missing its branch".to_string())?;
+        if nonce < self.start_nonce {
+            return Err(format!(
+                "Nonce is before the batch start nonce {}",
+                self.start_nonce
+            ));
+        }
+        if let Some(batch_size) = self.batch_size {
+            let max_branch_len = (64 - (batch_size.max(1) - 1).leading_zeros()) as usize;
+            if nonce - self.start_nonce >= batch_size
+                || branch.0.len() > max_branch_len
+                || branch.0.iter().any(|(d, _)| *d as usize > max_branch_len)
+            {
+                return Err("Merkle branch is too deep for the batch size".to_string());
+            }
+        }
+        let hash = MerkleHash::from(merkle_proof.leaf.clone());
+        match branch.calc_merkle_root(&hash, (nonce - self.start_nonce) as usize) {
+            Ok(merkle_root) if merkle_root == self.merkle_root => Ok(()),
+            _ => Err("Merkle branch does not match the merkle root".to_string()),
+        }
+    }
+}
@@ -0,0 +1,431 @@
+use crate::{
+    checkpoint::{Checkpoint, CheckpointKey, CheckpointRecord},
+    error::{WorkerError, WorkerResult},
+    report::{write_report, NonceOutcome, NonceReport},
+    worker::{self, AlgorithmRunner},
+};
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+use tig_structs::core::{BenchmarkSettings, MerkleProof, OutputData};
+use tig_utils::{compress_obj, decompress_obj, MerkleHash, MerkleTree};
+
+pub const DEFAULT_MAX_MEMORY: u64 = 1_000_000_000;
+pub const DEFAULT_MAX_FUEL: u64 = 2_000_000_000;
+
+/// A batch of nonces to compute and the Merkle proofs to generate for it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchJob {
+    pub settings: BenchmarkSettings,
+    pub rand_hash: String,
+    pub start_nonce: u64,
+    pub num_nonces: u64,
+    pub batch_size: u64,
+    #[serde(default = "default_max_memory")]
+    pub max_memory: u64,
+    #[serde(default = "default_max_fuel")]
+    pub max_fuel: u64,
+    /// Nonces for which to generate proofs
+    #[serde(default)]
+    pub sampled_nonces: Vec<u64>,
+    #[serde(default = "default_num_workers")]
+    pub num_workers: usize,
+    /// Where to save a `BatchArtifact` for generating proofs later
+    #[serde(default)]
+    pub artifact_path: Option<PathBuf>,
+    /// Where to write a `NonceReport` for every nonce
+    #[serde(default)]
+    pub report_path: Option<PathBuf>,
+    /// Directory to periodically save completed nonces to. A job with the same checkpoint
+    /// directory skips nonces saved by a previous run
+    #[serde(default)]
+    pub checkpoint_dir: Option<PathBuf>,
+    /// Hash of the algorithm's wasm, so that a checkpoint is not resumed with a different one
+    #[serde(default)]
+    pub wasm_hash: Option<String>,
+}
+
+fn default_max_memory() -> u64 {
+    DEFAULT_MAX_MEMORY
+}
+
+fn default_max_fuel() -> u64 {
+    DEFAULT_MAX_FUEL
+}
+
+fn default_num_workers() -> usize {
+    1
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchResult {
+    pub merkle_root: MerkleHash,
+    pub merkle_proofs: Vec<MerkleProof>,
+    pub solution_nonces: Vec<u64>,
+}
+
+impl BatchJob {
+    pub fn end_nonce(&self) -> u64 {
+        self.start_nonce + self.num_nonces
+    }
+
+    pub fn validate(&self) -> WorkerResult<()> {
+        if self.num_nonces == 0 || self.batch_size < self.num_nonces {
+            return Err(WorkerError::InvalidInput {
+                msg: "Invalid number of nonces. Must be non-zero and less than batch size"
+                    .to_string(),
+            });
+        }
+        for &nonce in &self.sampled_nonces {
+            if nonce < self.start_nonce || nonce >= self.end_nonce() {
+                return Err(WorkerError::InvalidInput {
+                    msg: format!(
+                        "Sampled nonce {} is out of range [{}, {})",
+                        nonce,
+                        self.start_nonce,
+                        self.end_nonce()
+                    ),
+                });
+            }
+        }
+        if self.batch_size == 0 || (self.batch_size & (self.batch_size - 1)) != 0 {
+            return Err(WorkerError::InvalidInput {
+                msg: "Batch size must be a power of 2".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Sets `wasm_hash` to the hash of the wasm that is actually run, so checkpoints are keyed on
+    /// it. Fails if the job states a different `wasm_hash`
+    pub fn set_wasm_hash(&mut self, wasm_hash: String) -> WorkerResult<()> {
+        if self.wasm_hash.as_ref().is_some_and(|h| *h != wasm_hash) {
+            return Err(WorkerError::InvalidInput {
+                msg: format!(
+                    "wasm_hash {} does not match the hash of the wasm being run {}",
+                    self.wasm_hash.as_ref().unwrap(),
+                    wasm_hash
+                ),
+            });
+        }
+        self.wasm_hash = Some(wasm_hash);
+        Ok(())
+    }
+}
+
+/// Computes every nonce of `job` with `num_workers` concurrent workers. `on_nonce` is called
+/// with the output data and report of each nonce, in the order they finish. Nonces restored from
+/// a checkpoint are not computed and `on_nonce` is not called for them
+pub async fn compute_batch<F>(
+    job: &BatchJob,
+    runner: AlgorithmRunner,
+    mut on_nonce: F,
+) -> Result<BatchResult>
+where
+    F: FnMut(&OutputData, &NonceReport),
+{
+    let mut state = BatchState::new(job)?;
+    let settings = Arc::new(job.settings.clone());
+    let mut results = stream::iter(state.remaining_nonces())
+        .map(|nonce| {
+            let settings = Arc::clone(&settings);
+            let runner = runner.clone();
+            let rand_hash = job.rand_hash.clone();
+            let (max_memory, max_fuel) = (job.max_memory, job.max_fuel);
+            tokio::task::spawn_blocking(move || {
+                compute_nonce(&runner, &settings, &rand_hash, nonce, max_memory, max_fuel)
+            })
+        })
+        .buffer_unordered(job.num_workers.max(1));
+
+    while let Some(result) = results.next().await {
+        let (output_data, report) = result??;
+        on_nonce(&output_data, &report);
+        state.insert(output_data, report)?;
+    }
+    state.finish()
+}
+
+/// Computes a single nonce and verifies its solution. Failures of the algorithm, including in
+/// `init`, are part of the report. An error means no nonce of the batch can be computed
+pub(crate) fn compute_nonce(
+    runner: &AlgorithmRunner,
+    settings: &BenchmarkSettings,
+    rand_hash: &String,
+    nonce: u64,
+    max_memory: u64,
+    max_fuel: u64,
+) -> WorkerResult<(OutputData, NonceReport)> {
+    let start = Instant::now();
+    let (output_data, err_msg) =
+        runner.compute_solution(settings, rand_hash, nonce, max_memory, max_fuel)?;
+    let is_empty = output_data.solution.is_empty();
+    let is_valid = err_msg.is_none()
+        && !is_empty
+        && worker::verify_solution(settings, rand_hash, nonce, &output_data.solution).is_ok();
+    let report = NonceReport {
+        nonce,
+        fuel_consumed: output_data.fuel_consumed,
+        runtime_signature: output_data.runtime_signature,
+        outcome: NonceOutcome::new(err_msg.as_ref(), is_empty, is_valid),
+        elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+    };
+    Ok((output_data, report))
+}
+
+/// Results of a batch collected so far, restored from its checkpoint if it has one
+pub(crate) struct BatchState<'a> {
+    job: &'a BatchJob,
+    keep_all: bool,
+    output_data_map: HashMap<u64, OutputData>,
+    hashes: Vec<MerkleHash>,
+    solution_nonces: Vec<u64>,
+    reports: Vec<NonceReport>,
+    checkpoint: Option<Checkpoint>,
+    done: HashSet<u64>,
+}
+
+impl<'a> BatchState<'a> {
+    pub(crate) fn new(job: &'a BatchJob) -> Result<Self> {
+        job.validate()?;
+        let mut state = Self {
+            job,
+            keep_all: job.artifact_path.is_some(),
+            output_data_map: HashMap::new(),
+            hashes: vec![MerkleHash::null(); job.num_nonces as usize],
+            solution_nonces: Vec::new(),
+            reports: Vec::new(),
+            checkpoint: None,
+            done: HashSet::new(),
+        };
+        if let Some(checkpoint_dir) = &job.checkpoint_dir {
+            let (checkpoint, records) =
+                Checkpoint::open(checkpoint_dir, &CheckpointKey::from(job))?;
+            for record in records {
+                let nonce = record.report.nonce;
+                // recompute if the output data is now required but was not saved
+                if state.needs_output_data(nonce) && record.output_data.is_none()
+                    || state.done.contains(&nonce)
+                {
+                    continue;
+                }
+                state.add(nonce, record.hash, record.report, record.output_data);
+            }
+            state.checkpoint = Some(checkpoint);
+        }
+        Ok(state)
+    }
+
+    /// Nonces that still need to be computed
+    pub(crate) fn remaining_nonces(&self) -> Vec<u64> {
+        (self.job.start_nonce..self.job.end_nonce())
+            .filter(|nonce| !self.done.contains(nonce))
+            .collect()
+    }
+
+    pub(crate) fn insert(&mut self, output_data: OutputData, report: NonceReport) -> Result<()> {
+        let nonce = output_data.nonce;
+        let hash = MerkleHash::from(output_data.clone());
+        // only keep the data if required
+        let output_data = match self.needs_output_data(nonce) {
+            true => Some(output_data),
+            false => None,
+        };
+        if let Some(checkpoint) = self.checkpoint.as_mut() {
+            checkpoint.append(&CheckpointRecord {
+                hash: hash.clone(),
+                report: report.clone(),
+                output_data: output_data.clone(),
+            })?;
+        }
+        self.add(nonce, hash, report, output_data);
+        Ok(())
+    }
+
+    fn needs_output_data(&self, nonce: u64) -> bool {
+        self.keep_all || self.job.sampled_nonces.contains(&nonce)
+    }
+
+    fn add(
+        &mut self,
+        nonce: u64,
+        hash: MerkleHash,
+        report: NonceReport,
+        output_data: Option<OutputData>,
+    ) {
+        self.done.insert(nonce);
+        if report.is_solution() {
+            self.solution_nonces.push(nonce);
+        }
+        if self.job.report_path.is_some() {
+            self.reports.push(report);
+        }
+        self.hashes[(nonce - self.job.start_nonce) as usize] = hash;
+        if let Some(output_data) = output_data {
+            self.output_data_map.insert(nonce, output_data);
+        }
+    }
+
+    /// Builds the Merkle tree and proofs once every nonce is done
+    pub(crate) fn finish(mut self) -> Result<BatchResult> {
+        let job = self.job;
+        if let Some(checkpoint) = self.checkpoint.as_mut() {
+            checkpoint.flush()?;
+        }
+        // restored and computed nonces finish in a different order
+        self.solution_nonces.sort();
+        let (merkle_root, merkle_proofs) = match &job.artifact_path {
+            Some(artifact_path) => {
+                let outputs = (job.start_nonce..job.end_nonce())
+                    .map(|nonce| self.output_data_map.remove(&nonce).unwrap())
+                    .collect();
+                let artifact = BatchArtifact::new(
+                    job.start_nonce,
+                    job.batch_size,
+                    outputs,
+                    self.solution_nonces.clone(),
+                )?;
+                artifact.save(artifact_path)?;
+                (artifact.merkle_root(), artifact.prove(&job.sampled_nonces)?)
+            }
+            None => {
+                let tree = MerkleTree::new(self.hashes, job.batch_size as usize)?;
+                let mut merkle_proofs = Vec::new();
+                for (nonce, output_data) in self.output_data_map {
+                    let branch = tree.calc_merkle_branch((nonce - job.start_nonce) as usize)?;
+                    merkle_proofs.push(MerkleProof {
+                        leaf: output_data,
+                        branch: Some(branch),
+                    });
+                }
+                (tree.calc_merkle_root(), merkle_proofs)
+            }
+        };
+        if let Some(report_path) = &job.report_path {
+            self.reports.sort_by_key(|report| report.nonce);
+            write_report(report_path, &self.reports)?;
+        }
+        Ok(BatchResult {
+            merkle_root,
+            merkle_proofs,
+            solution_nonces: self.solution_nonces,
+        })
+    }
+}
+
+/// Everything computed for a batch of nonces, so that Merkle proofs can be generated once
+/// sampled nonces are known without re-running the algorithm
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchArtifact {
+    pub start_nonce: u64,
+    pub merkle_tree: MerkleTree,
+    /// Output data of every nonce in the batch, ordered by nonce
+    pub outputs: Vec<OutputData>,
+    pub solution_nonces: Vec<u64>,
+}
+
+impl BatchArtifact {
+    pub fn new(
+        start_nonce: u64,
+        batch_size: u64,
+        outputs: Vec<OutputData>,
+        solution_nonces: Vec<u64>,
+    ) -> Result<Self> {
+        let hashes = outputs
+            .iter()
+            .map(|output_data| MerkleHash::from(output_data.clone()))
+            .collect();
+        let merkle_tree = MerkleTree::new(hashes, batch_size as usize)?;
+        Ok(Self {
+            start_nonce,
+            merkle_tree,
+            outputs,
+            solution_nonces,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path).map_err(|e| WorkerError::Io {
+            path: path.display().to_string(),
+            msg: e.to_string(),
+        })?;
+        decompress_obj(&bytes).map_err(|e| {
+            WorkerError::InvalidInput {
+                msg: format!("Failed to parse artifact {}: {}", path.display(), e),
+            }
+            .into()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, compress_obj(self)).map_err(|e| {
+            WorkerError::Io {
+                path: path.display().to_string(),
+                msg: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    pub fn end_nonce(&self) -> u64 {
+        self.start_nonce + self.outputs.len() as u64
+    }
+
+    pub fn merkle_root(&self) -> MerkleHash {
+        self.merkle_tree.calc_merkle_root()
+    }
+
+    /// Merkle proofs for `sampled_nonces`. Fails if a nonce is outside of the batch or its
+    /// output data does not match the leaf in the tree
+    pub fn prove(&self, sampled_nonces: &[u64]) -> Result<Vec<MerkleProof>> {
+        let merkle_root = self.merkle_root();
+        let mut merkle_proofs = Vec::new();
+        for &nonce in sampled_nonces {
+            if nonce < self.start_nonce || nonce >= self.end_nonce() {
+                return Err(WorkerError::InvalidInput {
+                    msg: format!(
+                        "Sampled nonce {} is out of range [{}, {})",
+                        nonce,
+                        self.start_nonce,
+                        self.end_nonce()
+                    ),
+                }
+                .into());
+            }
+            let idx = (nonce - self.start_nonce) as usize;
+            let output_data = &self.outputs[idx];
+            if output_data.nonce != nonce {
+                return Err(WorkerError::InvalidInput {
+                    msg: format!(
+                        "Artifact output data at index {} is for nonce {}, expected {}",
+                        idx, output_data.nonce, nonce
+                    ),
+                }
+                .into());
+            }
+            let branch = self.merkle_tree.calc_merkle_branch(idx)?;
+            let hash = MerkleHash::from(output_data.clone());
+            if branch.calc_merkle_root(&hash, idx)? != merkle_root {
+                return Err(WorkerError::InvalidInput {
+                    msg: format!(
+                        "Artifact output data for nonce {} does not match its merkle leaf",
+                        nonce
+                    ),
+                }
+                .into());
+            }
+            merkle_proofs.push(MerkleProof {
+                leaf: output_data.clone(),
+                branch: Some(branch),
+            });
+        }
+        Ok(merkle_proofs)
+    }
+}
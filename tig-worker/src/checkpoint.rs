@@ -0,0 +1,146 @@
+use crate::{
+    batch::BatchJob,
+    error::{WorkerError, WorkerResult},
+    report::NonceReport,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use tig_structs::core::{BenchmarkSettings, OutputData};
+use tig_utils::{dejsonify, jsonify, MerkleHash};
+
+/// Number of completed nonces buffered before they are flushed to disk
+pub const CHECKPOINT_INTERVAL: usize = 64;
+
+/// Identifies the batch a checkpoint belongs to. Resuming from a checkpoint with a different key
+/// is rejected, as its hashes would give a different Merkle root
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheckpointKey {
+    pub settings: BenchmarkSettings,
+    pub rand_hash: String,
+    pub start_nonce: u64,
+    pub num_nonces: u64,
+    pub batch_size: u64,
+    pub max_memory: u64,
+    pub max_fuel: u64,
+    pub wasm_hash: Option<String>,
+}
+
+impl From<&BatchJob> for CheckpointKey {
+    fn from(job: &BatchJob) -> Self {
+        Self {
+            settings: job.settings.clone(),
+            rand_hash: job.rand_hash.clone(),
+            start_nonce: job.start_nonce,
+            num_nonces: job.num_nonces,
+            batch_size: job.batch_size,
+            max_memory: job.max_memory,
+            max_fuel: job.max_fuel,
+            wasm_hash: job.wasm_hash.clone(),
+        }
+    }
+}
+
+/// A completed nonce
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CheckpointRecord {
+    pub hash: MerkleHash,
+    pub report: NonceReport,
+    /// Only saved for nonces whose output data is needed for proofs or an artifact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_data: Option<OutputData>,
+}
+
+/// Completed nonces of a batch, appended as JSON lines to `nonces.jsonl` in the checkpoint
+/// directory. `key.json` records the batch they belong to
+pub struct Checkpoint {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    num_pending: usize,
+}
+
+impl Checkpoint {
+    /// Opens the checkpoint in `dir`, creating it if it does not exist, and returns the records
+    /// saved by previous runs
+    pub fn open(dir: &Path, key: &CheckpointKey) -> WorkerResult<(Self, Vec<CheckpointRecord>)> {
+        fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+        let key_path = dir.join("key.json");
+        if key_path.exists() {
+            let saved = fs::read_to_string(&key_path).map_err(|e| io_error(&key_path, e))?;
+            if dejsonify::<CheckpointKey>(&saved).ok().as_ref() != Some(key) {
+                return Err(WorkerError::InvalidInput {
+                    msg: format!(
+                        "Checkpoint {} is for a different batch. Delete it or use another directory",
+                        dir.display()
+                    ),
+                });
+            }
+        } else {
+            fs::write(&key_path, jsonify(key)).map_err(|e| io_error(&key_path, e))?;
+        }
+
+        let path = dir.join("nonces.jsonl");
+        let mut records = Vec::new();
+        if path.exists() {
+            let file = File::open(&path).map_err(|e| io_error(&path, e))?;
+            for line in BufReader::new(file).lines() {
+                // the last line is incomplete if the process was killed mid-write
+                match line
+                    .ok()
+                    .and_then(|l| dejsonify::<CheckpointRecord>(&l).ok())
+                {
+                    Some(record) => records.push(record),
+                    None => break,
+                }
+            }
+        }
+        // rewrite without any incomplete line, so new records can be appended
+        let tmp_path = dir.join("nonces.jsonl.tmp");
+        let mut writer =
+            BufWriter::new(File::create(&tmp_path).map_err(|e| io_error(&tmp_path, e))?);
+        for record in records.iter() {
+            writeln!(writer, "{}", jsonify(record)).map_err(|e| io_error(&tmp_path, e))?;
+        }
+        writer.flush().map_err(|e| io_error(&tmp_path, e))?;
+        fs::rename(&tmp_path, &path).map_err(|e| io_error(&path, e))?;
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+        Ok((
+            Self {
+                path,
+                writer: BufWriter::new(file),
+                num_pending: 0,
+            },
+            records,
+        ))
+    }
+
+    /// Buffers a completed nonce, flushing every `CHECKPOINT_INTERVAL` nonces
+    pub fn append(&mut self, record: &CheckpointRecord) -> WorkerResult<()> {
+        writeln!(self.writer, "{}", jsonify(record)).map_err(|e| io_error(&self.path, e))?;
+        self.num_pending += 1;
+        if self.num_pending >= CHECKPOINT_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> WorkerResult<()> {
+        self.writer.flush().map_err(|e| io_error(&self.path, e))?;
+        self.num_pending = 0;
+        Ok(())
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> WorkerError {
+    WorkerError::Io {
+        path: path.display().to_string(),
+        msg: e.to_string(),
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{
+    error::{WorkerError, WorkerResult},
+    worker::{AlgorithmRunner, BenchmarkSettings, OutputData},
+};
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+/// A range of nonces to compute twice, to check `OutputData` is reproducible
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeterminismCheck {
+    pub settings: BenchmarkSettings,
+    pub rand_hash: String,
+    pub start_nonce: u64,
+    pub num_nonces: u64,
+    pub max_memory: u64,
+    pub max_fuel: u64,
+    pub num_workers: usize,
+}
+
+/// Result of computing a nonce once
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NonceRun {
+    pub output_data: Option<OutputData>,
+    pub error: Option<String>,
+}
+
+impl From<WorkerResult<(OutputData, Option<WorkerError>)>> for NonceRun {
+    fn from(result: WorkerResult<(OutputData, Option<WorkerError>)>) -> Self {
+        match result {
+            Ok((output_data, err_msg)) => Self {
+                output_data: Some(output_data),
+                error: err_msg.map(|e| e.to_string()),
+            },
+            Err(e) => Self {
+                output_data: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// A nonce that gave different results when computed twice
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeterminismMismatch {
+    pub nonce: u64,
+    /// Names of the fields that differ between the two runs
+    pub fields: Vec<String>,
+    pub first: NonceRun,
+    pub second: NonceRun,
+}
+
+impl DeterminismMismatch {
+    /// None if both runs are identical
+    pub fn compare(nonce: u64, first: NonceRun, second: NonceRun) -> Option<Self> {
+        if first == second {
+            return None;
+        }
+        let mut fields = Vec::new();
+        match (&first.output_data, &second.output_data) {
+            (Some(a), Some(b)) => {
+                if a.runtime_signature != b.runtime_signature {
+                    fields.push("runtime_signature".to_string());
+                }
+                if a.fuel_consumed != b.fuel_consumed {
+                    fields.push("fuel_consumed".to_string());
+                }
+                if a.solution != b.solution {
+                    fields.push("solution".to_string());
+                }
+            }
+            (None, None) => {}
+            _ => fields.push("output_data".to_string()),
+        }
+        if first.error != second.error {
+            fields.push("error".to_string());
+        }
+        Some(Self {
+            nonce,
+            fields,
+            first,
+            second,
+        })
+    }
+}
+
+/// Computes every nonce twice, each time in a new store. The first pass runs nonces in ascending
+/// order and the second in descending order, so that runs of a nonce are interleaved with
+/// different neighbours. Returns the nonces whose results differ, ordered by nonce
+pub async fn check_determinism(
+    check: &DeterminismCheck,
+    runner: AlgorithmRunner,
+) -> Result<Vec<DeterminismMismatch>> {
+    let nonces: Vec<u64> = (check.start_nonce..check.start_nonce + check.num_nonces).collect();
+    let mut first = run_nonces(check, &runner, nonces.clone()).await?;
+    let mut second = run_nonces(check, &runner, nonces.iter().rev().cloned().collect()).await?;
+    Ok(nonces
+        .into_iter()
+        .filter_map(|nonce| {
+            DeterminismMismatch::compare(
+                nonce,
+                first.remove(&nonce).unwrap(),
+                second.remove(&nonce).unwrap(),
+            )
+        })
+        .collect())
+}
+
+async fn run_nonces(
+    check: &DeterminismCheck,
+    runner: &AlgorithmRunner,
+    nonces: Vec<u64>,
+) -> Result<HashMap<u64, NonceRun>> {
+    let settings = Arc::new(check.settings.clone());
+    let mut results = stream::iter(nonces)
+        .map(|nonce| {
+            let settings = Arc::clone(&settings);
+            let runner = runner.clone();
+            let rand_hash = check.rand_hash.clone();
+            let (max_memory, max_fuel) = (check.max_memory, check.max_fuel);
+            tokio::task::spawn_blocking(move || {
+                let result =
+                    runner.compute_solution(&settings, &rand_hash, nonce, max_memory, max_fuel);
+                (nonce, NonceRun::from(result))
+            })
+        })
+        .buffer_unordered(check.num_workers.max(1));
+
+    let mut runs = HashMap::new();
+    while let Some(result) = results.next().await {
+        let (nonce, run) = result?;
+        runs.insert(nonce, run);
+    }
+    Ok(runs)
+}
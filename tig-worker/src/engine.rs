@@ -0,0 +1,190 @@
+use crate::{
+    determinism::{DeterminismMismatch, NonceRun},
+    error::{WorkerError, WorkerResult},
+    worker::{self, BenchmarkSettings},
+};
+use std::ops::Range;
+use wasmi::{
+    Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    TypedFunc,
+};
+
+/// Runs a compiled algorithm. `compute_solution_with_engine` creates a new instance for every
+/// nonce and drives it through the steps of `EngineInstance`.
+///
+/// wasmi's `Module` is the reference implementation and the only engine that can be used for
+/// proofs, as other engines are not expected to reproduce its fuel and runtime signature. Use
+/// `check_conformance` to compare an engine against it
+pub trait ExecutionEngine: Send + Sync {
+    /// Creates an instance whose memory is limited to `max_memory` bytes and which traps with
+    /// `WorkerError::FuelExhausted` after consuming `max_fuel`
+    fn instantiate(&self, max_memory: u64, max_fuel: u64) -> WorkerResult<Box<dyn EngineInstance>>;
+
+    /// If false, `fuel_consumed` is always 0 and `max_fuel` is not enforced
+    fn meters_fuel(&self) -> bool {
+        true
+    }
+
+    /// If false, `runtime_signature` is always 0
+    fn computes_runtime_signature(&self) -> bool {
+        true
+    }
+}
+
+/// A single run of an algorithm. Steps are called in order: `init` with the length of the
+/// serialized challenge, `write_memory` of the challenge at the returned pointer, `entry_point`,
+/// then `read_memory` of the length prefixed, compressed solution at the returned pointer
+pub trait EngineInstance {
+    fn init(&mut self, challenge_len: u32) -> WorkerResult<u32>;
+
+    fn entry_point(&mut self, challenge_ptr: u32, challenge_len: u32) -> WorkerResult<u32>;
+
+    fn read_memory(&self, offset: usize, buffer: &mut [u8]) -> anyhow::Result<()>;
+
+    fn write_memory(&mut self, offset: usize, data: &[u8]) -> anyhow::Result<()>;
+
+    fn fuel_consumed(&self) -> u64;
+
+    fn runtime_signature(&self) -> u64;
+}
+
+/// Compiles an algorithm's wasm. The module can be reused across calls to
+/// `compute_solution_with_module`
+pub fn compile_module(wasm: &[u8]) -> WorkerResult<Module> {
+    let mut config = Config::default();
+    config.update_runtime_signature(true);
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    Module::new(&engine, wasm).map_err(|e| WorkerError::InvalidWasm {
+        msg: format!("Failed to instantiate module: {:?}", e),
+    })
+}
+
+/// Hash identifying an algorithm's wasm, e.g. to check a checkpoint is resumed with the same one
+pub fn wasm_hash(wasm: &[u8]) -> String {
+    blake3::hash(wasm).to_hex().to_string()
+}
+
+impl ExecutionEngine for Module {
+    fn instantiate(&self, max_memory: u64, max_fuel: u64) -> WorkerResult<Box<dyn EngineInstance>> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(max_memory as usize)
+            .memories(1)
+            .trap_on_grow_failure(true)
+            .build();
+        // Setup instance of wasm module
+        let mut store = Store::new(self.engine(), limits);
+        store.limiter(|lim| lim);
+        store.set_fuel(max_fuel).unwrap();
+        let linker = Linker::new(self.engine());
+
+        let instance: Instance = linker
+            .instantiate(&mut store, self)
+            .map_err(|e| WorkerError::InvalidWasm {
+                msg: format!("Failed to instantiate linker: {:?}", e),
+            })?
+            .start(&mut store)
+            .map_err(|e| WorkerError::from_wasmi(&e))?;
+
+        let memory =
+            instance
+                .get_memory(&store, "memory")
+                .ok_or_else(|| WorkerError::MissingExport {
+                    name: "memory".to_string(),
+                })?;
+        let init = instance
+            .get_typed_func::<u32, u32>(&store, "init")
+            .map_err(|_| WorkerError::MissingExport {
+                name: "init".to_string(),
+            })?;
+        let entry_point = instance
+            .get_typed_func::<(u32, u32), u32>(&store, "entry_point")
+            .map_err(|_| WorkerError::MissingExport {
+                name: "entry_point".to_string(),
+            })?;
+
+        Ok(Box::new(WasmiInstance {
+            store,
+            memory,
+            init,
+            entry_point,
+            max_fuel,
+        }))
+    }
+}
+
+struct WasmiInstance {
+    store: Store<StoreLimits>,
+    memory: Memory,
+    init: TypedFunc<u32, u32>,
+    entry_point: TypedFunc<(u32, u32), u32>,
+    max_fuel: u64,
+}
+
+impl EngineInstance for WasmiInstance {
+    fn init(&mut self, challenge_len: u32) -> WorkerResult<u32> {
+        self.init
+            .call(&mut self.store, challenge_len)
+            .map_err(|e| WorkerError::from_wasmi(&e))
+    }
+
+    fn entry_point(&mut self, challenge_ptr: u32, challenge_len: u32) -> WorkerResult<u32> {
+        self.entry_point
+            .call(&mut self.store, (challenge_ptr, challenge_len))
+            .map_err(|e| WorkerError::from_wasmi(&e))
+    }
+
+    fn read_memory(&self, offset: usize, buffer: &mut [u8]) -> anyhow::Result<()> {
+        self.memory
+            .read(&self.store, offset, buffer)
+            .map_err(|e| anyhow::anyhow!("{:?}", e))
+    }
+
+    fn write_memory(&mut self, offset: usize, data: &[u8]) -> anyhow::Result<()> {
+        self.memory
+            .write(&mut self.store, offset, data)
+            .map_err(|e| anyhow::anyhow!("{:?}", e))
+    }
+
+    fn fuel_consumed(&self) -> u64 {
+        self.max_fuel - self.store.get_fuel().unwrap()
+    }
+
+    fn runtime_signature(&self) -> u64 {
+        self.store.get_runtime_signature()
+    }
+}
+
+/// Computes `nonces` with both `engine` and the wasmi `reference`, returning the nonces whose
+/// results differ. Fuel consumed and runtime signature are only compared if `engine` computes
+/// them, in which case `max_fuel` should be large enough for the reference to not run out
+pub fn check_conformance(
+    engine: &dyn ExecutionEngine,
+    reference: &Module,
+    settings: &BenchmarkSettings,
+    rand_hash: &String,
+    nonces: Range<u64>,
+    max_memory: u64,
+    max_fuel: u64,
+) -> Vec<DeterminismMismatch> {
+    // fields the engine does not compute are ignored for both runs
+    let run = |runner: &dyn ExecutionEngine, nonce: u64| {
+        let mut run = NonceRun::from(worker::compute_solution_with_engine(
+            settings, rand_hash, nonce, runner, max_memory, max_fuel,
+        ));
+        if let Some(output_data) = run.output_data.as_mut() {
+            if !engine.meters_fuel() {
+                output_data.fuel_consumed = 0;
+            }
+            if !engine.computes_runtime_signature() {
+                output_data.runtime_signature = 0;
+            }
+        }
+        run
+    };
+    nonces
+        .filter_map(|nonce| {
+            DeterminismMismatch::compare(nonce, run(reference, nonce), run(engine, nonce))
+        })
+        .collect()
+}
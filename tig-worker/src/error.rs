@@ -0,0 +1,146 @@
+use serde_json::{json, Value};
+use tig_challenges::VerificationError;
+use wasmi::core::TrapCode;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkerError {
+    AlgorithmTrap { msg: String },
+    FuelExhausted,
+    Internal { msg: String },
+    InvalidBatch { nonces: Vec<u64> },
+    InvalidInput { msg: String },
+    InvalidSettings { msg: String },
+    InvalidSolution { reason: VerificationError },
+    InvalidWasm { msg: String },
+    Io { path: String, msg: String },
+    MemoryLimitExceeded,
+    MissingExport { name: String },
+    NoSolutionFound,
+    Nondeterministic { nonces: Vec<u64> },
+    UnknownAlgorithm { algorithm_id: String },
+    UnknownChallenge { challenge_id: String },
+}
+
+impl WorkerError {
+    /// Classifies an error raised by wasmi while executing an algorithm
+    pub fn from_wasmi(e: &wasmi::Error) -> Self {
+        match e.as_trap_code() {
+            Some(TrapCode::OutOfFuel) => WorkerError::FuelExhausted,
+            Some(TrapCode::GrowthOperationLimited) => WorkerError::MemoryLimitExceeded,
+            _ => WorkerError::AlgorithmTrap {
+                msg: format!("{:?}", e),
+            },
+        }
+    }
+
+    /// Recovers the `WorkerError` behind `e`. Any other error is internal
+    pub fn from_anyhow(e: anyhow::Error) -> Self {
+        e.downcast::<WorkerError>()
+            .unwrap_or_else(|e| WorkerError::Internal { msg: e.to_string() })
+    }
+
+    /// Snake case name of the variant, used in JSON error output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WorkerError::AlgorithmTrap { .. } => "algorithm_trap",
+            WorkerError::FuelExhausted => "fuel_exhausted",
+            WorkerError::Internal { .. } => "internal",
+            WorkerError::InvalidBatch { .. } => "invalid_batch",
+            WorkerError::InvalidInput { .. } => "invalid_input",
+            WorkerError::InvalidSettings { .. } => "invalid_settings",
+            WorkerError::InvalidSolution { .. } => "invalid_solution",
+            WorkerError::InvalidWasm { .. } => "invalid_wasm",
+            WorkerError::Io { .. } => "io",
+            WorkerError::MemoryLimitExceeded => "memory_limit_exceeded",
+            WorkerError::MissingExport { .. } => "missing_export",
+            WorkerError::NoSolutionFound => "no_solution_found",
+            WorkerError::Nondeterministic { .. } => "nondeterministic",
+            WorkerError::UnknownAlgorithm { .. } => "unknown_algorithm",
+            WorkerError::UnknownChallenge { .. } => "unknown_challenge",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            WorkerError::InvalidBatch { .. }
+            | WorkerError::InvalidSolution { .. }
+            | WorkerError::NoSolutionFound => 1,
+            WorkerError::InvalidInput { .. } => 2,
+            WorkerError::Internal { .. } => 3,
+            WorkerError::InvalidSettings { .. } => 10,
+            WorkerError::UnknownChallenge { .. } => 11,
+            WorkerError::InvalidWasm { .. } => 12,
+            WorkerError::MissingExport { .. } => 13,
+            WorkerError::FuelExhausted => 14,
+            WorkerError::MemoryLimitExceeded => 15,
+            WorkerError::AlgorithmTrap { .. } => 16,
+            WorkerError::UnknownAlgorithm { .. } => 17,
+            WorkerError::Nondeterministic { .. } => 18,
+            WorkerError::Io { .. } => 20,
+        }
+    }
+
+    /// Whether running again could succeed: after a transient IO failure, or with a larger
+    /// fuel or memory limit. Every other error is permanent for the same inputs
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            WorkerError::Io { .. } | WorkerError::FuelExhausted | WorkerError::MemoryLimitExceeded
+        )
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "error": self.kind(),
+            "message": self.to_string(),
+            "exit_code": self.exit_code(),
+            "retryable": self.is_retryable(),
+        });
+        if let WorkerError::InvalidSolution { reason } = self {
+            value["reason"] = json!(reason);
+        }
+        value
+    }
+}
+
+impl std::fmt::Display for WorkerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkerError::AlgorithmTrap { msg } => write!(f, "Error executing algorithm: {}", msg),
+            WorkerError::FuelExhausted => write!(f, "Algorithm ran out of fuel"),
+            WorkerError::Internal { msg } => write!(f, "Internal error: {}", msg),
+            WorkerError::InvalidBatch { nonces } => {
+                write!(
+                    f,
+                    "Merkle proofs for nonces {:?} failed verification",
+                    nonces
+                )
+            }
+            WorkerError::InvalidInput { msg } => write!(f, "Invalid input: {}", msg),
+            WorkerError::InvalidSettings { msg } => write!(f, "Invalid settings: {}", msg),
+            WorkerError::InvalidSolution { reason } => write!(f, "Invalid solution: {}", reason),
+            WorkerError::InvalidWasm { msg } => write!(f, "Invalid wasm: {}", msg),
+            WorkerError::Io { path, msg } => write!(f, "Failed to access '{}': {}", path, msg),
+            WorkerError::MemoryLimitExceeded => {
+                write!(f, "Algorithm exceeded the memory limit")
+            }
+            WorkerError::MissingExport { name } => {
+                write!(f, "Wasm module does not export '{}'", name)
+            }
+            WorkerError::NoSolutionFound => write!(f, "No solution found"),
+            WorkerError::Nondeterministic { nonces } => {
+                write!(f, "Output data of nonces {:?} is not reproducible", nonces)
+            }
+            WorkerError::UnknownAlgorithm { algorithm_id } => {
+                write!(f, "Unknown algorithm '{}'", algorithm_id)
+            }
+            WorkerError::UnknownChallenge { challenge_id } => {
+                write!(f, "Unknown challenge '{}'", challenge_id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WorkerError {}
+
+pub type WorkerResult<T> = std::result::Result<T, WorkerError>;
@@ -1,2 +1,22 @@
+mod audit;
+pub use audit::*;
+mod batch;
+pub use batch::*;
+mod checkpoint;
+pub use checkpoint::*;
+mod determinism;
+pub use determinism::*;
+mod engine;
+pub use engine::*;
+mod error;
+pub use error::*;
+mod many;
+pub use many::*;
+mod native;
+pub use native::*;
+mod report;
+pub use report::*;
+mod serve;
+pub use serve::*;
 mod worker;
 pub use worker::*;
@@ -1,17 +1,23 @@
-mod worker;
 use anyhow::{anyhow, Result};
 use clap::{arg, Command};
-use futures::stream::{self, StreamExt};
 use serde_json::json;
-use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
+use std::{fs, io::Write, os::unix::fs::FileTypeExt, path::PathBuf, sync::Arc};
 use tig_structs::core::{BenchmarkSettings, MerkleProof};
-use tig_utils::{dejsonify, jsonify, MerkleHash, MerkleTree};
-use tokio::runtime::Runtime;
+use tig_utils::{dejsonify, jsonify};
+use tig_worker::{
+    AlgorithmRunner, BatchArtifact, BatchAudit, BatchJob, BatchProofs, BatchResult,
+    DeterminismCheck, ExportFormat, Manifest, ModuleCache, Solution, WorkerError, WorkerResult,
+};
+use tokio::{net::UnixListener, runtime::Runtime};
 
 fn cli() -> Command {
     Command::new("tig-worker")
         .about("Computes or verifies solutions")
         .arg_required_else_help(true)
+        .arg(
+            arg!(--"json-errors" "Print errors to stderr as a JSON object with the error kind, exit code and whether it is retryable")
+                .global(true),
+        )
         .subcommand(
             Command::new("compute_solution")
                 .about("Computes a solution")
@@ -24,7 +30,15 @@ fn cli() -> Command {
                         .value_parser(clap::value_parser!(String)),
                 )
                 .arg(arg!(<NONCE> "Nonce value").value_parser(clap::value_parser!(u64)))
-                .arg(arg!(<WASM> "Path to a wasm file").value_parser(clap::value_parser!(PathBuf)))
+                .arg(
+                    arg!([WASM] "Path to a wasm file. Required unless --native is set")
+                        .required_unless_present("native")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--native "Run settings.algorithm_id natively instead of in the WASM VM. Fuel consumed and runtime signature are always 0")
+                        .conflicts_with("WASM"),
+                )
                 .arg(
                     arg!(--fuel [FUEL] "Optional maximum fuel parameter for WASM VM")
                         .default_value("2000000000")
@@ -53,6 +67,45 @@ fn cli() -> Command {
                         .value_parser(clap::value_parser!(String)),
                 ),
         )
+        .subcommand(
+            Command::new("evaluate_solution")
+                .about("Grades a solution against the threshold it has to reach to be valid")
+                .arg(
+                    arg!(<SETTINGS> "Settings json string or path to json file")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    arg!(<RAND_HASH> "A string used in seed generation")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(arg!(<NONCE> "Nonce value").value_parser(clap::value_parser!(u64)))
+                .arg(
+                    arg!(<SOLUTION> "Solution json string or path to json file")
+                        .value_parser(clap::value_parser!(String)),
+                ),
+        )
+        .subcommand(
+            Command::new("export_instance")
+                .about("Writes the challenge instance in a standard format for external solvers")
+                .arg(
+                    arg!(<SETTINGS> "Settings json string or path to json file")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    arg!(<RAND_HASH> "A string used in seed generation")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(arg!(<NONCE> "Nonce value").value_parser(clap::value_parser!(u64)))
+                .arg(
+                    arg!(--format <FORMAT> "Format to write. Must match the challenge")
+                        .required(true)
+                        .value_parser(ExportFormat::ALL.map(|format| format.as_str())),
+                )
+                .arg(
+                    arg!(--output [OUTPUT] "Path to write to, without extension. Required for formats with several files, otherwise the instance is printed to stdout")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                ),
+        )
         .subcommand(
             Command::new("compute_batch")
                 .about("Computes batch of nonces and generates Merkle proofs")
@@ -73,7 +126,15 @@ fn cli() -> Command {
                     arg!(<BATCH_SIZE> "Batch size for Merkle tree")
                         .value_parser(clap::value_parser!(u64)),
                 )
-                .arg(arg!(<WASM> "Path to a wasm file").value_parser(clap::value_parser!(PathBuf)))
+                .arg(
+                    arg!([WASM] "Path to a wasm file. Required unless --native is set")
+                        .required_unless_present("native")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--native "Run settings.algorithm_id natively instead of in the WASM VM. Fuel consumed and runtime signature are always 0")
+                        .conflicts_with("WASM"),
+                )
                 .arg(
                     arg!(--fuel [FUEL] "Optional maximum fuel parameter for WASM VM")
                         .default_value("2000000000")
@@ -93,19 +154,151 @@ fn cli() -> Command {
                     arg!(--workers [WORKERS] "Number of worker threads")
                         .default_value("1")
                         .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    arg!(--artifact [ARTIFACT] "Path to write a batch artifact for generating proofs later with `prove`")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--report [REPORT] "Path to write a per-nonce report. Written as CSV if it ends in .csv, otherwise as JSON lines")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--checkpoint [CHECKPOINT] "Directory to periodically save completed nonces to. Rerunning with the same directory resumes the batch. Not supported with --native")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .conflicts_with("native"),
+                ),
+        )
+        .subcommand(
+            Command::new("compute_many")
+                .about("Computes several batches with a shared pool of workers")
+                .arg(
+                    arg!(<MANIFEST> "Manifest json string or path to json file")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    arg!(--workers [WORKERS] "Number of worker threads. Overrides num_workers in the manifest")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("check_determinism")
+                .about("Computes each nonce twice and reports any whose output data differs")
+                .arg(
+                    arg!(<SETTINGS> "Settings json string or path to json file")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    arg!(<RAND_HASH> "A string used in seed generation")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(arg!(<START_NONCE> "Starting nonce").value_parser(clap::value_parser!(u64)))
+                .arg(
+                    arg!(<NUM_NONCES> "Number of nonces to check")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    arg!([WASM] "Path to a wasm file. Required unless --native is set")
+                        .required_unless_present("native")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--native "Run settings.algorithm_id natively instead of in the WASM VM. Fuel consumed and runtime signature are always 0")
+                        .conflicts_with("WASM"),
+                )
+                .arg(
+                    arg!(--fuel [FUEL] "Optional maximum fuel parameter for WASM VM")
+                        .default_value("2000000000")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    arg!(--mem [MEM] "Optional maximum memory parameter for WASM VM")
+                        .default_value("1000000000")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    arg!(--workers [WORKERS] "Number of worker threads")
+                        .default_value("1")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("verify_batch")
+                .about("Verifies Merkle proofs of a batch against a claimed Merkle root")
+                .arg(
+                    arg!(<SETTINGS> "Settings json string or path to json file")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    arg!(<RAND_HASH> "A string used in seed generation")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    arg!(<MERKLE_ROOT> "Claimed Merkle root of the batch")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    arg!(<PROOFS> "Output of compute_batch or prove, or a list of Merkle proofs, as a json string or path to json file")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    arg!([WASM] "Optional path to a wasm file. If given, every leaf is recomputed and compared")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--"start-nonce" [START_NONCE] "Starting nonce of the batch")
+                        .default_value("0")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    arg!(--"batch-size" [BATCH_SIZE] "Optional batch size. If given, Merkle branches that are too deep are rejected")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    arg!(--fuel [FUEL] "Optional maximum fuel parameter for WASM VM")
+                        .default_value("2000000000")
+                        .value_parser(clap::value_parser!(u64)),
+                )
+                .arg(
+                    arg!(--mem [MEM] "Optional maximum memory parameter for WASM VM")
+                        .default_value("1000000000")
+                        .value_parser(clap::value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("prove")
+                .about("Generates Merkle proofs for sampled nonces from a batch artifact")
+                .arg(
+                    arg!(<ARTIFACT> "Path to a batch artifact written by compute_batch")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!(--sampled <SAMPLED_NONCES> "Sampled nonces for which to generate proofs")
+                        .required(true)
+                        .num_args(1..)
+                        .value_parser(clap::value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Runs as a daemon, accepting jobs as newline delimited JSON over a Unix socket")
+                .arg(
+                    arg!(<SOCKET> "Path of the Unix socket to listen on")
+                        .value_parser(clap::value_parser!(PathBuf)),
                 ),
         )
 }
 
 fn main() {
     let matches = cli().get_matches();
+    let json_errors = matches.get_flag("json-errors");
 
     if let Err(e) = match matches.subcommand() {
         Some(("compute_solution", sub_m)) => compute_solution(
             sub_m.get_one::<String>("SETTINGS").unwrap().clone(),
             sub_m.get_one::<String>("RAND_HASH").unwrap().clone(),
             *sub_m.get_one::<u64>("NONCE").unwrap(),
-            sub_m.get_one::<PathBuf>("WASM").unwrap().clone(),
+            sub_m.get_one::<PathBuf>("WASM").cloned(),
             *sub_m.get_one::<u64>("mem").unwrap(),
             *sub_m.get_one::<u64>("fuel").unwrap(),
         ),
@@ -115,14 +308,26 @@ fn main() {
             *sub_m.get_one::<u64>("NONCE").unwrap(),
             sub_m.get_one::<String>("SOLUTION").unwrap().clone(),
         ),
-
+        Some(("evaluate_solution", sub_m)) => evaluate_solution(
+            sub_m.get_one::<String>("SETTINGS").unwrap().clone(),
+            sub_m.get_one::<String>("RAND_HASH").unwrap().clone(),
+            *sub_m.get_one::<u64>("NONCE").unwrap(),
+            sub_m.get_one::<String>("SOLUTION").unwrap().clone(),
+        ),
+        Some(("export_instance", sub_m)) => export_instance(
+            sub_m.get_one::<String>("SETTINGS").unwrap().clone(),
+            sub_m.get_one::<String>("RAND_HASH").unwrap().clone(),
+            *sub_m.get_one::<u64>("NONCE").unwrap(),
+            sub_m.get_one::<String>("format").unwrap().clone(),
+            sub_m.get_one::<PathBuf>("output").cloned(),
+        ),
         Some(("compute_batch", sub_m)) => compute_batch(
             sub_m.get_one::<String>("SETTINGS").unwrap().clone(),
             sub_m.get_one::<String>("RAND_HASH").unwrap().clone(),
             *sub_m.get_one::<u64>("START_NONCE").unwrap(),
             *sub_m.get_one::<u64>("NUM_NONCES").unwrap(),
             *sub_m.get_one::<u64>("BATCH_SIZE").unwrap(),
-            sub_m.get_one::<PathBuf>("WASM").unwrap().clone(),
+            sub_m.get_one::<PathBuf>("WASM").cloned(),
             *sub_m.get_one::<u64>("mem").unwrap(),
             *sub_m.get_one::<u64>("fuel").unwrap(),
             sub_m
@@ -130,11 +335,52 @@ fn main() {
                 .map(|values| values.cloned().collect())
                 .unwrap_or_default(),
             *sub_m.get_one::<usize>("workers").unwrap(),
+            sub_m.get_one::<PathBuf>("artifact").cloned(),
+            sub_m.get_one::<PathBuf>("report").cloned(),
+            sub_m.get_one::<PathBuf>("checkpoint").cloned(),
+        ),
+        Some(("compute_many", sub_m)) => compute_many(
+            sub_m.get_one::<String>("MANIFEST").unwrap().clone(),
+            sub_m.get_one::<usize>("workers").cloned(),
+        ),
+        Some(("check_determinism", sub_m)) => check_determinism(
+            sub_m.get_one::<String>("SETTINGS").unwrap().clone(),
+            sub_m.get_one::<String>("RAND_HASH").unwrap().clone(),
+            *sub_m.get_one::<u64>("START_NONCE").unwrap(),
+            *sub_m.get_one::<u64>("NUM_NONCES").unwrap(),
+            sub_m.get_one::<PathBuf>("WASM").cloned(),
+            *sub_m.get_one::<u64>("mem").unwrap(),
+            *sub_m.get_one::<u64>("fuel").unwrap(),
+            *sub_m.get_one::<usize>("workers").unwrap(),
         ),
+        Some(("verify_batch", sub_m)) => verify_batch(
+            sub_m.get_one::<String>("SETTINGS").unwrap().clone(),
+            sub_m.get_one::<String>("RAND_HASH").unwrap().clone(),
+            sub_m.get_one::<String>("MERKLE_ROOT").unwrap().clone(),
+            sub_m.get_one::<String>("PROOFS").unwrap().clone(),
+            sub_m.get_one::<PathBuf>("WASM").cloned(),
+            *sub_m.get_one::<u64>("start-nonce").unwrap(),
+            sub_m.get_one::<u64>("batch-size").cloned(),
+            *sub_m.get_one::<u64>("mem").unwrap(),
+            *sub_m.get_one::<u64>("fuel").unwrap(),
+        ),
+        Some(("prove", sub_m)) => prove(
+            sub_m.get_one::<PathBuf>("ARTIFACT").unwrap().clone(),
+            sub_m
+                .get_many::<u64>("sampled")
+                .map(|values| values.cloned().collect())
+                .unwrap_or_default(),
+        ),
+        Some(("serve", sub_m)) => serve(sub_m.get_one::<PathBuf>("SOCKET").unwrap().clone()),
         _ => Err(anyhow!("Invalid subcommand")),
     } {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+        let e = WorkerError::from_anyhow(e);
+        if json_errors {
+            eprintln!("{}", jsonify(&e.to_json()));
+        } else {
+            eprintln!("Error: {}", e);
+        }
+        std::process::exit(e.exit_code());
     }
 }
 
@@ -142,29 +388,30 @@ fn compute_solution(
     settings: String,
     rand_hash: String,
     nonce: u64,
-    wasm_path: PathBuf,
+    wasm_path: Option<PathBuf>,
     max_memory: u64,
     max_fuel: u64,
 ) -> Result<()> {
-    let settings = load_settings(&settings);
-    let wasm = load_wasm(&wasm_path);
-
-    let (output_data, err_msg) = worker::compute_solution(
-        &settings,
-        &rand_hash,
-        nonce,
-        wasm.as_slice(),
-        max_memory,
-        max_fuel,
-    )?;
+    let settings = load_settings(&settings)?;
+    let (output_data, err_msg) = match wasm_path {
+        Some(wasm_path) => tig_worker::compute_solution(
+            &settings,
+            &rand_hash,
+            nonce,
+            load_wasm(&wasm_path)?.as_slice(),
+            max_memory,
+            max_fuel,
+        )?,
+        None => tig_worker::compute_solution_native(&settings, &rand_hash, nonce)?,
+    };
     println!("{}", jsonify(&output_data));
     if let Some(err_msg) = err_msg {
-        return Err(anyhow!("Runtime error: {}", err_msg));
-    } else if output_data.solution.len() == 0 {
-        return Err(anyhow!("No solution found"));
+        return Err(err_msg.into());
+    } else if output_data.solution.is_empty() {
+        return Err(WorkerError::NoSolutionFound.into());
     }
-    worker::verify_solution(&settings, &rand_hash, nonce, &output_data.solution)
-        .map_err(|e| anyhow!("Invalid solution: {}", e))
+    tig_worker::verify_solution(&settings, &rand_hash, nonce, &output_data.solution)?;
+    Ok(())
 }
 
 fn verify_solution(
@@ -173,16 +420,72 @@ fn verify_solution(
     nonce: u64,
     solution: String,
 ) -> Result<()> {
-    let settings = load_settings(&settings);
-    let solution = load_solution(&solution);
+    let settings = load_settings(&settings)?;
+    let solution = load_solution(&solution)?;
+
+    tig_worker::verify_solution(&settings, &rand_hash, nonce, &solution)?;
+    println!("Solution is valid");
+    Ok(())
+}
 
-    match worker::verify_solution(&settings, &rand_hash, nonce, &solution) {
-        Ok(()) => {
-            println!("Solution is valid");
-            Ok(())
+fn evaluate_solution(
+    settings: String,
+    rand_hash: String,
+    nonce: u64,
+    solution: String,
+) -> Result<()> {
+    let settings = load_settings(&settings)?;
+    let solution = load_solution(&solution)?;
+
+    let quality = tig_worker::evaluate_solution(&settings, &rand_hash, nonce, &solution)?;
+    let result = json!({
+        "valid": quality.is_valid(),
+        "quality": quality,
+    });
+    println!("{}", jsonify(&result));
+    Ok(())
+}
+
+fn export_instance(
+    settings: String,
+    rand_hash: String,
+    nonce: u64,
+    format: String,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let settings = load_settings(&settings)?;
+    let format = format
+        .parse()
+        .map_err(|e: anyhow::Error| WorkerError::InvalidInput { msg: e.to_string() })?;
+
+    let files = tig_worker::export_instance(&settings, &rand_hash, nonce, format)?;
+    match output {
+        Some(output) => {
+            let mut paths = Vec::new();
+            for file in files {
+                let path = PathBuf::from(format!("{}.{}", output.display(), file.extension));
+                fs::write(&path, &file.data).map_err(|e| WorkerError::Io {
+                    path: path.display().to_string(),
+                    msg: e.to_string(),
+                })?;
+                paths.push(path);
+            }
+            println!("{}", jsonify(&json!({ "paths": paths })));
+        }
+        None if files.len() == 1 => {
+            std::io::stdout().write_all(&files[0].data)?;
+        }
+        None => {
+            return Err(WorkerError::InvalidInput {
+                msg: format!(
+                    "Format {} writes several files. --output is required",
+                    format
+                ),
+            }
+            .into())
         }
-        Err(e) => Err(anyhow!("Invalid solution: {}", e)),
     }
+    Ok(())
 }
 
 fn compute_batch(
@@ -191,154 +494,274 @@ fn compute_batch(
     start_nonce: u64,
     num_nonces: u64,
     batch_size: u64,
-    wasm_path: PathBuf,
+    wasm_path: Option<PathBuf>,
     max_memory: u64,
     max_fuel: u64,
     sampled_nonces: Vec<u64>,
     num_workers: usize,
+    artifact_path: Option<PathBuf>,
+    report_path: Option<PathBuf>,
+    checkpoint_dir: Option<PathBuf>,
 ) -> Result<()> {
-    if num_nonces == 0 || batch_size < num_nonces {
-        return Err(anyhow!(
-            "Invalid number of nonces. Must be non-zero and less than batch size"
-        ));
-    }
-    let end_nonce = start_nonce + num_nonces;
-    for &nonce in &sampled_nonces {
-        if nonce < start_nonce || nonce >= end_nonce {
-            return Err(anyhow!(
-                "Sampled nonce {} is out of range [{}, {})",
-                nonce,
-                start_nonce,
-                end_nonce
-            ));
-        }
+    // a checkpoint can only be resumed with the same wasm. Native algorithms have nothing to
+    // identify their build by, so --checkpoint conflicts with --native
+    let wasm_hash = match (&checkpoint_dir, &wasm_path) {
+        (Some(_), Some(wasm_path)) => Some(tig_worker::wasm_hash(&load_wasm(wasm_path)?)),
+        _ => None,
+    };
+    let job = BatchJob {
+        settings: load_settings(&settings)?,
+        rand_hash,
+        start_nonce,
+        num_nonces,
+        batch_size,
+        max_memory,
+        max_fuel,
+        sampled_nonces,
+        num_workers,
+        artifact_path,
+        report_path,
+        checkpoint_dir,
+        wasm_hash,
+    };
+    job.validate()?;
+    let runner = load_runner(wasm_path)?;
+
+    let runtime = Runtime::new()?;
+    let result = runtime.block_on(tig_worker::compute_batch(&job, runner, |_, _| {}))?;
+
+    println!("{}", jsonify(&result));
+    Ok(())
+}
+
+fn compute_many(manifest: String, num_workers: Option<usize>) -> Result<()> {
+    let mut manifest = load_manifest(&manifest)?;
+    if let Some(num_workers) = num_workers {
+        manifest.num_workers = num_workers;
     }
-    if batch_size == 0 || (batch_size & (batch_size - 1)) != 0 {
-        return Err(anyhow!("Batch size must be a power of 2"));
+    manifest.validate()?;
+    let runners = manifest.load_runners()?;
+
+    // results are printed as each job finishes
+    let mut first_error = None;
+    let runtime = Runtime::new()?;
+    runtime.block_on(tig_worker::compute_many(
+        &manifest,
+        runners,
+        |job, result| {
+            let line = match result {
+                Ok(result) => json!({ "id": job.id, "result": result }),
+                Err(e) => {
+                    let e = WorkerError::from_anyhow(e);
+                    let line = json!({ "id": job.id, "error": e.to_json() });
+                    first_error.get_or_insert(e);
+                    line
+                }
+            };
+            println!("{}", jsonify(&line));
+        },
+    ))?;
+
+    match first_error {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
     }
+}
 
-    let settings = Arc::new(load_settings(&settings));
-    let wasm = Arc::new(load_wasm(&wasm_path));
+fn check_determinism(
+    settings: String,
+    rand_hash: String,
+    start_nonce: u64,
+    num_nonces: u64,
+    wasm_path: Option<PathBuf>,
+    max_memory: u64,
+    max_fuel: u64,
+    num_workers: usize,
+) -> Result<()> {
+    let check = DeterminismCheck {
+        settings: load_settings(&settings)?,
+        rand_hash,
+        start_nonce,
+        num_nonces,
+        max_memory,
+        max_fuel,
+        num_workers,
+    };
+    let runner = load_runner(wasm_path)?;
 
     let runtime = Runtime::new()?;
+    let mismatches = runtime.block_on(tig_worker::check_determinism(&check, runner))?;
+    let nonces: Vec<u64> = mismatches.iter().map(|m| m.nonce).collect();
+    let result = json!({
+        "num_nonces": num_nonces,
+        "nondeterministic_nonces": nonces,
+        "mismatches": mismatches,
+    });
 
-    runtime.block_on(async {
-        let mut output_data_map = HashMap::new();
-        let mut hashes = vec![MerkleHash::null(); num_nonces as usize];
-        let mut solution_nonces = Vec::new();
-
-        // Create a stream of nonces and process them concurrently
-        let results = stream::iter(start_nonce..end_nonce)
-            .map(|nonce| {
-                let settings = Arc::clone(&settings);
-                let wasm = Arc::clone(&wasm);
-                let rand_hash = rand_hash.clone();
-                let sampled_nonces = sampled_nonces.clone();
-                tokio::spawn(async move {
-                    let (output_data, err_msg) = worker::compute_solution(
-                        &settings,
-                        &rand_hash,
-                        nonce,
-                        wasm.as_slice(),
-                        max_memory,
-                        max_fuel,
-                    )?;
-                    let is_solution = err_msg.is_none()
-                        && worker::verify_solution(
-                            &settings,
-                            &rand_hash,
-                            nonce,
-                            &output_data.solution,
-                        )
-                        .is_ok();
-                    let hash = MerkleHash::from(output_data.clone());
-                    // only keep the data if required
-                    let output_data = if sampled_nonces.contains(&nonce) {
-                        Some(output_data)
-                    } else {
-                        None
-                    };
-                    Ok::<(u64, Option<worker::OutputData>, MerkleHash, bool), anyhow::Error>((
-                        nonce,
-                        output_data,
-                        hash,
-                        is_solution,
-                    ))
-                })
-            })
-            .buffer_unordered(num_workers)
-            .collect::<Vec<_>>()
-            .await;
-
-        for result in results {
-            let (nonce, output_data, hash, is_solution) = result??;
-            if let Some(output_data) = output_data {
-                output_data_map.insert(nonce, output_data);
-            }
-            if is_solution {
-                solution_nonces.push(nonce);
-            }
-            *hashes.get_mut((nonce - start_nonce) as usize).unwrap() = hash;
-        }
+    println!("{}", jsonify(&result));
+    if nonces.is_empty() {
+        Ok(())
+    } else {
+        Err(WorkerError::Nondeterministic { nonces }.into())
+    }
+}
+
+fn verify_batch(
+    settings: String,
+    rand_hash: String,
+    merkle_root: String,
+    proofs: String,
+    wasm_path: Option<PathBuf>,
+    start_nonce: u64,
+    batch_size: Option<u64>,
+    max_memory: u64,
+    max_fuel: u64,
+) -> Result<()> {
+    let audit = BatchAudit {
+        settings: load_settings(&settings)?,
+        rand_hash,
+        merkle_root: merkle_root.parse().map_err(|e| WorkerError::InvalidInput {
+            msg: format!("Failed to parse merkle root: {}", e),
+        })?,
+        start_nonce,
+        batch_size,
+        max_memory,
+        max_fuel,
+    };
+    let proofs = load_proofs(&proofs)?;
+    let module = match wasm_path {
+        Some(wasm_path) => Some(tig_worker::compile_module(&load_wasm(&wasm_path)?)?),
+        None => None,
+    };
 
-        let tree = MerkleTree::new(hashes, batch_size as usize)?;
-        let merkle_root = tree.calc_merkle_root();
+    let nonces = audit.verify(&proofs, module.as_ref());
+    let failed_nonces: Vec<u64> = nonces
+        .iter()
+        .filter(|n| !n.is_ok())
+        .map(|n| n.nonce)
+        .collect();
+    let result = json!({
+        "valid": failed_nonces.is_empty(),
+        "nonces": nonces,
+    });
 
-        let mut merkle_proofs = Vec::new();
-        for (nonce, output_data) in output_data_map {
-            let branch = tree.calc_merkle_branch((nonce - start_nonce) as usize)?;
-            merkle_proofs.push(MerkleProof {
-                leaf: output_data,
-                branch: Some(branch),
-            });
+    println!("{}", jsonify(&result));
+    if failed_nonces.is_empty() {
+        Ok(())
+    } else {
+        Err(WorkerError::InvalidBatch {
+            nonces: failed_nonces,
         }
+        .into())
+    }
+}
 
-        let result = json!({
-            "merkle_root": merkle_root,
-            "merkle_proofs": merkle_proofs,
-            "solution_nonces": solution_nonces,
-        });
+fn prove(artifact_path: PathBuf, sampled_nonces: Vec<u64>) -> Result<()> {
+    let artifact = BatchArtifact::load(&artifact_path)?;
+    let merkle_proofs = artifact.prove(&sampled_nonces)?;
 
-        println!("{}", jsonify(&result));
-        Ok(())
+    let result = BatchResult {
+        merkle_root: artifact.merkle_root(),
+        merkle_proofs,
+        solution_nonces: artifact.solution_nonces,
+    };
+
+    println!("{}", jsonify(&result));
+    Ok(())
+}
+
+fn serve(socket_path: PathBuf) -> Result<()> {
+    // remove a socket left behind by a previous daemon
+    if fs::metadata(&socket_path).is_ok_and(|m| m.file_type().is_socket()) {
+        fs::remove_file(&socket_path)?;
+    }
+
+    let runtime = Runtime::new()?;
+    runtime.block_on(async {
+        let listener = UnixListener::bind(&socket_path).map_err(|e| WorkerError::Io {
+            path: socket_path.display().to_string(),
+            msg: e.to_string(),
+        })?;
+        eprintln!("Listening on {}", socket_path.display());
+        tig_worker::serve(listener, ModuleCache::default()).await
     })
 }
 
-fn load_settings(settings: &str) -> BenchmarkSettings {
+fn load_settings(settings: &str) -> WorkerResult<BenchmarkSettings> {
     let settings = if settings.ends_with(".json") {
-        fs::read_to_string(settings).unwrap_or_else(|_| {
-            eprintln!("Failed to read settings file: {}", settings);
-            std::process::exit(1);
-        })
+        read_to_string(settings)?
     } else {
         settings.to_string()
     };
 
-    dejsonify::<BenchmarkSettings>(&settings).unwrap_or_else(|_| {
-        eprintln!("Failed to parse settings");
-        std::process::exit(1);
+    dejsonify::<BenchmarkSettings>(&settings).map_err(|e| WorkerError::InvalidSettings {
+        msg: format!("Failed to parse settings: {}", e),
     })
 }
 
-fn load_solution(solution: &str) -> worker::Solution {
+fn load_solution(solution: &str) -> WorkerResult<Solution> {
     let solution = if solution.ends_with(".json") {
-        fs::read_to_string(&solution).unwrap_or_else(|_| {
-            eprintln!("Failed to read solution file: {}", solution);
-            std::process::exit(1);
-        })
+        read_to_string(solution)?
     } else {
         solution.to_string()
     };
 
-    dejsonify::<worker::Solution>(&solution).unwrap_or_else(|_| {
-        eprintln!("Failed to parse solution");
-        std::process::exit(1);
+    dejsonify::<Solution>(&solution).map_err(|e| WorkerError::InvalidInput {
+        msg: format!("Failed to parse solution: {}", e),
+    })
+}
+
+fn load_manifest(manifest: &str) -> WorkerResult<Manifest> {
+    let manifest = if manifest.ends_with(".json") {
+        read_to_string(manifest)?
+    } else {
+        manifest.to_string()
+    };
+
+    dejsonify::<Manifest>(&manifest).map_err(|e| WorkerError::InvalidInput {
+        msg: format!("Failed to parse manifest: {}", e),
+    })
+}
+
+fn load_proofs(proofs: &str) -> WorkerResult<BatchProofs> {
+    let proofs = if proofs.ends_with(".json") {
+        read_to_string(proofs)?
+    } else {
+        proofs.to_string()
+    };
+
+    dejsonify::<BatchProofs>(&proofs)
+        .or_else(|_| {
+            dejsonify::<Vec<MerkleProof>>(&proofs).map(|merkle_proofs| BatchProofs {
+                merkle_proofs,
+                solution_nonces: None,
+            })
+        })
+        .map_err(|e| WorkerError::InvalidInput {
+            msg: format!("Failed to parse proofs: {}", e),
+        })
+}
+
+// no wasm means the algorithm is run natively
+fn load_runner(wasm_path: Option<PathBuf>) -> WorkerResult<AlgorithmRunner> {
+    match wasm_path {
+        Some(wasm_path) => Ok(AlgorithmRunner::Wasm(Arc::new(tig_worker::compile_module(
+            &load_wasm(&wasm_path)?,
+        )?))),
+        None => Ok(AlgorithmRunner::Native),
+    }
+}
+
+fn load_wasm(wasm_path: &PathBuf) -> WorkerResult<Vec<u8>> {
+    fs::read(wasm_path).map_err(|e| WorkerError::Io {
+        path: wasm_path.display().to_string(),
+        msg: e.to_string(),
     })
 }
 
-fn load_wasm(wasm_path: &PathBuf) -> Vec<u8> {
-    fs::read(wasm_path).unwrap_or_else(|_| {
-        eprintln!("Failed to read wasm file: {}", wasm_path.display());
-        std::process::exit(1);
+fn read_to_string(path: &str) -> WorkerResult<String> {
+    fs::read_to_string(path).map_err(|e| WorkerError::Io {
+        path: path.to_string(),
+        msg: e.to_string(),
     })
 }
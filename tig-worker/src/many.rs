@@ -0,0 +1,240 @@
+use crate::{
+    batch::{compute_nonce, BatchJob, BatchResult, BatchState},
+    engine::{compile_module, wasm_hash},
+    error::{WorkerError, WorkerResult},
+    worker::AlgorithmRunner,
+};
+use anyhow::{anyhow, Result};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashSet, VecDeque},
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Several batches to compute with one shared pool of workers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    #[serde(default = "default_num_workers")]
+    pub num_workers: usize,
+    pub jobs: Vec<ManifestJob>,
+}
+
+/// A batch in a manifest. Its `num_workers` is ignored, as workers are shared
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestJob {
+    /// Chosen by the caller. The result of the batch is tagged with it
+    pub id: String,
+    /// If not given, `settings.algorithm_id` is run natively
+    #[serde(default)]
+    pub wasm_path: Option<PathBuf>,
+    /// Share of the workers relative to other jobs. A job with weight 2 has twice as many of its
+    /// nonces started as a job with weight 1, until either runs out of nonces
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(flatten)]
+    pub batch: BatchJob,
+}
+
+fn default_num_workers() -> usize {
+    1
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl Manifest {
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        for job in self.jobs.iter() {
+            if !ids.insert(job.id.as_str()) {
+                return Err(WorkerError::InvalidInput {
+                    msg: format!("Duplicate job id {}", job.id),
+                }
+                .into());
+            }
+            if job.weight == 0 {
+                return Err(WorkerError::InvalidInput {
+                    msg: format!("Weight of job {} must be at least 1", job.id),
+                }
+                .into());
+            }
+            if job.wasm_path.is_none() && job.batch.checkpoint_dir.is_some() {
+                return Err(WorkerError::InvalidInput {
+                    msg: format!("Job {} is run natively and cannot be checkpointed", job.id),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles the wasm of each job into the runners expected by `compute_many`. The
+    /// `wasm_hash` of each job with a wasm is set to the hash of that wasm, so a checkpoint is
+    /// never resumed with a different one
+    pub fn load_runners(&mut self) -> WorkerResult<Vec<AlgorithmRunner>> {
+        let mut runners = Vec::with_capacity(self.jobs.len());
+        for job in self.jobs.iter_mut() {
+            let Some(wasm_path) = &job.wasm_path else {
+                runners.push(AlgorithmRunner::Native);
+                continue;
+            };
+            let wasm = fs::read(wasm_path).map_err(|e| WorkerError::Io {
+                path: wasm_path.display().to_string(),
+                msg: e.to_string(),
+            })?;
+            job.batch.set_wasm_hash(wasm_hash(&wasm))?;
+            runners.push(AlgorithmRunner::Wasm(Arc::new(compile_module(&wasm)?)));
+        }
+        Ok(runners)
+    }
+}
+
+/// Computes every job in `manifest` with `manifest.num_workers` workers shared between them.
+/// `runners` are the runners for each job, in the same order. Nonces are started in smooth
+/// weighted round robin order, so a finished job frees its workers for the remaining ones.
+/// `on_result` is called for each job as soon as its last nonce finishes. A job that fails does
+/// not stop the others. A nonce whose algorithm traps or runs out of fuel does not fail its job,
+/// it is reported with that outcome like any other nonce
+pub async fn compute_many<F>(
+    manifest: &Manifest,
+    runners: Vec<AlgorithmRunner>,
+    mut on_result: F,
+) -> Result<()>
+where
+    F: FnMut(&ManifestJob, Result<BatchResult>),
+{
+    manifest.validate()?;
+    if runners.len() != manifest.jobs.len() {
+        return Err(anyhow!(
+            "Expected {} runners, got {}",
+            manifest.jobs.len(),
+            runners.len()
+        ));
+    }
+
+    let mut states = Vec::new();
+    let mut queues = Vec::new();
+    for job in manifest.jobs.iter() {
+        match BatchState::new(&job.batch) {
+            Ok(state) => {
+                let remaining = state.remaining_nonces();
+                if remaining.is_empty() {
+                    on_result(job, state.finish());
+                    states.push(None);
+                    queues.push(VecDeque::new());
+                } else {
+                    states.push(Some(state));
+                    queues.push(remaining.into());
+                }
+            }
+            Err(e) => {
+                on_result(job, Err(e));
+                states.push(None);
+                queues.push(VecDeque::new());
+            }
+        }
+    }
+    let mut num_remaining: Vec<usize> = queues.iter().map(|q| q.len()).collect();
+    let failed: Vec<Arc<AtomicBool>> = manifest
+        .jobs
+        .iter()
+        .map(|_| Arc::new(AtomicBool::new(false)))
+        .collect();
+    let settings: Vec<_> = manifest
+        .jobs
+        .iter()
+        .map(|job| Arc::new(job.batch.settings.clone()))
+        .collect();
+
+    let schedule =
+        WeightedSchedule::new(queues, manifest.jobs.iter().map(|job| job.weight).collect());
+    let mut results = stream::iter(schedule)
+        .map(|(idx, nonce)| {
+            let job = &manifest.jobs[idx].batch;
+            let settings = Arc::clone(&settings[idx]);
+            let failed = Arc::clone(&failed[idx]);
+            let runner = runners[idx].clone();
+            let rand_hash = job.rand_hash.clone();
+            let (max_memory, max_fuel) = (job.max_memory, job.max_fuel);
+            tokio::task::spawn_blocking(move || {
+                // skip the remaining nonces of a failed job
+                let result = match failed.load(Ordering::Relaxed) {
+                    true => None,
+                    false => Some(compute_nonce(
+                        &runner, &settings, &rand_hash, nonce, max_memory, max_fuel,
+                    )),
+                };
+                (idx, result)
+            })
+        })
+        .buffer_unordered(manifest.num_workers.max(1));
+
+    while let Some(result) = results.next().await {
+        let (idx, result) = result?;
+        num_remaining[idx] -= 1;
+        if let Some(result) = result {
+            if let Some(state) = states[idx].as_mut() {
+                if let Err(e) = result
+                    .map_err(anyhow::Error::from)
+                    .and_then(|(output_data, report)| state.insert(output_data, report))
+                {
+                    failed[idx].store(true, Ordering::Relaxed);
+                    states[idx] = None;
+                    on_result(&manifest.jobs[idx], Err(e));
+                }
+            }
+        }
+        if num_remaining[idx] == 0 {
+            if let Some(state) = states[idx].take() {
+                on_result(&manifest.jobs[idx], state.finish());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Interleaves the nonces of several jobs by weight, using smooth weighted round robin
+struct WeightedSchedule {
+    queues: Vec<VecDeque<u64>>,
+    weights: Vec<i64>,
+    current: Vec<i64>,
+}
+
+impl WeightedSchedule {
+    fn new(queues: Vec<VecDeque<u64>>, weights: Vec<u32>) -> Self {
+        Self {
+            current: vec![0; queues.len()],
+            weights: weights.into_iter().map(|w| w as i64).collect(),
+            queues,
+        }
+    }
+}
+
+impl Iterator for WeightedSchedule {
+    type Item = (usize, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut total = 0;
+        let mut best: Option<usize> = None;
+        for idx in 0..self.queues.len() {
+            if self.queues[idx].is_empty() {
+                continue;
+            }
+            total += self.weights[idx];
+            self.current[idx] += self.weights[idx];
+            if best.is_none_or(|b| self.current[idx] > self.current[b]) {
+                best = Some(idx);
+            }
+        }
+        let best = best?;
+        self.current[best] -= total;
+        self.queues[best].pop_front().map(|nonce| (best, nonce))
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    error::{WorkerError, WorkerResult},
+    worker::{BenchmarkSettings, OutputData, Solution},
+};
+use std::panic::{self, AssertUnwindSafe};
+use tig_challenges::ChallengeTrait;
+use tig_utils::{dejsonify, jsonify};
+
+macro_rules! native_registry {
+    ($($challenge:ident => [$($algorithm:ident),*],)*) => {
+        /// Algorithm ids that can be run with `compute_solution_native`, generated from the
+        /// `c00X_a00Y` aliases in tig-algorithms
+        pub const NATIVE_ALGORITHMS: &[&str] = &[$($(stringify!($algorithm),)*)*];
+
+        fn solve_native(
+            algorithm_id: &str,
+            seed: [u8; 32],
+            difficulty: &Vec<i32>,
+        ) -> WorkerResult<anyhow::Result<Option<Solution>>> {
+            match algorithm_id {
+                $($(stringify!($algorithm) => {
+                    let challenge =
+                        tig_challenges::$challenge::Challenge::generate_instance_from_vec(
+                            seed, difficulty,
+                        )
+                        .map_err(|e| WorkerError::InvalidSettings {
+                            msg: format!("Failed to generate instance: {}", e),
+                        })?;
+                    Ok(tig_algorithms::$challenge::$algorithm::solve_challenge(&challenge)
+                        .map(|solution| solution.map(|s| to_solution(&s))))
+                })*)*
+                _ => Err(WorkerError::UnknownAlgorithm {
+                    algorithm_id: algorithm_id.to_string(),
+                }),
+            }
+        }
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/native_registry.rs"));
+
+// same conversion as a solution returned from wasm, which is compressed as json
+fn to_solution<T: serde::Serialize>(solution: &T) -> Solution {
+    dejsonify(&jsonify(solution)).unwrap()
+}
+
+/// Runs `settings.algorithm_id` natively instead of in the WASM VM, for developing and profiling
+/// algorithms. `fuel_consumed` and `runtime_signature` of the output data are always 0
+pub fn compute_solution_native(
+    settings: &BenchmarkSettings,
+    rand_hash: &String,
+    nonce: u64,
+) -> WorkerResult<(OutputData, Option<WorkerError>)> {
+    let algorithm_id = settings.algorithm_id.as_str();
+    if !NATIVE_ALGORITHMS.contains(&algorithm_id) {
+        return Err(WorkerError::UnknownAlgorithm {
+            algorithm_id: algorithm_id.to_string(),
+        });
+    }
+    if !algorithm_id.starts_with(&format!("{}_", settings.challenge_id)) {
+        return Err(WorkerError::InvalidSettings {
+            msg: format!(
+                "Algorithm {} is not for challenge {}",
+                algorithm_id, settings.challenge_id
+            ),
+        });
+    }
+    let seed = settings.calc_seed(rand_hash, nonce);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        solve_native(algorithm_id, seed, &settings.difficulty)
+    }))
+    .unwrap_or_else(|e| {
+        let msg = e
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic".to_string());
+        Ok(Err(anyhow::anyhow!("Algorithm panicked: {}", msg)))
+    })?;
+
+    let (solution, err_msg) = match result {
+        Ok(solution) => (solution.unwrap_or_default(), None),
+        Err(e) => (
+            Solution::new(),
+            Some(WorkerError::AlgorithmTrap { msg: e.to_string() }),
+        ),
+    };
+    Ok((
+        OutputData {
+            nonce,
+            runtime_signature: 0,
+            fuel_consumed: 0,
+            solution,
+        },
+        err_msg,
+    ))
+}
@@ -0,0 +1,91 @@
+use crate::error::{WorkerError, WorkerResult};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+use tig_utils::jsonify;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NonceOutcome {
+    Solution,
+    NoSolution,
+    InvalidSolution,
+    OutOfFuel,
+    OutOfMemory,
+    Trap,
+}
+
+impl NonceOutcome {
+    /// Outcome of a nonce given the algorithm's runtime error, if any, and whether its solution
+    /// passed verification
+    pub fn new(err: Option<&WorkerError>, is_empty: bool, is_valid: bool) -> Self {
+        match err {
+            Some(WorkerError::FuelExhausted) => NonceOutcome::OutOfFuel,
+            Some(WorkerError::MemoryLimitExceeded) => NonceOutcome::OutOfMemory,
+            Some(WorkerError::InvalidSolution { .. }) => NonceOutcome::InvalidSolution,
+            Some(_) => NonceOutcome::Trap,
+            None if is_empty => NonceOutcome::NoSolution,
+            None if is_valid => NonceOutcome::Solution,
+            None => NonceOutcome::InvalidSolution,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NonceOutcome::Solution => "solution",
+            NonceOutcome::NoSolution => "no_solution",
+            NonceOutcome::InvalidSolution => "invalid_solution",
+            NonceOutcome::OutOfFuel => "out_of_fuel",
+            NonceOutcome::OutOfMemory => "out_of_memory",
+            NonceOutcome::Trap => "trap",
+        }
+    }
+}
+
+/// What happened when computing a single nonce of a batch
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NonceReport {
+    pub nonce: u64,
+    pub fuel_consumed: u64,
+    pub runtime_signature: u64,
+    pub outcome: NonceOutcome,
+    /// Wall-clock time to compute and verify the nonce
+    pub elapsed_ms: f64,
+}
+
+impl NonceReport {
+    pub fn is_solution(&self) -> bool {
+        self.outcome == NonceOutcome::Solution
+    }
+}
+
+/// Writes one row per nonce. A path ending in `.csv` is written as CSV with a header, anything
+/// else as JSON lines
+pub fn write_report(path: &Path, reports: &[NonceReport]) -> WorkerResult<()> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let mut lines = Vec::with_capacity(reports.len() + 1);
+    if is_csv {
+        lines.push("nonce,fuel_consumed,runtime_signature,outcome,elapsed_ms".to_string());
+    }
+    for report in reports {
+        lines.push(if is_csv {
+            format!(
+                "{},{},{},{},{}",
+                report.nonce,
+                report.fuel_consumed,
+                report.runtime_signature,
+                report.outcome.as_str(),
+                report.elapsed_ms
+            )
+        } else {
+            jsonify(report)
+        });
+    }
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    fs::write(path, contents).map_err(|e| WorkerError::Io {
+        path: path.display().to_string(),
+        msg: e.to_string(),
+    })
+}
@@ -0,0 +1,309 @@
+use crate::{
+    batch::{self, BatchJob, DEFAULT_MAX_FUEL, DEFAULT_MAX_MEMORY},
+    engine,
+    error::WorkerError,
+    worker::{self, AlgorithmRunner, BenchmarkSettings, Solution},
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tig_utils::{dejsonify, jsonify};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::mpsc::{self, UnboundedSender},
+};
+
+/// A job sent to the daemon as a single line of JSON
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobRequest {
+    /// Chosen by the client. Every event for this job is tagged with it
+    pub id: String,
+    #[serde(flatten)]
+    pub job: Job,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Job {
+    ComputeSolution {
+        settings: BenchmarkSettings,
+        rand_hash: String,
+        nonce: u64,
+        algorithm_id: String,
+        /// Only required the first time `algorithm_id` is seen
+        #[serde(default)]
+        wasm_path: Option<PathBuf>,
+        #[serde(default = "default_max_memory")]
+        max_memory: u64,
+        #[serde(default = "default_max_fuel")]
+        max_fuel: u64,
+    },
+    VerifySolution {
+        settings: BenchmarkSettings,
+        rand_hash: String,
+        nonce: u64,
+        solution: Solution,
+    },
+    ComputeBatch {
+        algorithm_id: String,
+        /// Only required the first time `algorithm_id` is seen
+        #[serde(default)]
+        wasm_path: Option<PathBuf>,
+        #[serde(flatten)]
+        batch: BatchJob,
+    },
+}
+
+fn default_max_memory() -> u64 {
+    DEFAULT_MAX_MEMORY
+}
+
+fn default_max_fuel() -> u64 {
+    DEFAULT_MAX_FUEL
+}
+
+/// Streamed back to the client as a single line of JSON. Every job ends with either a `result`
+/// or an `error` event. `compute_batch` jobs emit a `progress` event as each nonce finishes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JobEvent {
+    Progress {
+        id: String,
+        nonce: u64,
+        is_solution: bool,
+    },
+    Result {
+        id: String,
+        result: Value,
+    },
+    /// `error` is the same JSON object that `--json-errors` prints
+    Error {
+        id: String,
+        error: Value,
+    },
+}
+
+/// A compiled module and the wasm it was compiled from
+#[derive(Clone)]
+pub struct CachedModule {
+    pub module: Arc<wasmi::Module>,
+    pub wasm_path: PathBuf,
+    pub wasm_hash: String,
+}
+
+/// Compiled modules by algorithm id, kept for the lifetime of the daemon
+#[derive(Default, Clone)]
+pub struct ModuleCache(Arc<Mutex<HashMap<String, CachedModule>>>);
+
+impl ModuleCache {
+    /// Returns the module of `algorithm_id`, compiling it from `wasm_path` the first time. A
+    /// different `wasm_path` for a cached algorithm is rejected
+    pub async fn get_or_compile(
+        &self,
+        algorithm_id: &str,
+        wasm_path: Option<&Path>,
+    ) -> Result<CachedModule> {
+        if let Some(cached) = self.0.lock().unwrap().get(algorithm_id) {
+            return match wasm_path {
+                Some(wasm_path) if wasm_path != cached.wasm_path => {
+                    Err(WorkerError::InvalidInput {
+                        msg: format!(
+                            "Algorithm {} is cached from '{}', not '{}'",
+                            algorithm_id,
+                            cached.wasm_path.display(),
+                            wasm_path.display()
+                        ),
+                    }
+                    .into())
+                }
+                _ => Ok(cached.clone()),
+            };
+        }
+        let wasm_path = wasm_path
+            .ok_or_else(|| WorkerError::InvalidInput {
+                msg: format!(
+                    "Algorithm {} is not cached and no wasm_path was given",
+                    algorithm_id
+                ),
+            })?
+            .to_path_buf();
+        let cached = tokio::task::spawn_blocking(move || {
+            let wasm = fs::read(&wasm_path).map_err(|e| WorkerError::Io {
+                path: wasm_path.display().to_string(),
+                msg: e.to_string(),
+            })?;
+            Ok::<_, WorkerError>(CachedModule {
+                module: Arc::new(engine::compile_module(&wasm)?),
+                wasm_hash: engine::wasm_hash(&wasm),
+                wasm_path,
+            })
+        })
+        .await??;
+        // keep the module of a concurrent job that compiled it first
+        Ok(self
+            .0
+            .lock()
+            .unwrap()
+            .entry(algorithm_id.to_string())
+            .or_insert(cached)
+            .clone())
+    }
+}
+
+/// Accepts connections until the listener fails. Each connection can submit any number of
+/// jobs, which run concurrently and share `modules`
+pub async fn serve(listener: UnixListener, modules: ModuleCache) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(handle_connection(stream, modules.clone()));
+    }
+}
+
+async fn handle_connection(stream: UnixStream, modules: ModuleCache) {
+    let (reader, mut writer) = stream.into_split();
+    let (tx, mut rx) = mpsc::unbounded_channel::<JobEvent>();
+    let writer_task = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            let line = format!("{}\n", jsonify(&event));
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match dejsonify::<JobRequest>(&line) {
+            Ok(request) => {
+                tokio::spawn(run_job(request, modules.clone(), tx.clone()));
+            }
+            Err(e) => {
+                let id = dejsonify::<Value>(&line)
+                    .ok()
+                    .and_then(|v| v.get("id").and_then(Value::as_str).map(str::to_string))
+                    .unwrap_or_default();
+                let _ = tx.send(JobEvent::Error {
+                    id,
+                    error: WorkerError::InvalidInput {
+                        msg: format!("Invalid job: {}", e),
+                    }
+                    .to_json(),
+                });
+            }
+        }
+    }
+    // writer finishes once every job of this connection has sent its last event
+    drop(tx);
+    let _ = writer_task.await;
+}
+
+async fn run_job(request: JobRequest, modules: ModuleCache, tx: UnboundedSender<JobEvent>) {
+    let id = request.id;
+    let event = match execute_job(&id, request.job, &modules, &tx).await {
+        Ok(result) => JobEvent::Result { id, result },
+        Err(e) => JobEvent::Error {
+            id,
+            error: WorkerError::from_anyhow(e).to_json(),
+        },
+    };
+    let _ = tx.send(event);
+}
+
+async fn execute_job(
+    id: &str,
+    job: Job,
+    modules: &ModuleCache,
+    tx: &UnboundedSender<JobEvent>,
+) -> Result<Value> {
+    match job {
+        Job::ComputeSolution {
+            settings,
+            rand_hash,
+            nonce,
+            algorithm_id,
+            wasm_path,
+            max_memory,
+            max_fuel,
+        } => {
+            let module = modules
+                .get_or_compile(&algorithm_id, wasm_path.as_deref())
+                .await?
+                .module;
+            tokio::task::spawn_blocking(move || {
+                let (output_data, err_msg) = worker::compute_solution_with_module(
+                    &settings, &rand_hash, nonce, &module, max_memory, max_fuel,
+                )?;
+                let error = match err_msg {
+                    Some(err_msg) => Some(err_msg.to_string()),
+                    None if output_data.solution.is_empty() => {
+                        Some(WorkerError::NoSolutionFound.to_string())
+                    }
+                    None => {
+                        worker::verify_solution(&settings, &rand_hash, nonce, &output_data.solution)
+                            .err()
+                            .map(|e| e.to_string())
+                    }
+                };
+                Ok(json!({
+                    "output_data": output_data,
+                    "error": error,
+                }))
+            })
+            .await?
+        }
+        Job::VerifySolution {
+            settings,
+            rand_hash,
+            nonce,
+            solution,
+        } => {
+            let error = tokio::task::spawn_blocking(move || {
+                worker::verify_solution(&settings, &rand_hash, nonce, &solution).err()
+            })
+            .await?;
+            let reason = match &error {
+                Some(WorkerError::InvalidSolution { reason }) => Some(reason.clone()),
+                _ => None,
+            };
+            Ok(json!({
+                "is_valid": error.is_none(),
+                "error": error.map(|e| e.to_string()),
+                "reason": reason,
+            }))
+        }
+        Job::ComputeBatch {
+            algorithm_id,
+            wasm_path,
+            mut batch,
+        } => {
+            batch.validate()?;
+            let cached = modules
+                .get_or_compile(&algorithm_id, wasm_path.as_deref())
+                .await?;
+            batch.set_wasm_hash(cached.wasm_hash)?;
+            let result = batch::compute_batch(
+                &batch,
+                AlgorithmRunner::Wasm(cached.module),
+                |output_data, report| {
+                    let _ = tx.send(JobEvent::Progress {
+                        id: id.to_string(),
+                        nonce: output_data.nonce,
+                        is_solution: report.is_solution(),
+                    });
+                },
+            )
+            .await?;
+            Ok(serde_json::to_value(result)?)
+        }
+    }
+}
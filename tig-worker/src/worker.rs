@@ -1,9 +1,53 @@
-use anyhow::{anyhow, Result};
-use bincode;
-use tig_challenges::*;
+use crate::{
+    engine::{compile_module, EngineInstance, ExecutionEngine},
+    error::{WorkerError, WorkerResult},
+    native::compute_solution_native,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use tig_challenges::{challenge_registry, ChallengeEntry};
+pub use tig_challenges::{ExportFormat, ExportedFile, SolutionQuality, VerificationError};
 pub use tig_structs::core::{BenchmarkSettings, OutputData, Solution};
 use tig_utils::decompress_obj;
-use wasmi::{Config, Engine, Linker, Module, Store, StoreLimitsBuilder};
+use wasmi::Module;
+
+/// How an algorithm is run when computing nonces
+#[derive(Clone)]
+pub enum AlgorithmRunner {
+    Wasm(Arc<Module>),
+    /// Runs the algorithm with an alternative engine. Output data is only reproducible by other
+    /// parties if the engine conforms to wasmi
+    Engine(Arc<dyn ExecutionEngine>),
+    /// Runs `settings.algorithm_id` with `compute_solution_native`. Fuel and memory limits are
+    /// not enforced
+    Native,
+}
+
+impl AlgorithmRunner {
+    pub fn compute_solution(
+        &self,
+        settings: &BenchmarkSettings,
+        rand_hash: &String,
+        nonce: u64,
+        max_memory: u64,
+        max_fuel: u64,
+    ) -> WorkerResult<(OutputData, Option<WorkerError>)> {
+        match self {
+            AlgorithmRunner::Wasm(module) => compute_solution_with_module(
+                settings, rand_hash, nonce, module, max_memory, max_fuel,
+            ),
+            AlgorithmRunner::Engine(engine) => compute_solution_with_engine(
+                settings,
+                rand_hash,
+                nonce,
+                engine.as_ref(),
+                max_memory,
+                max_fuel,
+            ),
+            AlgorithmRunner::Native => compute_solution_native(settings, rand_hash, nonce),
+        }
+    }
+}
 
 pub fn compute_solution(
     settings: &BenchmarkSettings,
@@ -12,126 +56,48 @@ pub fn compute_solution(
     wasm: &[u8],
     max_memory: u64,
     max_fuel: u64,
-) -> Result<(OutputData, Option<String>)> {
-    let seed = settings.calc_seed(rand_hash, nonce);
-    let serialized_challenge = match settings.challenge_id.as_str() {
-        "c001" => {
-            let challenge =
-                satisfiability::Challenge::generate_instance_from_vec(seed, &settings.difficulty)
-                    .unwrap();
-            bincode::serialize(&challenge).unwrap()
-        }
-        "c002" => {
-            let challenge =
-                vehicle_routing::Challenge::generate_instance_from_vec(seed, &settings.difficulty)
-                    .unwrap();
-            bincode::serialize(&challenge).unwrap()
-        }
-        "c003" => {
-            let challenge =
-                knapsack::Challenge::generate_instance_from_vec(seed, &settings.difficulty)
-                    .unwrap();
-            bincode::serialize(&challenge).unwrap()
-        }
-        "c004" => {
-            let challenge =
-                vector_search::Challenge::generate_instance_from_vec(seed, &settings.difficulty)
-                    .unwrap();
-            bincode::serialize(&challenge).unwrap()
-        }
-        _ => panic!("Unknown challenge"),
-    };
+) -> WorkerResult<(OutputData, Option<WorkerError>)> {
+    let module = compile_module(wasm)?;
+    compute_solution_with_module(settings, rand_hash, nonce, &module, max_memory, max_fuel)
+}
 
-    let mut config = Config::default();
-    config.update_runtime_signature(true);
-    config.consume_fuel(true);
-
-    let limits = StoreLimitsBuilder::new()
-        .memory_size(max_memory as usize)
-        .memories(1)
-        .trap_on_grow_failure(true)
-        .build();
-    // Setup instance of wasm module
-    let engine = Engine::new(&config);
-    let mut store = Store::new(&engine, limits);
-    store.limiter(|lim| lim);
-    store.set_fuel(max_fuel).unwrap();
-    let linker = Linker::new(&engine);
-    let module = Module::new(store.engine(), wasm).expect("Failed to instantiate module");
-
-    let instance = &linker
-        .instantiate(&mut store, &module)
-        .expect("Failed to instantiate linker")
-        .start(&mut store)
-        .expect("Failed to start module");
-
-    let memory = instance
-        .get_memory(&store, "memory")
-        .expect("Failed to find memory");
-
-    // Run algorithm
-    let init = instance
-        .get_typed_func::<u32, u32>(&store, "init")
-        .expect("Failed to find `init` function");
-    let entry_point = instance
-        .get_typed_func::<(u32, u32), u32>(&store, "entry_point")
-        .expect("Failed to find `entry_point` function");
+pub fn compute_solution_with_module(
+    settings: &BenchmarkSettings,
+    rand_hash: &String,
+    nonce: u64,
+    module: &Module,
+    max_memory: u64,
+    max_fuel: u64,
+) -> WorkerResult<(OutputData, Option<WorkerError>)> {
+    compute_solution_with_engine(settings, rand_hash, nonce, module, max_memory, max_fuel)
+}
 
-    let challenge_len = serialized_challenge.len() as u32;
-    let challenge_ptr: u32 = init.call(&mut store, challenge_len).unwrap();
-    memory
-        .write(&mut store, challenge_ptr as usize, &serialized_challenge)
-        .expect("Failed to write serialized challenge to `memory`");
-
-    let mut solution = Solution::new();
-    let mut err_msg = None;
-    let solution_ptr = match entry_point.call(&mut store, (challenge_ptr, challenge_len)) {
-        Ok(solution_ptr) => Some(solution_ptr),
-        Err(e) => {
-            err_msg = Some(format!("Error executing algorithm: {:?}", e));
-            None
-        }
-    };
+pub fn compute_solution_with_engine(
+    settings: &BenchmarkSettings,
+    rand_hash: &String,
+    nonce: u64,
+    engine: &dyn ExecutionEngine,
+    max_memory: u64,
+    max_fuel: u64,
+) -> WorkerResult<(OutputData, Option<WorkerError>)> {
+    let seed = settings.calc_seed(rand_hash, nonce);
+    let serialized_challenge = get_challenge(settings)?
+        .generate_instance(seed, &settings.difficulty)
+        .map_err(invalid_instance)?
+        .to_bytes();
 
-    let solution_len = match solution_ptr {
-        Some(solution_ptr) => {
-            let mut solution_len_bytes = [0u8; 4];
-            match memory.read(&store, solution_ptr as usize, &mut solution_len_bytes) {
-                Ok(_) => u32::from_le_bytes(solution_len_bytes),
-                Err(e) => {
-                    err_msg = Some(format!(
-                        "Error reading solution length from memory: {:?}",
-                        e
-                    ));
-                    0
-                }
-            }
-        }
-        None => 0,
-    };
+    let mut instance = engine.instantiate(max_memory, max_fuel)?;
 
-    if solution_len > 0 {
-        let mut serialized_solution = vec![0u8; solution_len as usize];
-        match memory.read(
-            &store,
-            (solution_ptr.unwrap() + 4) as usize,
-            &mut serialized_solution,
-        ) {
-            Ok(_) => match decompress_obj(&serialized_solution) {
-                Ok(s) => solution = s,
-                Err(e) => {
-                    err_msg = Some(format!("Error decompressing solution: {:?}", e));
-                }
-            },
-            Err(e) => {
-                err_msg = Some(format!("Error reading solution from memory: {:?}", e));
-            }
-        }
-    }
+    // Run algorithm. A failure at any step is the nonce's error, and the output data is what
+    // the instance computed up to that point
+    let (solution, err_msg) = match run_instance(instance.as_mut(), &serialized_challenge) {
+        Ok(solution) => (solution, None),
+        Err(e) => (Solution::new(), Some(e)),
+    };
 
     // Get runtime signature
-    let runtime_signature = store.get_runtime_signature();
-    let fuel_consumed = max_fuel - store.get_fuel().unwrap();
+    let runtime_signature = instance.runtime_signature();
+    let fuel_consumed = instance.fuel_consumed();
     let solution_data = OutputData {
         nonce,
         runtime_signature,
@@ -141,58 +107,103 @@ pub fn compute_solution(
     Ok((solution_data, err_msg))
 }
 
+fn run_instance(
+    instance: &mut dyn EngineInstance,
+    serialized_challenge: &[u8],
+) -> WorkerResult<Solution> {
+    let challenge_len = serialized_challenge.len() as u32;
+    let challenge_ptr = instance.init(challenge_len)?;
+    instance
+        .write_memory(challenge_ptr as usize, serialized_challenge)
+        .map_err(|e| WorkerError::AlgorithmTrap {
+            msg: format!("Failed to write serialized challenge to `memory`: {}", e),
+        })?;
+
+    let solution_ptr = instance.entry_point(challenge_ptr, challenge_len)?;
+    let mut solution_len_bytes = [0u8; 4];
+    instance
+        .read_memory(solution_ptr as usize, &mut solution_len_bytes)
+        .map_err(|e| WorkerError::AlgorithmTrap {
+            msg: format!("Error reading solution length from memory: {}", e),
+        })?;
+    let solution_len = u32::from_le_bytes(solution_len_bytes);
+    if solution_len == 0 {
+        return Ok(Solution::new());
+    }
+
+    let mut serialized_solution = vec![0u8; solution_len as usize];
+    instance
+        .read_memory((solution_ptr + 4) as usize, &mut serialized_solution)
+        .map_err(|e| WorkerError::AlgorithmTrap {
+            msg: format!("Error reading solution from memory: {}", e),
+        })?;
+    decompress_obj(&serialized_solution).map_err(|e| WorkerError::InvalidSolution {
+        reason: VerificationError::InvalidFormat {
+            msg: format!("Error decompressing solution: {:?}", e),
+        },
+    })
+}
+
 pub fn verify_solution(
     settings: &BenchmarkSettings,
     rand_hash: &String,
     nonce: u64,
     solution: &Solution,
-) -> Result<()> {
+) -> WorkerResult<()> {
     let seed = settings.calc_seed(rand_hash, nonce);
-    match settings.challenge_id.as_str() {
-        "c001" => {
-            let challenge =
-                satisfiability::Challenge::generate_instance_from_vec(seed, &settings.difficulty)
-                    .expect("Failed to generate satisfiability instance");
-            match satisfiability::Solution::try_from(solution.clone()) {
-                Ok(solution) => challenge.verify_solution(&solution),
-                Err(_) => Err(anyhow!(
-                    "Invalid solution. Cannot convert to satisfiability::Solution"
-                )),
-            }
-        }
-        "c002" => {
-            let challenge =
-                vehicle_routing::Challenge::generate_instance_from_vec(seed, &settings.difficulty)
-                    .expect("Failed to generate vehicle_routing instance");
-            match vehicle_routing::Solution::try_from(solution.clone()) {
-                Ok(solution) => challenge.verify_solution(&solution),
-                Err(_) => Err(anyhow!(
-                    "Invalid solution. Cannot convert to vehicle_routing::Solution"
-                )),
-            }
-        }
-        "c003" => {
-            let challenge =
-                knapsack::Challenge::generate_instance_from_vec(seed, &settings.difficulty)
-                    .expect("Failed to generate knapsack instance");
-            match knapsack::Solution::try_from(solution.clone()) {
-                Ok(solution) => challenge.verify_solution(&solution),
-                Err(_) => Err(anyhow!(
-                    "Invalid solution. Cannot convert to knapsack::Solution"
-                )),
-            }
-        }
-        "c004" => {
-            let challenge =
-                vector_search::Challenge::generate_instance_from_vec(seed, &settings.difficulty)
-                    .expect("Failed to generate vector_search instance");
-            match vector_search::Solution::try_from(solution.clone()) {
-                Ok(solution) => challenge.verify_solution(&solution),
-                Err(_) => Err(anyhow!(
-                    "Invalid solution. Cannot convert to vector_search::Solution"
-                )),
-            }
-        }
-        _ => panic!("Unknown challenge"),
+    get_challenge(settings)?
+        .generate_instance(seed, &settings.difficulty)
+        .map_err(invalid_instance)?
+        .verify_solution(&Value::Object(solution.clone()))
+        .map_err(invalid_solution)
+}
+
+/// Grades a solution against the threshold it has to reach to be valid
+pub fn evaluate_solution(
+    settings: &BenchmarkSettings,
+    rand_hash: &String,
+    nonce: u64,
+    solution: &Solution,
+) -> WorkerResult<SolutionQuality> {
+    let seed = settings.calc_seed(rand_hash, nonce);
+    get_challenge(settings)?
+        .generate_instance(seed, &settings.difficulty)
+        .map_err(invalid_instance)?
+        .evaluate_solution(&Value::Object(solution.clone()))
+        .map_err(invalid_solution)
+}
+
+/// Writes the instance for a nonce in a standard format for external solvers
+pub fn export_instance(
+    settings: &BenchmarkSettings,
+    rand_hash: &String,
+    nonce: u64,
+    format: ExportFormat,
+) -> WorkerResult<Vec<ExportedFile>> {
+    let seed = settings.calc_seed(rand_hash, nonce);
+    get_challenge(settings)?
+        .generate_instance(seed, &settings.difficulty)
+        .map_err(invalid_instance)?
+        .export(format)
+        .map_err(|e| WorkerError::InvalidInput { msg: e.to_string() })
+}
+
+fn get_challenge(settings: &BenchmarkSettings) -> WorkerResult<&'static dyn ChallengeEntry> {
+    challenge_registry()
+        .get(&settings.challenge_id)
+        .ok_or_else(|| WorkerError::UnknownChallenge {
+            challenge_id: settings.challenge_id.clone(),
+        })
+}
+
+fn invalid_instance(e: anyhow::Error) -> WorkerError {
+    WorkerError::InvalidSettings {
+        msg: format!("Failed to generate instance: {}", e),
+    }
+}
+
+fn invalid_solution(e: anyhow::Error) -> WorkerError {
+    WorkerError::InvalidSolution {
+        reason: VerificationError::from_anyhow(&e),
     }
 }
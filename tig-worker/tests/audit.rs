@@ -0,0 +1,116 @@
+use std::{path::PathBuf, sync::Arc};
+use tig_utils::{dejsonify, MerkleHash};
+use tig_worker::{
+    compile_module, compute_batch, AlgorithmRunner, BatchAudit, BatchJob, BatchProofs, BatchResult,
+    BenchmarkSettings,
+};
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_settings() -> BenchmarkSettings {
+    dejsonify(
+        r#"{"challenge_id":"c002","difficulty":[40,250],"algorithm_id":"","player_id":"","block_id":""}"#,
+    )
+    .unwrap()
+}
+
+async fn test_batch() -> BatchResult {
+    let job = BatchJob {
+        settings: test_settings(),
+        rand_hash: "random_string".to_string(),
+        start_nonce: 8,
+        num_nonces: 4,
+        batch_size: 4,
+        max_memory: 1_000_000_000,
+        max_fuel: 2_000_000_000,
+        sampled_nonces: vec![9, 11],
+        num_workers: 2,
+        artifact_path: None,
+        report_path: None,
+        checkpoint_dir: None,
+        wasm_hash: None,
+    };
+    let module = Arc::new(compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap());
+    compute_batch(&job, AlgorithmRunner::Wasm(module), |_, _| {})
+        .await
+        .unwrap()
+}
+
+fn test_audit(merkle_root: MerkleHash) -> BatchAudit {
+    BatchAudit {
+        settings: test_settings(),
+        rand_hash: "random_string".to_string(),
+        merkle_root,
+        start_nonce: 8,
+        batch_size: Some(4),
+        max_memory: 1_000_000_000,
+        max_fuel: 2_000_000_000,
+    }
+}
+
+#[tokio::test]
+async fn test_verify_batch_accepts_honest_proofs() {
+    let result = test_batch().await;
+    let proofs = BatchProofs {
+        merkle_proofs: result.merkle_proofs.clone(),
+        solution_nonces: Some(result.solution_nonces.clone()),
+    };
+    let module = compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap();
+    let audits = test_audit(result.merkle_root).verify(&proofs, Some(&module));
+    assert_eq!(audits.len(), 2);
+    for audit in audits {
+        assert!(audit.is_ok(), "{:?}", audit.errors);
+        assert!(audit.valid_merkle_branch);
+        assert_eq!(audit.matches_recomputed, Some(true));
+    }
+}
+
+#[tokio::test]
+async fn test_verify_batch_rejects_tampered_proofs() {
+    let result = test_batch().await;
+    let module = compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap();
+
+    let mut merkle_proofs = result.merkle_proofs.clone();
+    merkle_proofs[0].leaf.fuel_consumed += 1;
+    let proofs = BatchProofs {
+        merkle_proofs,
+        solution_nonces: Some(result.solution_nonces.clone()),
+    };
+    let audits = test_audit(result.merkle_root.clone()).verify(&proofs, Some(&module));
+    assert!(!audits[0].valid_merkle_branch);
+    assert_eq!(audits[0].matches_recomputed, Some(false));
+    assert!(audits[1].is_ok());
+
+    // wrong root fails every branch, without recomputing
+    let proofs = BatchProofs {
+        merkle_proofs: result.merkle_proofs.clone(),
+        solution_nonces: Some(result.solution_nonces.clone()),
+    };
+    let audits = test_audit(MerkleHash::null()).verify(&proofs, None);
+    assert!(audits
+        .iter()
+        .all(|a| !a.valid_merkle_branch && a.matches_recomputed.is_none()));
+
+    // a batch too small for the branches is rejected
+    let mut audit = test_audit(result.merkle_root.clone());
+    audit.batch_size = Some(1);
+    assert!(audit.verify(&proofs, None).iter().all(|a| !a.is_ok()));
+}
+
+#[tokio::test]
+async fn test_verify_batch_checks_claimed_solutions() {
+    let result = test_batch().await;
+    let proofs = BatchProofs {
+        merkle_proofs: result.merkle_proofs.clone(),
+        solution_nonces: None,
+    };
+    // leaves with a non-empty solution are treated as claimed
+    for audit in test_audit(result.merkle_root).verify(&proofs, None) {
+        assert!(audit.claimed_solution);
+        assert_eq!(audit.is_ok(), audit.valid_solution);
+        assert_eq!(audit.valid_solution, audit.invalid_solution_reason.is_none());
+    }
+}
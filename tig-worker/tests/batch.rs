@@ -0,0 +1,60 @@
+use serde_json::json;
+use tig_utils::MerkleHash;
+use tig_worker::{BatchArtifact, OutputData};
+
+fn test_outputs(start_nonce: u64, num_nonces: u64) -> Vec<OutputData> {
+    (start_nonce..start_nonce + num_nonces)
+        .map(|nonce| OutputData {
+            nonce,
+            runtime_signature: nonce * 7,
+            fuel_consumed: 100 + nonce,
+            solution: json!({ "nonce": nonce }).as_object().unwrap().clone(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_prove_from_artifact() {
+    let artifact = BatchArtifact::new(10, 8, test_outputs(10, 6), vec![11, 13]).unwrap();
+    assert_eq!(artifact.end_nonce(), 16);
+
+    let merkle_proofs = artifact.prove(&[11, 15]).unwrap();
+    assert_eq!(merkle_proofs.len(), 2);
+    for merkle_proof in merkle_proofs {
+        let idx = (merkle_proof.leaf.nonce - 10) as usize;
+        let hash = MerkleHash::from(merkle_proof.leaf.clone());
+        assert_eq!(
+            merkle_proof
+                .branch
+                .unwrap()
+                .calc_merkle_root(&hash, idx)
+                .unwrap(),
+            artifact.merkle_root()
+        );
+    }
+
+    assert!(artifact.prove(&[9]).is_err());
+    assert!(artifact.prove(&[16]).is_err());
+}
+
+#[test]
+fn test_artifact_round_trip() {
+    let artifact = BatchArtifact::new(0, 4, test_outputs(0, 4), vec![2]).unwrap();
+    let path = std::env::temp_dir().join(format!("tig_worker_batch_{}.bin", std::process::id()));
+    artifact.save(&path).unwrap();
+    let loaded = BatchArtifact::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, artifact);
+    assert_eq!(
+        loaded.prove(&[0, 3]).unwrap(),
+        artifact.prove(&[0, 3]).unwrap()
+    );
+}
+
+#[test]
+fn test_prove_rejects_tampered_output() {
+    let mut artifact = BatchArtifact::new(0, 4, test_outputs(0, 4), vec![]).unwrap();
+    artifact.outputs[1].runtime_signature += 1;
+    assert!(artifact.prove(&[0]).is_ok());
+    assert!(artifact.prove(&[1]).is_err());
+}
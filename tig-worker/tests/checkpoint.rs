@@ -0,0 +1,105 @@
+use std::{fs, path::PathBuf, sync::Arc};
+use tig_utils::dejsonify;
+use tig_worker::{compile_module, compute_batch, AlgorithmRunner, BatchJob, WorkerError};
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_runner() -> AlgorithmRunner {
+    AlgorithmRunner::Wasm(Arc::new(
+        compile_module(&fs::read(wasm_path()).unwrap()).unwrap(),
+    ))
+}
+
+fn test_job(checkpoint_dir: Option<PathBuf>) -> BatchJob {
+    BatchJob {
+        settings: dejsonify(
+            r#"{"challenge_id":"c002","difficulty":[40,250],"algorithm_id":"","player_id":"","block_id":""}"#,
+        )
+        .unwrap(),
+        rand_hash: "random_string".to_string(),
+        start_nonce: 0,
+        num_nonces: 6,
+        batch_size: 8,
+        max_memory: 1_000_000_000,
+        max_fuel: 2_000_000_000,
+        sampled_nonces: vec![4],
+        num_workers: 2,
+        artifact_path: None,
+        report_path: None,
+        checkpoint_dir,
+        wasm_hash: Some("wasm_hash".to_string()),
+    }
+}
+
+fn checkpoint_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "tig-worker-checkpoint-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn test_compute_batch_resumes_from_checkpoint() {
+    let expected = compute_batch(&test_job(None), test_runner(), |_, _| {})
+        .await
+        .unwrap();
+
+    let dir = checkpoint_dir("resume");
+    let job = test_job(Some(dir.clone()));
+    let result = compute_batch(&job, test_runner(), |_, _| {}).await.unwrap();
+    assert_eq!(result, expected);
+
+    // simulate a run killed after 3 nonces, mid-way through writing the 4th
+    let nonces_path = dir.join("nonces.jsonl");
+    let saved = fs::read_to_string(&nonces_path).unwrap();
+    let lines: Vec<&str> = saved.lines().collect();
+    assert_eq!(lines.len(), 6);
+    fs::write(
+        &nonces_path,
+        format!("{}\n{}", lines[..3].join("\n"), &lines[3][..10]),
+    )
+    .unwrap();
+
+    let mut computed = Vec::new();
+    let result = compute_batch(&job, test_runner(), |output_data, _| {
+        computed.push(output_data.nonce)
+    })
+    .await
+    .unwrap();
+    assert_eq!(computed.len(), 3);
+    assert_eq!(result, expected);
+
+    // a finished checkpoint computes nothing
+    let mut num_computed = 0;
+    let result = compute_batch(&job, test_runner(), |_, _| num_computed += 1)
+        .await
+        .unwrap();
+    assert_eq!(num_computed, 0);
+    assert_eq!(result, expected);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_checkpoint_rejects_different_batch() {
+    let dir = checkpoint_dir("reject");
+    compute_batch(&test_job(Some(dir.clone())), test_runner(), |_, _| {})
+        .await
+        .unwrap();
+
+    let mut job = test_job(Some(dir.clone()));
+    job.wasm_hash = Some("other_wasm_hash".to_string());
+    let err = compute_batch(&job, test_runner(), |_, _| {})
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<WorkerError>(),
+        Some(WorkerError::InvalidInput { .. })
+    ));
+    fs::remove_dir_all(&dir).unwrap();
+}
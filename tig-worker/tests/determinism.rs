@@ -0,0 +1,68 @@
+use std::{path::PathBuf, sync::Arc};
+use tig_utils::dejsonify;
+use tig_worker::{
+    check_determinism, compile_module, AlgorithmRunner, BenchmarkSettings, DeterminismCheck,
+    DeterminismMismatch, NonceRun, OutputData,
+};
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_settings() -> BenchmarkSettings {
+    dejsonify(
+        r#"{"challenge_id":"c002","difficulty":[40,250],"algorithm_id":"","player_id":"","block_id":""}"#,
+    )
+    .unwrap()
+}
+
+fn test_run(runtime_signature: u64, error: Option<&str>) -> NonceRun {
+    NonceRun {
+        output_data: Some(OutputData {
+            nonce: 0,
+            runtime_signature,
+            fuel_consumed: 100,
+            solution: Default::default(),
+        }),
+        error: error.map(|e| e.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_check_determinism_wasm() {
+    let check = DeterminismCheck {
+        settings: test_settings(),
+        rand_hash: "random_string".to_string(),
+        start_nonce: 5,
+        num_nonces: 4,
+        max_memory: 1_000_000_000,
+        max_fuel: 2_000_000_000,
+        num_workers: 2,
+    };
+    let module = Arc::new(compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap());
+    let mismatches = check_determinism(&check, AlgorithmRunner::Wasm(module))
+        .await
+        .unwrap();
+    assert_eq!(mismatches, vec![]);
+}
+
+#[test]
+fn test_compare_runs() {
+    assert_eq!(
+        DeterminismMismatch::compare(0, test_run(1, None), test_run(1, None)),
+        None
+    );
+
+    let mismatch =
+        DeterminismMismatch::compare(3, test_run(1, None), test_run(2, Some("trap"))).unwrap();
+    assert_eq!(mismatch.nonce, 3);
+    assert_eq!(mismatch.fields, vec!["runtime_signature", "error"]);
+
+    let failed = NonceRun {
+        output_data: None,
+        error: Some("trap".to_string()),
+    };
+    let mismatch = DeterminismMismatch::compare(0, test_run(1, None), failed).unwrap();
+    assert_eq!(mismatch.fields, vec!["output_data", "error"]);
+}
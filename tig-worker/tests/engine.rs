@@ -0,0 +1,151 @@
+use std::{path::PathBuf, sync::Arc};
+use tig_utils::dejsonify;
+use tig_worker::{
+    check_conformance, compile_module, compute_batch, AlgorithmRunner, BatchJob, BenchmarkSettings,
+    EngineInstance, ExecutionEngine, WorkerResult,
+};
+use wasmi::Module;
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_settings() -> BenchmarkSettings {
+    dejsonify(
+        r#"{"challenge_id":"c002","difficulty":[40,250],"algorithm_id":"","player_id":"","block_id":""}"#,
+    )
+    .unwrap()
+}
+
+fn test_module() -> Module {
+    compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap()
+}
+
+// wraps wasmi to stand in for an alternative engine
+struct TestEngine {
+    module: Module,
+    runtime_signature: bool,
+    fuel_offset: u64,
+}
+
+struct TestInstance {
+    inner: Box<dyn EngineInstance>,
+    runtime_signature: bool,
+    fuel_offset: u64,
+}
+
+impl ExecutionEngine for TestEngine {
+    fn instantiate(&self, max_memory: u64, max_fuel: u64) -> WorkerResult<Box<dyn EngineInstance>> {
+        Ok(Box::new(TestInstance {
+            inner: self.module.instantiate(max_memory, max_fuel)?,
+            runtime_signature: self.runtime_signature,
+            fuel_offset: self.fuel_offset,
+        }))
+    }
+
+    fn computes_runtime_signature(&self) -> bool {
+        self.runtime_signature
+    }
+}
+
+impl EngineInstance for TestInstance {
+    fn init(&mut self, challenge_len: u32) -> WorkerResult<u32> {
+        self.inner.init(challenge_len)
+    }
+
+    fn entry_point(&mut self, challenge_ptr: u32, challenge_len: u32) -> WorkerResult<u32> {
+        self.inner.entry_point(challenge_ptr, challenge_len)
+    }
+
+    fn read_memory(&self, offset: usize, buffer: &mut [u8]) -> anyhow::Result<()> {
+        self.inner.read_memory(offset, buffer)
+    }
+
+    fn write_memory(&mut self, offset: usize, data: &[u8]) -> anyhow::Result<()> {
+        self.inner.write_memory(offset, data)
+    }
+
+    fn fuel_consumed(&self) -> u64 {
+        self.inner.fuel_consumed() + self.fuel_offset
+    }
+
+    fn runtime_signature(&self) -> u64 {
+        match self.runtime_signature {
+            true => self.inner.runtime_signature(),
+            false => 0,
+        }
+    }
+}
+
+#[test]
+fn test_conformance() {
+    let reference = test_module();
+    let rand_hash = "random_string".to_string();
+    let check = |engine: &dyn ExecutionEngine| {
+        check_conformance(
+            engine,
+            &reference,
+            &test_settings(),
+            &rand_hash,
+            0..3,
+            1_000_000_000,
+            2_000_000_000,
+        )
+    };
+
+    // wasmi conforms to itself
+    assert_eq!(check(&test_module()), vec![]);
+
+    // runtime signature is ignored if the engine does not compute it
+    let engine = TestEngine {
+        module: test_module(),
+        runtime_signature: false,
+        fuel_offset: 0,
+    };
+    assert_eq!(check(&engine), vec![]);
+
+    let engine = TestEngine {
+        module: test_module(),
+        runtime_signature: true,
+        fuel_offset: 1,
+    };
+    let mismatches = check(&engine);
+    assert_eq!(mismatches.len(), 3);
+    assert!(mismatches.iter().all(|m| m.fields == vec!["fuel_consumed"]));
+}
+
+#[tokio::test]
+async fn test_compute_batch_with_engine() {
+    let job = BatchJob {
+        settings: test_settings(),
+        rand_hash: "random_string".to_string(),
+        start_nonce: 0,
+        num_nonces: 4,
+        batch_size: 4,
+        max_memory: 1_000_000_000,
+        max_fuel: 2_000_000_000,
+        sampled_nonces: vec![1],
+        num_workers: 2,
+        artifact_path: None,
+        report_path: None,
+        checkpoint_dir: None,
+        wasm_hash: None,
+    };
+    let wasm = compute_batch(
+        &job,
+        AlgorithmRunner::Wasm(Arc::new(test_module())),
+        |_, _| {},
+    )
+    .await
+    .unwrap();
+    let engine = TestEngine {
+        module: test_module(),
+        runtime_signature: true,
+        fuel_offset: 0,
+    };
+    let result = compute_batch(&job, AlgorithmRunner::Engine(Arc::new(engine)), |_, _| {})
+        .await
+        .unwrap();
+    assert_eq!(result, wasm);
+}
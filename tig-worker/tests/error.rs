@@ -0,0 +1,130 @@
+use serde_json::Value;
+use std::{path::PathBuf, process::Command};
+use tig_utils::dejsonify;
+use tig_worker::{
+    compute_solution, verify_solution, BenchmarkSettings, Solution, VerificationError, WorkerError,
+};
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_settings(challenge_id: &str, difficulty: &str) -> BenchmarkSettings {
+    dejsonify(&format!(
+        r#"{{"challenge_id":"{}","difficulty":{},"algorithm_id":"","player_id":"","block_id":""}}"#,
+        challenge_id, difficulty
+    ))
+    .unwrap()
+}
+
+fn run(settings: &BenchmarkSettings, wasm: &[u8], max_fuel: u64) -> WorkerError {
+    match compute_solution(
+        settings,
+        &"random_string".to_string(),
+        0,
+        wasm,
+        1_000_000_000,
+        max_fuel,
+    ) {
+        Err(e) => e,
+        Ok((_, Some(e))) => e,
+        Ok((_, None)) => panic!("Expected an error"),
+    }
+}
+
+#[test]
+fn test_compute_solution_errors() {
+    let wasm = std::fs::read(wasm_path()).unwrap();
+    let settings = test_settings("c002", "[40,250]");
+
+    assert_eq!(
+        run(&test_settings("c999", "[40,250]"), &wasm, 2_000_000_000),
+        WorkerError::UnknownChallenge {
+            challenge_id: "c999".to_string()
+        }
+    );
+    assert!(matches!(
+        run(&test_settings("c002", "[40]"), &wasm, 2_000_000_000),
+        WorkerError::InvalidSettings { .. }
+    ));
+    assert!(matches!(
+        run(&settings, b"not wasm", 2_000_000_000),
+        WorkerError::InvalidWasm { .. }
+    ));
+    assert_eq!(run(&settings, &wasm, 1_000), WorkerError::FuelExhausted);
+}
+
+#[test]
+fn test_verify_solution_errors() {
+    let rand_hash = "random_string".to_string();
+    let e = verify_solution(
+        &test_settings("c002", "[40,250]"),
+        &rand_hash,
+        0,
+        &Solution::new(),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        e,
+        WorkerError::InvalidSolution {
+            reason: VerificationError::InvalidFormat { .. }
+        }
+    ));
+    assert_eq!(e.exit_code(), 1);
+    assert!(!e.is_retryable());
+
+    let solution = serde_json::from_str(r#"{"routes":[[0,1,0]]}"#).unwrap();
+    let e =
+        verify_solution(&test_settings("c002", "[40,250]"), &rand_hash, 0, &solution).unwrap_err();
+    assert_eq!(
+        e,
+        WorkerError::InvalidSolution {
+            reason: VerificationError::UnvisitedNode { node: 2 }
+        }
+    );
+    assert_eq!(
+        e.to_json()["reason"],
+        serde_json::json!({"reason": "unvisited_node", "node": 2})
+    );
+}
+
+#[test]
+fn test_cli_exit_codes_and_json_errors() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tig-worker"))
+        .args([
+            "compute_solution",
+            r#"{"challenge_id":"c002","difficulty":[40,250],"algorithm_id":"","player_id":"","block_id":""}"#,
+            "random_string",
+            "0",
+            wasm_path().to_str().unwrap(),
+            "--fuel",
+            "1000",
+            "--json-errors",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(WorkerError::FuelExhausted.exit_code())
+    );
+    let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"], "fuel_exhausted");
+    assert_eq!(error["retryable"], true);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_tig-worker"))
+        .args([
+            "--json-errors",
+            "verify_solution",
+            "not json",
+            "random_string",
+            "0",
+            "{}",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(10));
+    let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"], "invalid_settings");
+    assert_eq!(error["retryable"], false);
+}
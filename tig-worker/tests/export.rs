@@ -0,0 +1,67 @@
+use std::process::Command;
+use tig_utils::dejsonify;
+use tig_worker::{export_instance, BenchmarkSettings, ExportFormat, WorkerError};
+
+fn test_settings(challenge_id: &str, difficulty: &str) -> BenchmarkSettings {
+    dejsonify(&format!(
+        r#"{{"challenge_id":"{}","difficulty":{},"algorithm_id":"","player_id":"","block_id":""}}"#,
+        challenge_id, difficulty
+    ))
+    .unwrap()
+}
+
+fn run(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_tig-worker"))
+        .arg("export_instance")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_export_instance() {
+    let rand_hash = "random_string".to_string();
+    let settings = test_settings("c001", "[50,300]");
+    let files = export_instance(&settings, &rand_hash, 3, ExportFormat::Dimacs).unwrap();
+    assert_eq!(files.len(), 1);
+    // a different nonce is a different instance
+    let other = export_instance(&settings, &rand_hash, 4, ExportFormat::Dimacs).unwrap();
+    assert_ne!(files[0].data, other[0].data);
+
+    assert!(matches!(
+        export_instance(&settings, &rand_hash, 3, ExportFormat::Qkp),
+        Err(WorkerError::InvalidInput { .. })
+    ));
+}
+
+#[test]
+fn test_cli_export_instance() {
+    let settings = r#"{"challenge_id":"c003","difficulty":[50,10],"algorithm_id":"","player_id":"","block_id":""}"#;
+
+    let output = run(&[settings, "random_string", "0", "--format", "qkp"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().nth(1), Some("50"));
+
+    let dir = std::env::temp_dir().join(format!("tig-worker-export-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let prefix = dir.join("instance");
+    let output = run(&[
+        settings,
+        "random_string",
+        "0",
+        "--format",
+        "qkp",
+        "--output",
+        prefix.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(dir.join("instance.qkp")).unwrap(),
+        stdout
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let output = run(&[settings, "random_string", "0", "--format", "dimacs"]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,180 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use tig_utils::dejsonify;
+use tig_worker::{
+    compile_module, compute_batch, compute_many, AlgorithmRunner, BatchJob, Manifest, ManifestJob,
+    WorkerError,
+};
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_job(id: &str, challenge_id: &str, rand_hash: &str, weight: u32) -> ManifestJob {
+    ManifestJob {
+        id: id.to_string(),
+        wasm_path: Some(wasm_path()),
+        weight,
+        batch: BatchJob {
+            settings: dejsonify(&format!(
+                r#"{{"challenge_id":"{}","difficulty":[40,250],"algorithm_id":"","player_id":"","block_id":""}}"#,
+                challenge_id
+            ))
+            .unwrap(),
+            rand_hash: rand_hash.to_string(),
+            start_nonce: 3,
+            num_nonces: 5,
+            batch_size: 8,
+            max_memory: 1_000_000_000,
+            max_fuel: 2_000_000_000,
+            sampled_nonces: vec![4],
+            num_workers: 1,
+            artifact_path: None,
+            report_path: None,
+            checkpoint_dir: None,
+            wasm_hash: None,
+        },
+    }
+}
+
+fn test_runner() -> AlgorithmRunner {
+    AlgorithmRunner::Wasm(Arc::new(
+        compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap(),
+    ))
+}
+
+#[tokio::test]
+async fn test_compute_many_matches_compute_batch() {
+    let manifest = Manifest {
+        num_workers: 3,
+        jobs: vec![
+            test_job("a", "c002", "random_string", 1),
+            test_job("b", "c002", "other_string", 3),
+        ],
+    };
+    let mut results = HashMap::new();
+    compute_many(
+        &manifest,
+        vec![test_runner(), test_runner()],
+        |job, result| {
+            assert!(results.insert(job.id.clone(), result.unwrap()).is_none());
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results.len(), 2);
+    for job in manifest.jobs.iter() {
+        let expected = compute_batch(&job.batch, test_runner(), |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(results[&job.id], expected);
+    }
+}
+
+#[tokio::test]
+async fn test_compute_many_isolates_failed_jobs() {
+    let manifest = Manifest {
+        num_workers: 2,
+        jobs: vec![
+            test_job("bad", "c999", "random_string", 1),
+            test_job("good", "c002", "random_string", 1),
+        ],
+    };
+    let mut results = HashMap::new();
+    compute_many(
+        &manifest,
+        vec![test_runner(), test_runner()],
+        |job, result| {
+            results.insert(job.id.clone(), result);
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(
+        results["bad"]
+            .as_ref()
+            .unwrap_err()
+            .downcast_ref::<WorkerError>(),
+        Some(WorkerError::UnknownChallenge { .. })
+    ));
+    assert!(results["good"].is_ok());
+
+    // ids must be unique
+    let manifest = Manifest {
+        num_workers: 1,
+        jobs: vec![
+            test_job("a", "c002", "random_string", 1),
+            test_job("a", "c002", "other_string", 1),
+        ],
+    };
+    assert!(manifest.validate().is_err());
+
+    // native jobs cannot be checkpointed
+    let mut job = test_job("native", "c002", "random_string", 1);
+    job.wasm_path = None;
+    job.batch.checkpoint_dir = Some(std::env::temp_dir());
+    let manifest = Manifest {
+        num_workers: 1,
+        jobs: vec![job],
+    };
+    assert!(manifest.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_compute_many_rejects_resume_with_different_wasm() {
+    let dir =
+        std::env::temp_dir().join(format!("tig-worker-many-checkpoint-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut job = test_job("a", "c002", "random_string", 1);
+    job.batch.checkpoint_dir = Some(dir.clone());
+
+    let mut manifest = Manifest {
+        num_workers: 1,
+        jobs: vec![job.clone()],
+    };
+    let runners = manifest.load_runners().unwrap();
+    let wasm_hash = manifest.jobs[0].batch.wasm_hash.clone();
+    assert!(wasm_hash.is_some());
+    compute_many(&manifest, runners, |_, result| {
+        result.unwrap();
+    })
+    .await
+    .unwrap();
+
+    // resuming with a different wasm
+    job.wasm_path = Some(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright_super.wasm"),
+    );
+    let mut manifest = Manifest {
+        num_workers: 1,
+        jobs: vec![job.clone()],
+    };
+    let runners = manifest.load_runners().unwrap();
+    assert_ne!(manifest.jobs[0].batch.wasm_hash, wasm_hash);
+    let mut num_results = 0;
+    compute_many(&manifest, runners, |_, result| {
+        num_results += 1;
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<WorkerError>(),
+            Some(WorkerError::InvalidInput { .. })
+        ));
+    })
+    .await
+    .unwrap();
+    assert_eq!(num_results, 1);
+
+    // stating the hash of the original wasm
+    job.batch.wasm_hash = wasm_hash;
+    let mut manifest = Manifest {
+        num_workers: 1,
+        jobs: vec![job],
+    };
+    assert!(matches!(
+        manifest.load_runners(),
+        Err(WorkerError::InvalidInput { .. })
+    ));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
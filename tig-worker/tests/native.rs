@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+use tig_utils::dejsonify;
+use tig_worker::{
+    compute_batch, compute_solution, compute_solution_native, AlgorithmRunner, BatchJob,
+    BenchmarkSettings, WorkerError, NATIVE_ALGORITHMS,
+};
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_settings(algorithm_id: &str) -> BenchmarkSettings {
+    dejsonify(&format!(
+        r#"{{"challenge_id":"c002","difficulty":[40,250],"algorithm_id":"{}","player_id":"","block_id":""}}"#,
+        algorithm_id
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_native_registry() {
+    for algorithm_id in ["c001_a001", "c002_a001", "c003_a001", "c004_a014"] {
+        assert!(NATIVE_ALGORITHMS.contains(&algorithm_id));
+    }
+    assert!(NATIVE_ALGORITHMS.iter().all(|id| id.len() == 9));
+}
+
+#[test]
+fn test_native_matches_wasm_solution() {
+    let settings = test_settings("c002_a001");
+    let rand_hash = "random_string".to_string();
+    let wasm = std::fs::read(wasm_path()).unwrap();
+    for nonce in 0..3 {
+        let (native, native_err) = compute_solution_native(&settings, &rand_hash, nonce).unwrap();
+        let (wasm, wasm_err) = compute_solution(
+            &settings,
+            &rand_hash,
+            nonce,
+            &wasm,
+            1_000_000_000,
+            2_000_000_000,
+        )
+        .unwrap();
+        assert_eq!(native_err, None);
+        assert_eq!(wasm_err, None);
+        assert_eq!(native.nonce, nonce);
+        assert_eq!(native.fuel_consumed, 0);
+        assert_eq!(native.runtime_signature, 0);
+        assert_eq!(native.solution, wasm.solution);
+    }
+}
+
+#[test]
+fn test_native_rejects_unknown_algorithm() {
+    let rand_hash = "random_string".to_string();
+    assert_eq!(
+        compute_solution_native(&test_settings("c002_a999"), &rand_hash, 0).unwrap_err(),
+        WorkerError::UnknownAlgorithm {
+            algorithm_id: "c002_a999".to_string()
+        }
+    );
+    assert!(matches!(
+        compute_solution_native(&test_settings("c001_a001"), &rand_hash, 0).unwrap_err(),
+        WorkerError::InvalidSettings { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_native_compute_batch() {
+    let job = BatchJob {
+        settings: test_settings("c002_a001"),
+        rand_hash: "random_string".to_string(),
+        start_nonce: 0,
+        num_nonces: 4,
+        batch_size: 4,
+        max_memory: 1_000_000_000,
+        max_fuel: 2_000_000_000,
+        sampled_nonces: vec![2],
+        num_workers: 2,
+        artifact_path: None,
+        report_path: None,
+        checkpoint_dir: None,
+        wasm_hash: None,
+    };
+    let mut num_nonces = 0;
+    let result = compute_batch(&job, AlgorithmRunner::Native, |output_data, _| {
+        assert_eq!(output_data.fuel_consumed, 0);
+        num_nonces += 1;
+    })
+    .await
+    .unwrap();
+    assert_eq!(num_nonces, 4);
+    assert_eq!(result.merkle_proofs.len(), 1);
+    assert!(!result.merkle_proofs[0].leaf.solution.is_empty());
+}
@@ -0,0 +1,164 @@
+use std::{path::PathBuf, sync::Arc};
+use tig_utils::dejsonify;
+use tig_worker::{
+    compile_module, compute_batch, AlgorithmRunner, BatchJob, NonceOutcome, NonceReport,
+};
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_job(max_fuel: u64, report_path: PathBuf) -> BatchJob {
+    BatchJob {
+        settings: dejsonify(
+            r#"{"challenge_id":"c002","difficulty":[40,250],"algorithm_id":"","player_id":"","block_id":""}"#,
+        )
+        .unwrap(),
+        rand_hash: "random_string".to_string(),
+        start_nonce: 5,
+        num_nonces: 3,
+        batch_size: 4,
+        max_memory: 1_000_000_000,
+        max_fuel,
+        sampled_nonces: vec![],
+        num_workers: 2,
+        artifact_path: None,
+        report_path: Some(report_path),
+        checkpoint_dir: None,
+        wasm_hash: None,
+    }
+}
+
+fn report_path(extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "tig_worker_report_{}.{}",
+        std::process::id(),
+        extension
+    ))
+}
+
+#[tokio::test]
+async fn test_compute_batch_writes_json_lines_report() {
+    let path = report_path("jsonl");
+    let module = Arc::new(compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap());
+    let mut streamed = Vec::new();
+    let result = compute_batch(
+        &test_job(2_000_000_000, path.clone()),
+        AlgorithmRunner::Wasm(module),
+        |_, report| streamed.push(report.clone()),
+    )
+    .await
+    .unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let reports = contents
+        .lines()
+        .map(|line| dejsonify::<NonceReport>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reports.iter().map(|r| r.nonce).collect::<Vec<_>>(),
+        vec![5, 6, 7]
+    );
+    streamed.sort_by_key(|r| r.nonce);
+    for (report, streamed) in reports.iter().zip(&streamed) {
+        assert_eq!(report.nonce, streamed.nonce);
+        assert_eq!(report.fuel_consumed, streamed.fuel_consumed);
+        assert_eq!(report.runtime_signature, streamed.runtime_signature);
+        assert_eq!(report.outcome, streamed.outcome);
+    }
+    for report in &reports {
+        assert!(report.fuel_consumed > 0);
+        assert_eq!(
+            report.is_solution(),
+            result.solution_nonces.contains(&report.nonce)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_compute_batch_reports_out_of_fuel_as_csv() {
+    let path = report_path("csv");
+    let module = Arc::new(compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap());
+    let result = compute_batch(
+        &test_job(100_000, path.clone()),
+        AlgorithmRunner::Wasm(module),
+        |_, _| {},
+    )
+    .await
+    .unwrap();
+    assert!(result.solution_nonces.is_empty());
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mut lines = contents.lines();
+    assert_eq!(
+        lines.next(),
+        Some("nonce,fuel_consumed,runtime_signature,outcome,elapsed_ms")
+    );
+    let rows = lines
+        .map(|line| line.split(',').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 3);
+    for (row, nonce) in rows.iter().zip(5..) {
+        assert_eq!(row[0], nonce.to_string());
+        assert!(row[1].parse::<u64>().unwrap() <= 100_000);
+        assert_eq!(row[3], "out_of_fuel");
+    }
+}
+
+#[tokio::test]
+async fn test_compute_batch_continues_after_init_failures() {
+    // not enough fuel to run `init`, so every nonce fails before `entry_point`
+    let path = report_path("init.jsonl");
+    let module = Arc::new(compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap());
+    let result = compute_batch(
+        &test_job(1, path.clone()),
+        AlgorithmRunner::Wasm(module),
+        |_, _| {},
+    )
+    .await
+    .unwrap();
+    assert!(result.solution_nonces.is_empty());
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let reports = contents
+        .lines()
+        .map(|line| dejsonify::<NonceReport>(line).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(reports.len(), 3);
+    for report in &reports {
+        assert_eq!(report.outcome, NonceOutcome::OutOfFuel);
+        assert!(report.fuel_consumed <= 1);
+    }
+}
+
+#[test]
+fn test_nonce_outcome() {
+    use tig_worker::WorkerError;
+    assert_eq!(
+        NonceOutcome::new(Some(&WorkerError::FuelExhausted), true, false),
+        NonceOutcome::OutOfFuel
+    );
+    assert_eq!(
+        NonceOutcome::new(
+            Some(&WorkerError::AlgorithmTrap {
+                msg: "unreachable".to_string()
+            }),
+            true,
+            false
+        ),
+        NonceOutcome::Trap
+    );
+    assert_eq!(
+        NonceOutcome::new(None, true, false),
+        NonceOutcome::NoSolution
+    );
+    assert_eq!(
+        NonceOutcome::new(None, false, false),
+        NonceOutcome::InvalidSolution
+    );
+    assert_eq!(NonceOutcome::new(None, false, true), NonceOutcome::Solution);
+}
@@ -0,0 +1,260 @@
+use serde_json::{json, Value};
+use std::{path::PathBuf, sync::Arc};
+use tig_utils::{dejsonify, jsonify};
+use tig_worker::{
+    compile_module, compute_batch, serve, AlgorithmRunner, BatchJob, BatchResult,
+    BenchmarkSettings, JobEvent, ModuleCache,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    net::{
+        unix::{OwnedReadHalf, OwnedWriteHalf},
+        UnixListener, UnixStream,
+    },
+};
+
+fn wasm_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../tig-algorithms/wasm/vehicle_routing/clarke_wright.wasm")
+}
+
+fn test_settings() -> BenchmarkSettings {
+    dejsonify(
+        r#"{"challenge_id":"c002","difficulty":[40,250],"algorithm_id":"c002_a001","player_id":"","block_id":""}"#,
+    )
+    .unwrap()
+}
+
+async fn start_daemon(name: &str) -> (Lines<BufReader<OwnedReadHalf>>, OwnedWriteHalf) {
+    let socket_path =
+        std::env::temp_dir().join(format!("tig_worker_{}_{}.sock", name, std::process::id()));
+    let _ = std::fs::remove_file(&socket_path);
+    let listener = UnixListener::bind(&socket_path).unwrap();
+    tokio::spawn(serve(listener, ModuleCache::default()));
+    let (reader, writer) = UnixStream::connect(&socket_path)
+        .await
+        .unwrap()
+        .into_split();
+    std::fs::remove_file(&socket_path).unwrap();
+    (BufReader::new(reader).lines(), writer)
+}
+
+async fn send(writer: &mut OwnedWriteHalf, request: Value) {
+    writer
+        .write_all(format!("{}\n", jsonify(&request)).as_bytes())
+        .await
+        .unwrap();
+}
+
+async fn next_event(lines: &mut Lines<BufReader<OwnedReadHalf>>) -> JobEvent {
+    dejsonify(&lines.next_line().await.unwrap().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_serve_compute_batch_streams_progress() {
+    let (mut lines, mut writer) = start_daemon("batch").await;
+    let job = BatchJob {
+        settings: test_settings(),
+        rand_hash: "random_string".to_string(),
+        start_nonce: 0,
+        num_nonces: 4,
+        batch_size: 4,
+        max_memory: 1_000_000_000,
+        max_fuel: 2_000_000_000,
+        sampled_nonces: vec![1, 3],
+        num_workers: 2,
+        artifact_path: None,
+        report_path: None,
+        checkpoint_dir: None,
+        wasm_hash: None,
+    };
+    let mut request = serde_json::to_value(&job).unwrap();
+    request["id"] = json!("batch");
+    request["type"] = json!("compute_batch");
+    request["algorithm_id"] = json!("c002_a001");
+    request["wasm_path"] = json!(wasm_path());
+    send(&mut writer, request).await;
+
+    let mut progress_nonces = Vec::new();
+    let mut progress_solutions = Vec::new();
+    let result = loop {
+        match next_event(&mut lines).await {
+            JobEvent::Progress {
+                id,
+                nonce,
+                is_solution,
+            } => {
+                assert_eq!(id, "batch");
+                progress_nonces.push(nonce);
+                if is_solution {
+                    progress_solutions.push(nonce);
+                }
+            }
+            JobEvent::Result { id, result } => {
+                assert_eq!(id, "batch");
+                break serde_json::from_value::<BatchResult>(result).unwrap();
+            }
+            JobEvent::Error { error, .. } => panic!("Unexpected error: {}", error),
+        }
+    };
+    progress_nonces.sort();
+    assert_eq!(progress_nonces, vec![0, 1, 2, 3]);
+    assert_eq!(result.solution_nonces, progress_solutions);
+    assert_eq!(result.merkle_proofs.len(), 2);
+
+    let module = Arc::new(compile_module(&std::fs::read(wasm_path()).unwrap()).unwrap());
+    let expected = compute_batch(&job, AlgorithmRunner::Wasm(module), |_, _| {})
+        .await
+        .unwrap();
+    assert_eq!(result.merkle_root, expected.merkle_root);
+}
+
+#[tokio::test]
+async fn test_serve_reuses_cached_module() {
+    let (mut lines, mut writer) = start_daemon("cache").await;
+    let request = json!({
+        "id": "first",
+        "type": "compute_solution",
+        "settings": test_settings(),
+        "rand_hash": "random_string",
+        "nonce": 0,
+        "algorithm_id": "c002_a001",
+        "wasm_path": wasm_path(),
+    });
+    send(&mut writer, request.clone()).await;
+    let output_data = match next_event(&mut lines).await {
+        JobEvent::Result { id, result } => {
+            assert_eq!(id, "first");
+            result["output_data"].clone()
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    };
+
+    // no wasm_path, so only succeeds if the module was cached
+    let mut request = request;
+    request["id"] = json!("second");
+    request.as_object_mut().unwrap().remove("wasm_path");
+    send(&mut writer, request).await;
+    match next_event(&mut lines).await {
+        JobEvent::Result { id, result } => {
+            assert_eq!(id, "second");
+            assert_eq!(result["output_data"], output_data);
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+
+    send(
+        &mut writer,
+        json!({
+            "id": "uncached",
+            "type": "compute_solution",
+            "settings": test_settings(),
+            "rand_hash": "random_string",
+            "nonce": 0,
+            "algorithm_id": "c002_a002",
+        }),
+    )
+    .await;
+    assert!(matches!(
+        next_event(&mut lines).await,
+        JobEvent::Error { id, error } if id == "uncached" && error["error"] == "invalid_input"
+    ));
+}
+
+#[tokio::test]
+async fn test_serve_rejects_mismatched_wasm() {
+    let (mut lines, mut writer) = start_daemon("mismatch").await;
+    let job = BatchJob {
+        settings: test_settings(),
+        rand_hash: "random_string".to_string(),
+        start_nonce: 0,
+        num_nonces: 1,
+        batch_size: 1,
+        max_memory: 1_000_000_000,
+        max_fuel: 2_000_000_000,
+        sampled_nonces: vec![],
+        num_workers: 1,
+        artifact_path: None,
+        report_path: None,
+        checkpoint_dir: None,
+        wasm_hash: Some("other_wasm_hash".to_string()),
+    };
+    let mut request = serde_json::to_value(&job).unwrap();
+    request["id"] = json!("hash");
+    request["type"] = json!("compute_batch");
+    request["algorithm_id"] = json!("c002_a001");
+    request["wasm_path"] = json!(wasm_path());
+    send(&mut writer, request.clone()).await;
+    assert!(matches!(
+        next_event(&mut lines).await,
+        JobEvent::Error { id, error } if id == "hash" && error["error"] == "invalid_input"
+    ));
+
+    // the module is now cached from `wasm_path`
+    request["id"] = json!("path");
+    request["wasm_path"] = json!(wasm_path().with_file_name("other.wasm"));
+    request.as_object_mut().unwrap().remove("wasm_hash");
+    send(&mut writer, request).await;
+    assert!(matches!(
+        next_event(&mut lines).await,
+        JobEvent::Error { id, error } if id == "path" && error["error"] == "invalid_input"
+    ));
+}
+
+#[tokio::test]
+async fn test_serve_errors_are_structured() {
+    let (mut lines, mut writer) = start_daemon("errors").await;
+    send(
+        &mut writer,
+        json!({
+            "id": "missing",
+            "type": "compute_solution",
+            "settings": test_settings(),
+            "rand_hash": "random_string",
+            "nonce": 0,
+            "algorithm_id": "c002_a001",
+            "wasm_path": wasm_path().with_file_name("missing.wasm"),
+        }),
+    )
+    .await;
+    match next_event(&mut lines).await {
+        JobEvent::Error { id, error } => {
+            assert_eq!(id, "missing");
+            assert_eq!(error["error"], json!("io"));
+            assert_eq!(error["exit_code"], json!(20));
+            assert_eq!(error["retryable"], json!(true));
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+}
+
+#[tokio::test]
+async fn test_serve_verify_solution_and_invalid_job() {
+    let (mut lines, mut writer) = start_daemon("verify").await;
+    send(
+        &mut writer,
+        json!({
+            "id": "verify",
+            "type": "verify_solution",
+            "settings": test_settings(),
+            "rand_hash": "random_string",
+            "nonce": 0,
+            "solution": { "routes": [] },
+        }),
+    )
+    .await;
+    match next_event(&mut lines).await {
+        JobEvent::Result { id, result } => {
+            assert_eq!(id, "verify");
+            assert_eq!(result["is_valid"], json!(false));
+            assert!(result["error"].is_string());
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+
+    send(&mut writer, json!({ "id": "bad", "type": "unknown_job" })).await;
+    assert!(matches!(
+        next_event(&mut lines).await,
+        JobEvent::Error { id, error } if id == "bad" && error["error"] == "invalid_input"
+    ));
+}